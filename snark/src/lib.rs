@@ -4,16 +4,39 @@ extern crate libc;
 extern crate lazy_static;
 
 use std::sync::Mutex;
+use std::ffi::CString;
 
 use bn::*;
 
 extern "C" {
     fn libsnarkwrap_init();
     fn libsnarkwrap_getcs_dummy(d: *mut libc::uint64_t, vars: *mut libc::uint64_t, inputs: *mut libc::uint64_t, omega: *mut Fr) -> *mut libc::c_void;
-    fn libsnarkwrap_getcs_file(d: *mut libc::uint64_t, vars: *mut libc::uint64_t, inputs: *mut libc::uint64_t, omega: *mut Fr) -> *mut libc::c_void;
+    fn libsnarkwrap_getcs_file(path: *const libc::c_char, d: *mut libc::uint64_t, vars: *mut libc::uint64_t, inputs: *mut libc::uint64_t, omega: *mut Fr) -> *mut libc::c_void;
     fn libsnarkwrap_dropcs(cs: *mut libc::c_void);
     fn libsnarkwrap_dropkeypair(kp: *mut libc::c_void);
-    fn libsnarkwrap_keypair_write(kp: *const libc::c_void);
+    fn libsnarkwrap_cs_new(num_inputs: libc::uint64_t, num_aux: libc::uint64_t) -> *mut libc::c_void;
+    fn libsnarkwrap_cs_add_constraint(
+        cs: *mut libc::c_void,
+        a_len: libc::uint64_t,
+        a_indexes: *const libc::uint64_t,
+        a_coeffs: *const Fr,
+        b_len: libc::uint64_t,
+        b_indexes: *const libc::uint64_t,
+        b_coeffs: *const Fr,
+        c_len: libc::uint64_t,
+        c_indexes: *const libc::uint64_t,
+        c_coeffs: *const Fr);
+    fn libsnarkwrap_cs_finish(cs: *mut libc::c_void, d: *mut libc::uint64_t, vars: *mut libc::uint64_t, inputs: *mut libc::uint64_t, omega: *mut Fr) -> *mut libc::c_void;
+    fn libsnarkwrap_cs_num_constraints(cs: *const libc::c_void) -> libc::uint64_t;
+    fn libsnarkwrap_cs_num_terms(cs: *const libc::c_void, constraint: libc::uint64_t, which: libc::uint8_t) -> libc::uint64_t;
+    fn libsnarkwrap_cs_term(
+        cs: *const libc::c_void,
+        constraint: libc::uint64_t,
+        which: libc::uint8_t,
+        term: libc::uint64_t,
+        index: *mut libc::uint64_t,
+        coeff: *mut Fr);
+    fn libsnarkwrap_keypair_write(kp: *const libc::c_void, pk_path: *const libc::c_char, vk_path: *const libc::c_char);
     fn libsnarkwrap_eval(
         cs: *const libc::c_void,
         lc1: *const G1,
@@ -105,6 +128,17 @@ pub struct CS {
     pub omega: Fr
 }
 
+/// A linear combination of variables, as pairs of variable index and
+/// coefficient. Variable 0 is the constant one.
+pub type LinearCombination = Vec<(usize, Fr)>;
+
+/// A constraint `a * b = c`.
+pub struct Constraint {
+    pub a: LinearCombination,
+    pub b: LinearCombination,
+    pub c: LinearCombination
+}
+
 pub struct Keypair {
     ptr: *mut libc::c_void
 }
@@ -120,11 +154,15 @@ impl PartialEq for Keypair {
 }
 
 impl Keypair {
-    pub fn write_to_disk(&self) {
+    /// Writes the keypair in libsnark's own format.
+    pub fn write_to_disk(&self, pk_path: &str, vk_path: &str) {
         initialize();
 
+        let pk_path = CString::new(pk_path).expect("path should not contain NUL bytes");
+        let vk_path = CString::new(vk_path).expect("path should not contain NUL bytes");
+
         unsafe {
-            libsnarkwrap_keypair_write(self.ptr);
+            libsnarkwrap_keypair_write(self.ptr, pk_path.as_ptr(), vk_path.as_ptr());
         }
     }
 
@@ -206,15 +244,17 @@ impl Keypair {
 }
 
 impl CS {
-    pub fn from_file() -> Self {
+    pub fn from_file(path: &str) -> Self {
         initialize();
 
+        let path = CString::new(path).expect("path should not contain NUL bytes");
+
         let mut d = 0;
         let mut vars = 0;
         let mut num_inputs = 0;
         let mut o = Fr::zero();
 
-        let cs = unsafe { libsnarkwrap_getcs_file(&mut d, &mut vars, &mut num_inputs, &mut o) };
+        let cs = unsafe { libsnarkwrap_getcs_file(path.as_ptr(), &mut d, &mut vars, &mut num_inputs, &mut o) };
 
         CS {
             ptr: cs,
@@ -244,6 +284,75 @@ impl CS {
         }
     }
 
+    pub fn from_constraints(num_inputs: usize, num_aux: usize, constraints: &[Constraint]) -> Self {
+        initialize();
+
+        let split = |lc: &LinearCombination| -> (Vec<u64>, Vec<Fr>) {
+            (lc.iter().map(|t| t.0 as u64).collect(), lc.iter().map(|t| t.1).collect())
+        };
+
+        let cs = unsafe { libsnarkwrap_cs_new(num_inputs as u64, num_aux as u64) };
+
+        for constraint in constraints {
+            let (a_indexes, a_coeffs) = split(&constraint.a);
+            let (b_indexes, b_coeffs) = split(&constraint.b);
+            let (c_indexes, c_coeffs) = split(&constraint.c);
+
+            unsafe {
+                libsnarkwrap_cs_add_constraint(
+                    cs,
+                    a_indexes.len() as u64, a_indexes.as_ptr(), a_coeffs.as_ptr(),
+                    b_indexes.len() as u64, b_indexes.as_ptr(), b_coeffs.as_ptr(),
+                    c_indexes.len() as u64, c_indexes.as_ptr(), c_coeffs.as_ptr()
+                );
+            }
+        }
+
+        let mut d = 0;
+        let mut vars = 0;
+        let mut num_inputs = 0;
+        let mut o = Fr::zero();
+
+        let cs = unsafe { libsnarkwrap_cs_finish(cs, &mut d, &mut vars, &mut num_inputs, &mut o) };
+
+        CS {
+            ptr: cs,
+            num_vars: vars as usize,
+            num_inputs: num_inputs as usize,
+            d: d as usize,
+            omega: o
+        }
+    }
+
+    /// The constraints, after libsnark has swapped A and B if that
+    /// was beneficial.
+    pub fn constraints(&self) -> Vec<Constraint> {
+        initialize();
+
+        let lc = |constraint: u64, which: u8| {
+            let n = unsafe { libsnarkwrap_cs_num_terms(self.ptr, constraint, which) };
+
+            (0..n).map(|term| {
+                let mut index = 0;
+                let mut coeff = Fr::zero();
+
+                unsafe { libsnarkwrap_cs_term(self.ptr, constraint, which, term, &mut index, &mut coeff) };
+
+                (index as usize, coeff)
+            }).collect()
+        };
+
+        let n = unsafe { libsnarkwrap_cs_num_constraints(self.ptr) };
+
+        (0..n).map(|i| {
+            Constraint {
+                a: lc(i, 0),
+                b: lc(i, 1),
+                c: lc(i, 2)
+            }
+        }).collect()
+    }
+
     pub fn test_compare_tau(&self, v1: &[G1], v2: &[G2], tau: &Fr) -> bool {
         initialize();
 
@@ -95,11 +95,11 @@ void* libsnark_cs_return(
     return new r1cs_constraint_system<curve_Fr>(cs);
 }
 
-extern "C" void* libsnarkwrap_getcs_file(uint64_t *d, uint64_t *vars, uint64_t *num_inputs, curve_Fr *omega)
+extern "C" void* libsnarkwrap_getcs_file(const char *path, uint64_t *d, uint64_t *vars, uint64_t *num_inputs, curve_Fr *omega)
 {
     r1cs_constraint_system<curve_Fr> cs;
 
-    loadFromFile("r1cs", cs);
+    loadFromFile(path, cs);
 
     return libsnark_cs_return(
         d,
@@ -110,6 +110,65 @@ extern "C" void* libsnarkwrap_getcs_file(uint64_t *d, uint64_t *vars, uint64_t *
     );
 }
 
+extern "C" void* libsnarkwrap_cs_new(uint64_t num_inputs, uint64_t num_aux)
+{
+    auto cs = new r1cs_constraint_system<curve_Fr>();
+
+    cs->primary_input_size = num_inputs;
+    cs->auxiliary_input_size = num_aux;
+
+    return cs;
+}
+
+linear_combination<curve_Fr> libsnark_lc(uint64_t len, const uint64_t *indexes, const curve_Fr *coeffs)
+{
+    linear_combination<curve_Fr> lc;
+
+    for (uint64_t i = 0; i < len; i++) {
+        lc.add_term(variable<curve_Fr>(indexes[i]), coeffs[i]);
+    }
+
+    return lc;
+}
+
+extern "C" void libsnarkwrap_cs_add_constraint(
+    r1cs_constraint_system<curve_Fr> *cs,
+    uint64_t a_len,
+    const uint64_t *a_indexes,
+    const curve_Fr *a_coeffs,
+    uint64_t b_len,
+    const uint64_t *b_indexes,
+    const curve_Fr *b_coeffs,
+    uint64_t c_len,
+    const uint64_t *c_indexes,
+    const curve_Fr *c_coeffs
+)
+{
+    cs->add_constraint(r1cs_constraint<curve_Fr>(
+        libsnark_lc(a_len, a_indexes, a_coeffs),
+        libsnark_lc(b_len, b_indexes, b_coeffs),
+        libsnark_lc(c_len, c_indexes, c_coeffs)
+    ));
+}
+
+extern "C" void* libsnarkwrap_cs_finish(
+    r1cs_constraint_system<curve_Fr> *cs,
+    uint64_t *d, uint64_t *vars, uint64_t *num_inputs, curve_Fr *omega
+)
+{
+    void *result = libsnark_cs_return(
+        d,
+        vars,
+        num_inputs,
+        omega,
+        *cs
+    );
+
+    delete cs;
+
+    return result;
+}
+
 extern "C" void* libsnarkwrap_getcs_dummy(uint64_t *d, uint64_t *vars, uint64_t *num_inputs, curve_Fr *omega)
 {
     // Generate a dummy circuit
@@ -129,6 +188,55 @@ extern "C" void libsnarkwrap_dropcs(r1cs_constraint_system<curve_Fr> *cs)
     delete cs;
 }
 
+// Constraint export, so the constraint system can be evaluated natively
+
+const linear_combination<curve_Fr>& libsnark_cs_lc(
+    const r1cs_constraint_system<curve_Fr> *cs,
+    uint64_t constraint,
+    uint8_t which
+)
+{
+    assert(constraint < cs->num_constraints());
+
+    switch (which) {
+        case 0: return cs->constraints[constraint].a;
+        case 1: return cs->constraints[constraint].b;
+        default:
+            assert(which == 2);
+            return cs->constraints[constraint].c;
+    }
+}
+
+extern "C" uint64_t libsnarkwrap_cs_num_constraints(const r1cs_constraint_system<curve_Fr> *cs)
+{
+    return cs->num_constraints();
+}
+
+extern "C" uint64_t libsnarkwrap_cs_num_terms(
+    const r1cs_constraint_system<curve_Fr> *cs,
+    uint64_t constraint,
+    uint8_t which
+)
+{
+    return libsnark_cs_lc(cs, constraint, which).terms.size();
+}
+
+extern "C" void libsnarkwrap_cs_term(
+    const r1cs_constraint_system<curve_Fr> *cs,
+    uint64_t constraint,
+    uint8_t which,
+    uint64_t term,
+    uint64_t *index,
+    curve_Fr *coeff
+)
+{
+    auto &lc = libsnark_cs_lc(cs, constraint, which);
+    assert(term < lc.terms.size());
+
+    *index = lc.terms[term].index;
+    *coeff = lc.terms[term].coeff;
+}
+
 extern "C" void libsnarkwrap_dropkeypair(r1cs_ppzksnark_keypair<curve_pp> *kp)
 {
     delete kp;
@@ -278,11 +386,13 @@ extern "C" void* libsnarkwrap_test_keygen(
 }
 
 extern "C" void libsnarkwrap_keypair_write(
-    const r1cs_ppzksnark_keypair<curve_pp> *kp
+    const r1cs_ppzksnark_keypair<curve_pp> *kp,
+    const char *pk_path,
+    const char *vk_path
 )
 {
-    saveToFile("pk", kp->pk);
-    saveToFile("vk", kp->vk);
+    saveToFile(pk_path, kp->pk);
+    saveToFile(vk_path, kp->vk);
 }
 
 extern "C" bool libsnarkwrap_keypair_eq(
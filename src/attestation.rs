@@ -0,0 +1,271 @@
+//! Lets a participant confirm that their contribution made it into the
+//! final transcript, using the commitment and disc hashes they wrote
+//! down during the ceremony.
+//!
+//! Discs A, C and E carry a stage to the player, and their hashes end
+//! the player's contribution to that stage as its ihash. Discs B, D and
+//! F carry the player's contribution, and their hashes are the hashes
+//! of the records in the transcript.
+
+use std::fs::File;
+use protocol::Digest256;
+use transcript::*;
+use report::*;
+
+pub const USAGE: &'static str = "\
+Usage: verifier attest --commitment <hash> [--disc <A-F>=<hash>]... [options]
+
+Verifies the transcript, and checks that the commitment and disc hashes
+a player wrote down appear in it, and that every later contribution
+builds on theirs. Any of the options of the verifier can follow.";
+
+/// Every disc a player hashes, with the stage it belongs to and whether
+/// it carries the player's contribution rather than the stage they were
+/// given.
+const DISCS: [(&'static str, u8, bool); 6] = [
+    ("A", 1, false),
+    ("B", 1, true),
+    ("C", 2, false),
+    ("D", 2, true),
+    ("E", 3, false),
+    ("F", 3, true)
+];
+
+fn disc(name: &str) -> Option<(u8, bool)> {
+    DISCS.iter().find(|d| d.0 == name).map(|d| (d.1, d.2))
+}
+
+fn parse_hash(s: &str) -> Result<Digest256, String> {
+    Digest256::from_string(s).ok_or_else(|| format!("invalid hash {}", s))
+}
+
+pub struct Attestation {
+    commitment: Digest256,
+    discs: Vec<(String, Digest256)>
+}
+
+impl Attestation {
+    /// Takes the attestation's own options from `args`, returning the
+    /// rest of them for `Config`.
+    pub fn from_args(args: &[String]) -> Result<(Attestation, Vec<String>), String> {
+        let mut commitment = None;
+        let mut discs: Vec<(String, Digest256)> = vec![];
+        let mut rest = vec![];
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--commitment" => {
+                    let value = try!(args.next().ok_or_else(|| "missing value for --commitment".to_string()));
+
+                    commitment = Some(try!(parse_hash(value)));
+                },
+                "--disc" => {
+                    let value = try!(args.next().ok_or_else(|| "missing value for --disc".to_string()));
+                    let mut parts = value.splitn(2, '=');
+                    let name = parts.next().unwrap().to_uppercase();
+                    let hash = try!(parts.next().ok_or_else(|| format!("expected <disc>=<hash>, not {}", value)));
+
+                    if disc(&name).is_none() {
+                        return Err(format!("unknown disc {}", name));
+                    }
+
+                    if discs.iter().any(|d| d.0 == name) {
+                        return Err(format!("disc {} is given twice", name));
+                    }
+
+                    discs.push((name, try!(parse_hash(hash))));
+                },
+                _ => rest.push(arg.clone())
+            }
+        }
+
+        let commitment = try!(commitment.ok_or_else(|| "the player's commitment is required".to_string()));
+
+        Ok((Attestation {
+            commitment: commitment,
+            discs: discs
+        }, rest))
+    }
+
+    /// Checks the attestation against the outcome of verifying the
+    /// transcript, which must have collected every failure. Returns
+    /// whether it holds, recording in `report` why if it doesn't.
+    pub fn check(&self, report: &mut Report, transcript: &str) -> bool {
+        let transcript = match File::open(transcript).map_err(TranscriptError::from).and_then(TranscriptReader::open) {
+            Ok(transcript) => transcript,
+            Err(e) => {
+                report.fail(Failure::Attestation, format!("Could not read the transcript: {}", e));
+                return false;
+            }
+        };
+
+        let commitment = self.commitment.to_string();
+
+        let player = match report.players.iter().position(|p| p.commitment == commitment) {
+            Some(player) => player,
+            None => {
+                report.fail(Failure::Attestation, format!("Commitment {} is not in the transcript", commitment));
+                return false;
+            }
+        };
+
+        println!("Commitment {} is that of player {}", commitment, player+1);
+
+        let mut holds = true;
+
+        for &(ref name, ref hash) in &self.discs {
+            let (stage, is_contribution) = disc(name).unwrap();
+            let recorded = report.players[player].discs.iter()
+                                                   .find(|d| d.disc == *name)
+                                                   .map(|d| d.hash.clone());
+
+            match recorded {
+                None => {
+                    report.fail(Failure::Attestation, format!("Player {} has no disc {} in the transcript", player+1, name));
+                    holds = false;
+                },
+                Some(ref recorded) if *recorded != hash.to_string() => {
+                    report.fail(Failure::Attestation, format!("Disc {} of player {} is {} in the transcript, not {}",
+                                                              name, player+1, recorded, hash.to_string()));
+                    holds = false;
+                },
+                Some(_) => {
+                    match transcript.entry(Section::Contribution(stage, player)) {
+                        Some(entry) if is_contribution => {
+                            println!("Disc {} is the hash of player {}'s stage{} contribution, bytes {}..{} of the transcript",
+                                     name, player+1, stage, entry.offset, entry.offset + entry.len);
+                        },
+                        Some(entry) => {
+                            println!("Disc {} is the ihash ending player {}'s stage{} contribution, bytes {}..{} of the transcript",
+                                     name, player+1, stage, entry.offset + entry.len - 32, entry.offset + entry.len);
+                        },
+                        None => {
+                            println!("Disc {} is the stage{} given to player {}, who did not contribute to it",
+                                     name, stage, player+1);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(ref reason) = report.players[player].excluded.clone() {
+            report.fail(Failure::Attestation, format!("Player {} was excluded ({}), so the ceremony does not build on their contribution",
+                                                      player+1, reason));
+            return false;
+        }
+
+        // Contributions are made stage by stage, each in the order the
+        // coordinator scheduled its turns, so everything from the
+        // player's first contribution onward must be valid for the
+        // keypair to build on it. That's in stage2 for a ceremony started
+        // from an accumulator.
+        let first = if transcript.header().params.accumulator.is_some() { 2 } else { 1 };
+        let order = transcript.order(first);
+        let before: Vec<usize> = order.iter().cloned().take_while(|&i| i != player).collect();
+        let mut broken = vec![];
+
+        for (i, p) in report.players.iter().enumerate() {
+            for check in &p.checks {
+                if !check.passed && (check.stage > first || !before.contains(&i)) {
+                    broken.push(format!("{} of player {} in stage{}", check.name, i+1, check.stage));
+                }
+            }
+        }
+
+        if report.failures.iter().any(|f| f.class == Failure::Transcript) {
+            broken.push("the transcript itself".to_string());
+        }
+
+        if !broken.is_empty() {
+            report.fail(Failure::Attestation, format!("The ceremony does not build on the contribution of player {}; failed: {}",
+                                                      player+1, broken.join(", ")));
+            return false;
+        }
+
+        println!("Every later contribution builds on the contribution of player {}", player+1);
+
+        holds
+    }
+}
+
+#[test]
+fn attestation_follows_the_turn_order() {
+    use std::env;
+    use bincode::SizeLimit::Infinite;
+    use bincode::rustc_serialize::encode_into;
+    use config::Config;
+    use protocol::SigningKey;
+
+    // Three players took their stage1 turns in the order 2, 1, 3.
+    let path = env::temp_dir().join("mpc-test-attestation");
+    let header = TranscriptHeader {
+        config: Config::default(),
+        params: CeremonyParams::universal(4, &Config::default())
+    };
+
+    let mut writer = TranscriptWriter::create(File::create(&path).unwrap(), &header, SigningKey::new(&mut ::rand::thread_rng())).unwrap();
+    writer.record(Section::Commitments, |w| encode_into(&vec![1u8, 2, 3], w, Infinite)).unwrap();
+    for &player in &[1, 0, 2] {
+        writer.record(Section::Contribution(1, player), |w| encode_into(&(player as u64), w, Infinite)).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let commitments: Vec<Digest256> = (0..3).map(|i| Digest256::from(&i).unwrap()).collect();
+    let disc = Digest256::from(&"disc B").unwrap();
+
+    let report = || {
+        let mut report = Report::new("test", "test", true);
+        for (i, commitment) in commitments.iter().enumerate() {
+            report.add_player(commitment);
+            report.check(i, 1, "transformation", true);
+        }
+        report.disc(0, "B", &disc);
+
+        report
+    };
+
+    let attestation = Attestation {
+        commitment: commitments[0].clone(),
+        discs: vec![("B".to_string(), disc.clone())]
+    };
+
+    let attest = |report: &mut Report| {
+        let holds = attestation.check(report, path.to_str().unwrap());
+        assert_eq!(holds, !report.failures.iter().any(|f| f.class == Failure::Attestation));
+
+        holds
+    };
+
+    assert!(attest(&mut report()));
+
+    // A bad turn before theirs in stage1 doesn't undo their contribution.
+    let mut earlier = report();
+    earlier.check(1, 1, "transformation", false);
+    assert!(attest(&mut earlier));
+
+    // Anything after it does, as does being excluded.
+    let mut later = report();
+    later.check(2, 1, "transformation", false);
+    assert!(!attest(&mut later));
+
+    let mut next_stage = report();
+    next_stage.check(1, 2, "transformation", false);
+    assert!(!attest(&mut next_stage));
+
+    let mut excluded = report();
+    excluded.excluded(0, 1, "unresponsive");
+    assert!(!attest(&mut excluded));
+
+    let wrong_disc = Attestation {
+        commitment: commitments[0].clone(),
+        discs: vec![("B".to_string(), Digest256::from(&"disc D").unwrap())]
+    };
+    assert!(!wrong_disc.check(&mut report(), path.to_str().unwrap()));
+
+    let stranger = Attestation {
+        commitment: Digest256::from(&"stranger").unwrap(),
+        discs: vec![]
+    };
+    assert!(!stranger.check(&mut report(), path.to_str().unwrap()));
+}
@@ -0,0 +1,458 @@
+//! The authenticated and encrypted channel between the network machines
+//! and the coordinator, which runs over any `Connection`.
+//!
+//! Every network machine holds an identity key for the duration of the
+//! ceremony, and its peerid is derived from it, so a player can only
+//! ever reconnect as themselves. The coordinator records the identity
+//! along with the player's commitment, which it receives over the
+//! channel, and turns away anyone who connects with the same peerid
+//! but a different identity.
+//!
+//! The channel is [Noise] (`Noise_XX_25519_ChaChaPoly_BLAKE2s`, as
+//! implemented by `snow`):
+//!
+//! ```text
+//! network:     NETWORK_MAGIC [u8; 8] | message(e)
+//! coordinator: COORDINATOR_MAGIC [u8; 8] | message(e, ee, s, es; certificate)
+//! network:     message(s, se; msgid u64)
+//! ```
+//!
+//! The coordinator's static key is generated when it starts, and the
+//! certificate is its `Signature` of that key with its long-term signing
+//! key (see `signing.rs`). Network machines are given the coordinator's
+//! published key with `--coordinator-key`, and won't talk to anyone who
+//! can't show a certificate from it, so nobody can sit between them and
+//! the coordinator. The coordinator learns the network machine's identity
+//! `s` under encryption, and the msgid proves it holds the secret to it.
+//! The msgid is the number of messages the network machine has exchanged
+//! with the coordinator (see `wire.rs`). Once the coordinator knows
+//! whether the player is reconnecting, it answers with its own, as a u64
+//! in the first record of the channel.
+//!
+//! Every Noise message, in the handshake and after it, is sent as a
+//! record:
+//!
+//! ```text
+//! record: length u16 | message [u8; length]
+//! ```
+//!
+//! Noise numbers the messages implicitly, so they can't be replayed,
+//! dropped or reordered without failing authentication. Reconnecting
+//! starts the handshake over, with fresh keys.
+//!
+//! [Noise]: https://noiseprotocol.org/noise.html
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt, LittleEndian};
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::{encode, decode};
+use snow::{Builder, HandshakeState, TransportState};
+use protocol::{Digest256, SigningKey, CoordinatorKey, Signature};
+use consts::*;
+use transport::Connection;
+
+const NOISE_PARAMS: &'static str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+
+/// Bound into the handshake, so that it can't be mistaken for any other
+/// use of the same Noise pattern.
+const PROLOGUE: &'static [u8] = b"mpc network channel";
+
+/// How long either end waits on the other during the handshake.
+const HANDSHAKE_TIMEOUT: u64 = 5;
+
+/// Largest Noise message.
+const MAX_MESSAGE: usize = 65535;
+
+const TAG_LENGTH: usize = 16;
+
+/// Largest plaintext of a record; writes are buffered up to this size.
+const MAX_RECORD: usize = MAX_MESSAGE - TAG_LENGTH;
+
+fn builder<'a>() -> Builder<'a> {
+    Builder::new(NOISE_PARAMS.parse().unwrap())
+}
+
+/// The key a network machine identifies itself with.
+pub struct Identity {
+    private: Vec<u8>,
+    public: Vec<u8>
+}
+
+impl Identity {
+    pub fn new() -> Identity {
+        let keypair = builder().generate_keypair().unwrap();
+
+        Identity {
+            private: keypair.private,
+            public: keypair.public
+        }
+    }
+
+    pub fn hash(&self) -> Digest256 {
+        identity_hash(&self.public)
+    }
+
+    pub fn peerid(&self) -> [u8; 8] {
+        peerid(&self.hash())
+    }
+}
+
+fn identity_hash(public: &[u8]) -> Digest256 {
+    Digest256::from(&public.to_vec()).unwrap()
+}
+
+/// The peerid of the network machine with the given identity.
+pub fn peerid(identity: &Digest256) -> [u8; 8] {
+    let mut peerid = [0; 8];
+    peerid.copy_from_slice(&identity.0[0..8]);
+
+    peerid
+}
+
+/// What the coordinator's signing key vouches for in a certificate.
+fn certified(public: &[u8]) -> Digest256 {
+    Digest256::from(&("coordinator channel key", public.to_vec())).unwrap()
+}
+
+/// The coordinator's static key for the channel, along with the
+/// certificate its long-term signing key gives it.
+pub struct CoordinatorIdentity {
+    private: Vec<u8>,
+    certificate: Vec<u8>
+}
+
+impl CoordinatorIdentity {
+    pub fn new(key: &SigningKey) -> CoordinatorIdentity {
+        let keypair = builder().generate_keypair().unwrap();
+        let certificate = key.sign(&mut ::rand::thread_rng(), &certified(&keypair.public));
+
+        CoordinatorIdentity {
+            private: keypair.private,
+            certificate: encode(&certificate, Infinite).unwrap()
+        }
+    }
+}
+
+/// A channel established by the handshake.
+pub struct SecureConnection<C: Connection> {
+    inner: C,
+    noise: TransportState,
+    /// Plaintext waiting to be sent in a record.
+    outgoing: Vec<u8>,
+    /// Plaintext of the last record received.
+    incoming: Vec<u8>,
+    /// How much of `incoming` has been read.
+    read: usize,
+    /// The message of the record being sent.
+    message: Vec<u8>
+}
+
+impl<C: Connection> SecureConnection<C> {
+    fn new(inner: C, state: HandshakeState) -> io::Result<SecureConnection<C>> {
+        let noise = try!(state.into_transport_mode().map_err(|_| bad_record()));
+
+        Ok(SecureConnection {
+            inner: inner,
+            noise: noise,
+            outgoing: vec![],
+            incoming: vec![],
+            read: 0,
+            message: vec![0; MAX_MESSAGE]
+        })
+    }
+
+    fn send_record(&mut self, len: usize) -> io::Result<()> {
+        let n = try!(self.noise.write_message(&self.outgoing[0..len], &mut self.message).map_err(|_| bad_record()));
+        self.outgoing.drain(0..len);
+
+        write_record(&mut self.inner, &self.message[0..n])
+    }
+}
+
+impl<C: Connection> Read for SecureConnection<C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.read == self.incoming.len() {
+            let record = try!(read_record(&mut self.inner));
+
+            self.incoming.resize(MAX_MESSAGE, 0);
+            let len = try!(self.noise.read_message(&record, &mut self.incoming).map_err(|_| bad_record()));
+            self.incoming.truncate(len);
+            self.read = 0;
+        }
+
+        let n = ::std::cmp::min(buf.len(), self.incoming.len() - self.read);
+        buf[..n].copy_from_slice(&self.incoming[self.read..(self.read + n)]);
+        self.read += n;
+
+        Ok(n)
+    }
+}
+
+impl<C: Connection> Write for SecureConnection<C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.outgoing.extend_from_slice(buf);
+
+        while self.outgoing.len() >= MAX_RECORD {
+            try!(self.send_record(MAX_RECORD));
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.outgoing.is_empty() {
+            let len = self.outgoing.len();
+            try!(self.send_record(len));
+        }
+
+        self.inner.flush()
+    }
+}
+
+impl<C: Connection> Connection for SecureConnection<C> {
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.inner.set_timeout(timeout)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.inner.shutdown()
+    }
+
+    fn remote(&self) -> String {
+        self.inner.remote()
+    }
+}
+
+/// The network machine's side of the handshake, which only completes
+/// with the holder of `coordinator`'s signing key. Returns the channel
+/// and the msgid the coordinator has reached with us, or `None` if the
+/// handshake failed.
+pub fn handshake<C: Connection>(
+    mut c: C,
+    identity: &Identity,
+    coordinator: &CoordinatorKey,
+    msgid: u64,
+    timeout: u64
+) -> Option<(SecureConnection<C>, u64)>
+{
+    if c.set_timeout(Some(Duration::from_secs(HANDSHAKE_TIMEOUT))).is_err() {
+        return None;
+    }
+
+    let mut state = match builder().local_private_key(&identity.private).prologue(PROLOGUE).build_initiator() {
+        Ok(state) => state,
+        Err(_) => return None
+    };
+
+    let mut message = vec![0; MAX_MESSAGE];
+    let mut payload = vec![0; MAX_MESSAGE];
+
+    let len = match state.write_message(&[], &mut message) {
+        Ok(len) => len,
+        Err(_) => return None
+    };
+
+    if c.write_all(&NETWORK_MAGIC).is_err() ||
+       write_record(&mut c, &message[..len]).is_err() ||
+       c.flush().is_err()
+    {
+        return None;
+    }
+
+    let _ = c.set_timeout(Some(Duration::from_secs(timeout)));
+
+    let mut magic = [0; 8];
+    if c.read_exact(&mut magic).is_err() || magic != COORDINATOR_MAGIC {
+        return None;
+    }
+
+    let len = match read_record(&mut c).ok().and_then(|record| state.read_message(&record, &mut payload).ok()) {
+        Some(len) => len,
+        None => return None
+    };
+
+    // Whoever we're talking to has to show the coordinator's certificate
+    // for the static key they just proved they hold.
+    let certificate: Signature = match decode(&payload[..len]) {
+        Ok(certificate) => certificate,
+        Err(_) => return None
+    };
+
+    match state.get_remote_static() {
+        Some(s) if coordinator.verify(&certified(s), &certificate) => {},
+        _ => return None
+    }
+
+    let mut msgid_bytes = [0; 8];
+    LittleEndian::write_u64(&mut msgid_bytes, msgid);
+
+    let len = match state.write_message(&msgid_bytes, &mut message) {
+        Ok(len) => len,
+        Err(_) => return None
+    };
+
+    if write_record(&mut c, &message[..len]).is_err() || c.flush().is_err() {
+        return None;
+    }
+
+    let mut channel = match SecureConnection::new(c, state) {
+        Ok(channel) => channel,
+        Err(_) => return None
+    };
+
+    match channel.read_u64::<LittleEndian>() {
+        Ok(their_msgid) => Some((channel, their_msgid)),
+        Err(_) => None
+    }
+}
+
+/// The coordinator's side of the handshake, up to sending the msgid.
+/// Returns the channel, the identity of the network machine and the
+/// msgid it has reached.
+pub fn accept_handshake<C: Connection>(
+    mut c: C,
+    coordinator: &CoordinatorIdentity,
+    timeout: u64
+) -> Result<(SecureConnection<C>, Digest256, u64), String>
+{
+    let _ = c.set_timeout(Some(Duration::from_secs(HANDSHAKE_TIMEOUT)));
+
+    let mut state = try!(builder().local_private_key(&coordinator.private)
+                                  .prologue(PROLOGUE)
+                                  .build_responder()
+                                  .map_err(|e| format!("could not start the handshake; {:?}", e)));
+
+    let mut message = vec![0; MAX_MESSAGE];
+    let mut payload = vec![0; MAX_MESSAGE];
+
+    let mut magic = [0; 8];
+    try!(c.read_exact(&mut magic).map_err(|e| format!("did not handshake; {}", e)));
+
+    if magic != NETWORK_MAGIC {
+        return Err("did not supply correct network magic".to_string());
+    }
+
+    let record = try!(read_record(&mut c).map_err(|e| format!("did not handshake; {}", e)));
+    try!(state.read_message(&record, &mut payload).map_err(|_| "sent a malformed handshake".to_string()));
+
+    let len = try!(state.write_message(&coordinator.certificate, &mut message)
+                        .map_err(|e| format!("could not complete the handshake; {:?}", e)));
+
+    if c.write_all(&COORDINATOR_MAGIC).is_err() ||
+       write_record(&mut c, &message[..len]).is_err() ||
+       c.flush().is_err() ||
+       c.set_timeout(Some(Duration::from_secs(timeout))).is_err()
+    {
+        return Err("could not complete the handshake".to_string());
+    }
+
+    let record = try!(read_record(&mut c).map_err(|e| format!("did not identify itself; {}", e)));
+    let len = try!(state.read_message(&record, &mut payload).map_err(|_| "could not prove its identity".to_string()));
+
+    if len != 8 {
+        return Err("sent a malformed handshake".to_string());
+    }
+
+    let msgid = LittleEndian::read_u64(&payload[..8]);
+    let identity = match state.get_remote_static() {
+        Some(s) => identity_hash(s),
+        None => return Err("did not identify itself".to_string())
+    };
+
+    let channel = try!(SecureConnection::new(c, state).map_err(|e| format!("could not complete the handshake; {}", e)));
+
+    Ok((channel, identity, msgid))
+}
+
+fn bad_record() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "record failed authentication")
+}
+
+fn read_record<R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+    let len = try!(r.read_u16::<LittleEndian>()) as usize;
+
+    let mut record = vec![0; len];
+    try!(r.read_exact(&mut record));
+
+    Ok(record)
+}
+
+fn write_record<W: Write>(w: &mut W, message: &[u8]) -> io::Result<()> {
+    try!(w.write_u16::<LittleEndian>(message.len() as u16));
+
+    w.write_all(message)
+}
+
+#[test]
+fn secure_channel() {
+    use std::thread;
+    use transport::{Transport, Listener, Memory};
+
+    let transport = Memory::new();
+    let listener = transport.listen("coordinator").unwrap();
+
+    let key = SigningKey::new(&mut ::rand::thread_rng());
+    let coordinator = CoordinatorIdentity::new(&key);
+    let published = key.verifying_key();
+
+    let identity = Identity::new();
+    let expected = identity.hash();
+
+    let remote = transport.clone();
+    let player = thread::spawn(move || {
+        let c = remote.connect("coordinator").unwrap();
+        let (mut c, msgid) = handshake(c, &identity, &published, 3, 10).unwrap();
+
+        let mut buf = vec![0; MAX_RECORD * 2 + 5];
+        c.read_exact(&mut buf).unwrap();
+        c.write_all(&buf).unwrap();
+        c.flush().unwrap();
+
+        // Small reads are served from the record already received.
+        let mut small = [0; 3];
+        for _ in 0..2 {
+            c.read_exact(&mut small).unwrap();
+            assert_eq!(small, [1, 2, 3]);
+        }
+
+        (msgid, identity.peerid())
+    });
+
+    let (mut c, identity, msgid) = accept_handshake(listener.accept().unwrap(), &coordinator, 10).unwrap();
+    assert!(identity == expected);
+    assert_eq!(msgid, 3);
+
+    c.write_u64::<LittleEndian>(2).unwrap();
+    c.flush().unwrap();
+
+    let message: Vec<u8> = (0..(MAX_RECORD * 2 + 5)).map(|i| i as u8).collect();
+    c.write_all(&message).unwrap();
+    c.flush().unwrap();
+
+    let mut echoed = vec![0; message.len()];
+    c.read_exact(&mut echoed).unwrap();
+    assert!(echoed == message);
+
+    c.write_all(&[1, 2, 3, 1, 2, 3]).unwrap();
+    c.flush().unwrap();
+
+    let (msgid, their_peerid) = player.join().unwrap();
+    assert_eq!(msgid, 2);
+    assert_eq!(their_peerid, peerid(&identity));
+
+    // Network machines won't talk to a coordinator without the
+    // published key's certificate.
+    let impostor = CoordinatorIdentity::new(&SigningKey::new(&mut ::rand::thread_rng()));
+    let published = key.verifying_key();
+
+    let remote = transport.clone();
+    let player = thread::spawn(move || {
+        let c = remote.connect("coordinator").unwrap();
+
+        handshake(c, &Identity::new(), &published, 0, 1).is_none()
+    });
+
+    assert!(accept_handshake(listener.accept().unwrap(), &impostor, 1).is_err());
+    assert!(player.join().unwrap());
+}
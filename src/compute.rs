@@ -14,14 +14,14 @@ use self::protocol::*;
 mod dvd;
 use self::dvd::*;
 
+mod config;
+use self::config::*;
+
 use rand::{SeedableRng, Rng};
 use std::fs::{File};
 use bincode::SizeLimit::Infinite;
 use bincode::rustc_serialize::{encode_into, decode_from};
 
-pub const THREADS: usize = 8;
-pub const DIRECTORY_PREFIX: &'static str = "/home/compute/";
-pub const ASK_USER_TO_RECORD_HASHES: bool = true;
 
 fn get_entropy() -> [u32; 8] {
     use blake2_rfc::blake2s::blake2s;
@@ -65,69 +65,135 @@ fn get_entropy() -> [u32; 8] {
 }
 
 fn main() {
+    let config = Config::load("compute", Config {
+        directory_prefix: "/home/compute/".into(),
+        ask_user_to_record_hashes: true,
+        .. Config::default()
+    });
+
+    set_threads(config.threads);
+
     prompt("Press [ENTER] when you're ready to perform diagnostics of the DVD drive.");
-    // perform_diagnostics();
+    // perform_diagnostics(&config);
     prompt("Diagnostics complete. Press [ENTER] when you're ready to begin the ceremony.");
 
+    match config.scheme {
+        ProofSystem::Pghr13 => contribute::<Pghr13>(&config),
+        ProofSystem::Groth16 => contribute::<Groth16>(&config)
+    }
+}
+
+fn contribute<S: Scheme>(config: &Config) {
     let mut chacha_rng = rand::chacha::ChaChaRng::from_seed(&get_entropy());
 
-    let privkey = PrivateKey::new(&mut chacha_rng);
+    let privkey = <S::PrivateKey as SchemePrivateKey>::new(&mut chacha_rng);
     let pubkey = privkey.pubkey(&mut chacha_rng);
     let comm = pubkey.hash();
 
-    let (hash_of_commitments, mut stage1, prev_msg_hash): (Digest512, Stage1Contents, Digest256) = read_disc(
-        "A",
-        &format!("Commitment: {}\n\n\
-                  Write this commitment down on paper.\n\n\
-                  Then type the above commitment into the networked machine.\n\n\
-                  The networked machine should produce disc 'A'.\n\n\
-                  When disc 'A' is in the DVD drive, press [ENTER].", comm.to_string()),
-        |f, p| -> Result<_, bincode::rustc_serialize::DecodingError> {
-            let hash_of_commitments: Digest512 = try!(decode_from(f, Infinite));
-            let stage: Stage1Contents = try!(decode_from(f, Infinite));
-
-            Ok((hash_of_commitments, stage, p.unwrap()))
-        }
-    );
+    // A ceremony started from an accumulator has no stage 1 turn, so the
+    // public key is revealed with the stage 2 transformation instead, and
+    // the first disc is 'C'.
+    let (mut stage2, prev_msg_hash, nizks): (S::Stage2, Digest256, Option<S::PublicKeyNizks>) = if config.from_accumulator {
+        let (hash_of_commitments, stage2, prev_msg_hash): (Digest512, S::Stage2, Digest256) = read_disc(
+            config,
+            "C",
+            &format!("Commitment: {}\n\n\
+                      Write this commitment down on paper.\n\n\
+                      Then type the above commitment into the networked machine.\n\n\
+                      The networked machine should produce disc 'C'.\n\n\
+                      When disc 'C' is in the DVD drive, press [ENTER].", comm.to_string()),
+            |f, p| -> Result<_, bincode::rustc_serialize::DecodingError> {
+                let hash_of_commitments: Digest512 = try!(decode_from(f, Infinite));
+                let stage: S::Stage2 = try!(decode_from(f, Infinite));
+
+                Ok((hash_of_commitments, stage, p.unwrap()))
+            }
+        );
 
-    let nizks = pubkey.nizks(&mut chacha_rng, &privkey, &hash_of_commitments);
+        let nizks = pubkey.nizks(&mut chacha_rng, &privkey, &hash_of_commitments);
+
+        (stage2, prev_msg_hash, Some(nizks))
+    } else {
+        let (hash_of_commitments, mut stage1, prev_msg_hash): (Digest512, Stage1Contents, Digest256) = read_disc(
+            config,
+            "A",
+            &format!("Commitment: {}\n\n\
+                      Write this commitment down on paper.\n\n\
+                      Then type the above commitment into the networked machine.\n\n\
+                      The networked machine should produce disc 'A'.\n\n\
+                      When disc 'A' is in the DVD drive, press [ENTER].", comm.to_string()),
+            |f, p| -> Result<_, bincode::rustc_serialize::DecodingError> {
+                let hash_of_commitments: Digest512 = try!(decode_from(f, Infinite));
+                let stage: Stage1Contents = try!(decode_from(f, Infinite));
+
+                Ok((hash_of_commitments, stage, p.unwrap()))
+            }
+        );
 
-    reset();
-    println!("Please wait while disc 'B' is computed... This should take 30 minutes to an hour.");
-    stage1.transform(&privkey);
+        let nizks = pubkey.nizks(&mut chacha_rng, &privkey, &hash_of_commitments);
 
-    let (mut stage2, prev_msg_hash): (Stage2Contents, Digest256) = exchange_disc(
-        "B",
-        "C",
-        |f| {
-            try!(encode_into(&pubkey, f, Infinite));
-            try!(encode_into(&nizks, f, Infinite));
-            try!(encode_into(&stage1, f, Infinite));
+        reset();
+        println!("Please wait while disc 'B' is computed... This should take 30 minutes to an hour.");
+        stage1.transform(&privkey);
 
-            encode_into(&prev_msg_hash, f, Infinite)
-        },
-        |f, p| -> Result<(Stage2Contents, Digest256), bincode::rustc_serialize::DecodingError> {
-            let stage2 = try!(decode_from(f, Infinite));
+        // A universal ceremony ends with stage 1.
+        if config.powers_of_tau.is_some() {
+            write_disc(
+                config,
+                "B",
+                |f| {
+                    try!(encode_into(&pubkey, f, Infinite));
+                    try!(encode_into(&nizks, f, Infinite));
+                    try!(encode_into(&stage1, f, Infinite));
+
+                    encode_into(&prev_msg_hash, f, Infinite)
+                },
+            );
 
-            Ok((stage2, p.unwrap()))
+            return;
         }
-    );
 
-    drop(stage1);
+        let (stage2, prev_msg_hash): (S::Stage2, Digest256) = exchange_disc(
+            config,
+            "B",
+            "C",
+            |f| {
+                try!(encode_into(&pubkey, f, Infinite));
+                try!(encode_into(&nizks, f, Infinite));
+                try!(encode_into(&stage1, f, Infinite));
+
+                encode_into(&prev_msg_hash, f, Infinite)
+            },
+            |f, p| -> Result<(S::Stage2, Digest256), bincode::rustc_serialize::DecodingError> {
+                let stage2 = try!(decode_from(f, Infinite));
+
+                Ok((stage2, p.unwrap()))
+            }
+        );
+
+        drop(stage1);
+
+        (stage2, prev_msg_hash, None)
+    };
 
     reset();
     println!("Please wait while disc 'D' is computed... This should take 45 to 90 minutes.");
     stage2.transform(&privkey);
 
-    let (mut stage3, prev_msg_hash): (Stage3Contents, Digest256) = exchange_disc(
+    let (mut stage3, prev_msg_hash): (S::Stage3, Digest256) = exchange_disc(
+        config,
         "D",
         "E",
         |f| {
+            if let Some(ref nizks) = nizks {
+                try!(encode_into(&pubkey, f, Infinite));
+                try!(encode_into(nizks, f, Infinite));
+            }
             try!(encode_into(&stage2, f, Infinite));
 
             encode_into(&prev_msg_hash, f, Infinite)
         },
-        |f, p| -> Result<(Stage3Contents, Digest256), bincode::rustc_serialize::DecodingError> {
+        |f, p| -> Result<(S::Stage3, Digest256), bincode::rustc_serialize::DecodingError> {
             let stage3 = try!(decode_from(f, Infinite));
 
             Ok((stage3, p.unwrap()))
@@ -141,6 +207,7 @@ fn main() {
     stage3.transform(&privkey);
 
     write_disc(
+        config,
         "F",
         |f| {
             try!(encode_into(&stage3, f, Infinite));
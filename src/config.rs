@@ -0,0 +1,641 @@
+//! Runtime configuration shared by all of the ceremony binaries.
+//!
+//! Every binary starts from its own defaults, which are overridden by a
+//! JSON ceremony config (`--config <path>`), which is in turn overridden
+//! by individual command line flags. The result is validated before the
+//! binary does anything else.
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::process;
+use rustc_serialize::json;
+use protocol::{ProofSystem, CoordinatorKey};
+
+/// Value of `cs` selecting the dummy constraint system, `R1CS::dummy`.
+pub const DUMMY_CS: &'static str = "dummy";
+
+/// Seconds between the heartbeats a network machine sends while its
+/// player is busy with their turn, and the coordinator sends to players
+/// waiting for theirs.
+pub const HEARTBEAT_INTERVAL: u64 = 15;
+
+const USAGE: &'static str = "\
+Options:
+    --config <path>              JSON ceremony config, overridden by the flags below
+    --players <n>                number of players in the ceremony
+    --min-players <n>            abort rather than continue with fewer players
+    --listen <addr>              address the coordinator listens on
+    --coordinator <addr>         address of the coordinator
+    --transport <tcp|unix>       how the network machines connect to the
+                                 coordinator; unix addresses are socket paths
+    --threads <n>                number of worker threads
+    --cs <path|dummy>            constraint system to use
+    --scheme <pghr13|groth16>    zk-SNARK to construct parameters for
+    --powers-of-tau <k>          run stage 1 alone, up to 2^k powers of tau, for
+                                 no constraint system in particular
+    --accumulator <path>         start from a powers of tau accumulator instead
+                                 of stage 1
+    --from-accumulator           the ceremony starts from an accumulator, so
+                                 players have no stage 1 turn
+    --beacon                     end the ceremony with a public random beacon,
+                                 which the coordinator contributes as a final
+                                 player; the operator types `beacon <hex>` once
+                                 the last player's first turn is recorded
+    --beacon-iterations <n>      times the beacon value is hashed
+    --transcript <path>          path of the transcript
+    --directory-prefix <path>    local directory for disc contents, ending in '/'
+    --record-hashes              ask the user to write down disc hashes
+    --no-record-hashes           don't ask the user to write down disc hashes
+    --network-timeout <secs>     read/write timeout on the network connection
+    --unresponsive-timeout <secs>
+                                 how long to wait on a silent player
+    --turn-limits <secs,secs,secs>
+                                 how long a player's turn may take in each stage
+    --on-timeout <skip|wait|abort>
+                                 what the coordinator does when a turn takes
+                                 too long
+    --status <addr>              serve the coordinator's status as JSON over
+                                 HTTP on this address, like 127.0.0.1:8080
+    --metrics <path>             where the coordinator or verifier writes its
+                                 metrics when it finishes
+    --signing-key <path>         the coordinator's long-term signing key,
+                                 created if it doesn't exist
+    --coordinator-key <hex>      the coordinator's published key, which network
+                                 machines check the coordinator against, and
+                                 the verifier the transcript's signatures
+    --verify-window <n>          vector elements the verifier decodes at a time
+    --report <path>              where the verifier writes its JSON report
+    --collect-all                verifier checks everything instead of stopping
+                                 at the first failure
+    --accept-unconfirmed         verifier accepts exclusions it has no
+                                 evidence for, like unresponsive players
+    --legacy-keypair             verifier also writes the keypair in libsnark's
+                                 format, to pk.libsnark and vk.libsnark
+    --help                       print this message";
+
+#[derive(Clone, RustcEncodable, RustcDecodable)]
+pub struct Config {
+    /// Number of players in the ceremony.
+    pub players: usize,
+    /// The coordinator aborts the ceremony rather than continuing with
+    /// fewer players than this after exclusions.
+    pub min_players: usize,
+    /// Address the coordinator listens on.
+    pub listen_addr: String,
+    /// Address of the coordinator, used by the network machine.
+    pub coordinator_addr: String,
+    /// How the network machines connect to the coordinator.
+    pub transport: TransportKind,
+    /// Number of threads used for the heavy lifting.
+    pub threads: usize,
+    /// Path of the R1CS file, or `DUMMY_CS`.
+    pub cs: String,
+    /// The zk-SNARK the ceremony constructs parameters for.
+    pub scheme: ProofSystem,
+    /// If set, the ceremony is a universal one which only computes 2^k
+    /// powers of tau, for this k.
+    pub powers_of_tau: Option<u32>,
+    /// Path of the accumulator of a universal ceremony to take stage 1
+    /// from, if any.
+    pub accumulator: Option<String>,
+    /// Whether the ceremony starts from an accumulator, which is all the
+    /// players' machines need to know about it.
+    pub from_accumulator: bool,
+    /// Whether the ceremony ends with the contribution of a public random
+    /// beacon. Its value is only given once every player has taken their
+    /// first turn, so it isn't part of the config.
+    pub beacon: bool,
+    /// Number of times the beacon value is hashed.
+    pub beacon_iterations: u64,
+    /// Path of the transcript file.
+    pub transcript: String,
+    /// Local directory where disc contents are kept before burning.
+    pub directory_prefix: String,
+    /// Whether the user is asked to write down the hash of every disc.
+    pub ask_user_to_record_hashes: bool,
+    /// Read/write timeout on network connections, in seconds.
+    pub network_timeout: u64,
+    /// How long the coordinator waits on a silent player before
+    /// excluding them, in seconds.
+    pub unresponsive_timeout: u64,
+    /// How long a player's turn may take in each of the three stages, in
+    /// seconds.
+    pub turn_limits: Vec<u64>,
+    /// What the coordinator does when a player's turn takes longer than
+    /// its limit, unless the operator says otherwise.
+    pub on_timeout: TimeoutAction,
+    /// Address of the coordinator's HTTP status endpoint, if it should
+    /// serve one.
+    pub status_addr: Option<String>,
+    /// Path the coordinator or verifier writes its metrics to when it
+    /// finishes, if any.
+    pub metrics: Option<String>,
+    /// Path of the coordinator's long-term key, which it vouches for its
+    /// channel key and signs the transcript with.
+    pub signing_key: String,
+    /// The coordinator's published verifying key, in hex, which network
+    /// machines check the coordinator against, and the verifier the
+    /// transcript's signatures.
+    pub coordinator_key: Option<String>,
+    /// Number of elements of each vector the verifier decodes at a
+    /// time, which bounds its memory use.
+    pub verify_window: usize,
+    /// Path of the verifier's JSON report, if one should be written.
+    pub report: Option<String>,
+    /// Whether the verifier carries on after a failure, to report every
+    /// problem with a transcript at once.
+    pub collect_all: bool,
+    /// Whether the verifier accepts exclusions whose evidence it can't
+    /// check, rather than failing verification.
+    pub accept_unconfirmed: bool,
+    /// Whether the verifier also writes the keypair in libsnark's format.
+    pub legacy_keypair: bool
+}
+
+/// Names the transport between the network machines and the
+/// coordinator in the configuration. The implementations are in
+/// `transport.rs`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, RustcEncodable, RustcDecodable)]
+pub enum TransportKind {
+    Tcp,
+    /// Unix domain sockets, for setups where the coordinator and the
+    /// network machines share a host.
+    Unix
+}
+
+impl TransportKind {
+    pub fn from_name(name: &str) -> Option<TransportKind> {
+        match name {
+            "tcp" => Some(TransportKind::Tcp),
+            "unix" if cfg!(unix) => Some(TransportKind::Unix),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TransportKind::Tcp => "tcp",
+            TransportKind::Unix => "unix"
+        }
+    }
+
+    fn is_valid_addr(&self, addr: &str) -> bool {
+        match *self {
+            TransportKind::Tcp => addr.parse::<SocketAddr>().is_ok(),
+            TransportKind::Unix => !addr.is_empty()
+        }
+    }
+}
+
+/// What the coordinator does when a player's turn takes too long.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, RustcEncodable, RustcDecodable)]
+pub enum TimeoutAction {
+    /// Exclude the player and move on to the next one in line.
+    Skip,
+    /// Give the player as long again.
+    Wait,
+    /// Stop the ceremony, leaving its transcript to be resumed from.
+    Abort
+}
+
+impl TimeoutAction {
+    pub fn from_name(name: &str) -> Option<TimeoutAction> {
+        match name {
+            "skip" => Some(TimeoutAction::Skip),
+            "wait" => Some(TimeoutAction::Wait),
+            "abort" => Some(TimeoutAction::Abort),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TimeoutAction::Skip => "skip",
+            TimeoutAction::Wait => "wait",
+            TimeoutAction::Abort => "abort"
+        }
+    }
+}
+
+/// A ceremony config file, which need not specify everything.
+#[derive(RustcDecodable)]
+struct ConfigFile {
+    players: Option<usize>,
+    min_players: Option<usize>,
+    listen_addr: Option<String>,
+    coordinator_addr: Option<String>,
+    transport: Option<String>,
+    threads: Option<usize>,
+    cs: Option<String>,
+    scheme: Option<String>,
+    powers_of_tau: Option<u32>,
+    accumulator: Option<String>,
+    from_accumulator: Option<bool>,
+    beacon: Option<bool>,
+    beacon_iterations: Option<u64>,
+    transcript: Option<String>,
+    directory_prefix: Option<String>,
+    ask_user_to_record_hashes: Option<bool>,
+    network_timeout: Option<u64>,
+    unresponsive_timeout: Option<u64>,
+    turn_limits: Option<Vec<u64>>,
+    on_timeout: Option<String>,
+    status_addr: Option<String>,
+    metrics: Option<String>,
+    signing_key: Option<String>,
+    coordinator_key: Option<String>,
+    verify_window: Option<usize>,
+    report: Option<String>,
+    collect_all: Option<bool>,
+    accept_unconfirmed: Option<bool>,
+    legacy_keypair: Option<bool>
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            players: 1,
+            min_players: 1,
+            listen_addr: "0.0.0.0:65530".into(),
+            coordinator_addr: "0.0.0.0:65530".into(),
+            transport: TransportKind::Tcp,
+            threads: 8,
+            cs: DUMMY_CS.into(),
+            scheme: ProofSystem::Pghr13,
+            powers_of_tau: None,
+            accumulator: None,
+            from_accumulator: false,
+            beacon: false,
+            beacon_iterations: 1 << 20,
+            transcript: "transcript".into(),
+            directory_prefix: "/".into(),
+            ask_user_to_record_hashes: false,
+            network_timeout: 60,
+            unresponsive_timeout: 6 * 60 * 60,
+            turn_limits: vec![4 * 60 * 60, 2 * 60 * 60, 2 * 60 * 60],
+            on_timeout: TimeoutAction::Skip,
+            status_addr: None,
+            metrics: None,
+            signing_key: "coordinator.key".into(),
+            coordinator_key: None,
+            verify_window: 1 << 16,
+            report: None,
+            collect_all: false,
+            accept_unconfirmed: false,
+            legacy_keypair: false
+        }
+    }
+}
+
+impl Config {
+    /// Builds the configuration from the command line on top of the
+    /// binary's defaults, exiting with a usage message if it is invalid.
+    pub fn load(binary: &str, defaults: Config) -> Config {
+        let args: Vec<String> = env::args().skip(1).collect();
+
+        Config::load_from(binary, defaults, &args)
+    }
+
+    /// As `load`, but from the given arguments rather than the command
+    /// line, for binaries which take some arguments of their own.
+    pub fn load_from(binary: &str, defaults: Config, args: &[String]) -> Config {
+        if args.iter().any(|a| a == "--help") {
+            println!("Usage: {} [options]\n\n{}", binary, USAGE);
+            process::exit(0);
+        }
+
+        match Config::from_args(defaults, args).and_then(|c| c.validate().map(|_| c)) {
+            Ok(config) => config,
+            Err(e) => {
+                println!("{}: {}\n\nUsage: {} [options]\n\n{}", binary, e, binary, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    pub fn use_dummy_cs(&self) -> bool {
+        self.cs == DUMMY_CS
+    }
+
+    fn from_args(mut config: Config, args: &[String]) -> Result<Config, String> {
+        // The config file is applied first so that flags always win,
+        // regardless of the order they're given in.
+        for pair in args.windows(2) {
+            if pair[0] == "--config" {
+                try!(config.apply_file(&pair[1]));
+            }
+        }
+
+        let mut args = args.iter();
+
+        while let Some(flag) = args.next() {
+            match &flag[..] {
+                "--record-hashes" => {
+                    config.ask_user_to_record_hashes = true;
+                    continue;
+                },
+                "--no-record-hashes" => {
+                    config.ask_user_to_record_hashes = false;
+                    continue;
+                },
+                "--from-accumulator" => {
+                    config.from_accumulator = true;
+                    continue;
+                },
+                "--beacon" => {
+                    config.beacon = true;
+                    continue;
+                },
+                "--collect-all" => {
+                    config.collect_all = true;
+                    continue;
+                },
+                "--accept-unconfirmed" => {
+                    config.accept_unconfirmed = true;
+                    continue;
+                },
+                "--legacy-keypair" => {
+                    config.legacy_keypair = true;
+                    continue;
+                },
+                _ => {}
+            }
+
+            let value = try!(args.next().ok_or_else(|| format!("missing value for {}", flag)));
+
+            match &flag[..] {
+                "--config" => {},
+                "--players" => config.players = try!(parse_number(flag, value)),
+                "--min-players" => config.min_players = try!(parse_number(flag, value)),
+                "--listen" => config.listen_addr = value.clone(),
+                "--coordinator" => config.coordinator_addr = value.clone(),
+                "--transport" => config.transport = try!(parse_transport(value)),
+                "--threads" => config.threads = try!(parse_number(flag, value)),
+                "--cs" => config.cs = value.clone(),
+                "--scheme" => config.scheme = try!(parse_scheme(value)),
+                "--powers-of-tau" => config.powers_of_tau = Some(try!(parse_number(flag, value))),
+                "--accumulator" => config.accumulator = Some(value.clone()),
+                "--beacon-iterations" => config.beacon_iterations = try!(parse_number(flag, value)),
+                "--transcript" => config.transcript = value.clone(),
+                "--directory-prefix" => config.directory_prefix = value.clone(),
+                "--network-timeout" => config.network_timeout = try!(parse_number(flag, value)),
+                "--unresponsive-timeout" => config.unresponsive_timeout = try!(parse_number(flag, value)),
+                "--turn-limits" => config.turn_limits = try!(parse_turn_limits(flag, value)),
+                "--on-timeout" => config.on_timeout = try!(parse_timeout_action(value)),
+                "--status" => config.status_addr = Some(value.clone()),
+                "--metrics" => config.metrics = Some(value.clone()),
+                "--signing-key" => config.signing_key = value.clone(),
+                "--coordinator-key" => config.coordinator_key = Some(value.clone()),
+                "--verify-window" => config.verify_window = try!(parse_number(flag, value)),
+                "--report" => config.report = Some(value.clone()),
+                _ => return Err(format!("unknown option {}", flag))
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn apply_file(&mut self, path: &str) -> Result<(), String> {
+        let mut contents = String::new();
+
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("could not read config file {}: {}", path, e)));
+
+        let file: ConfigFile = try!(json::decode(&contents)
+                                    .map_err(|e| format!("invalid config file {}: {}", path, e)));
+
+        if let Some(v) = file.players { self.players = v; }
+        if let Some(v) = file.min_players { self.min_players = v; }
+        if let Some(v) = file.listen_addr { self.listen_addr = v; }
+        if let Some(v) = file.coordinator_addr { self.coordinator_addr = v; }
+        if let Some(v) = file.transport { self.transport = try!(parse_transport(&v)); }
+        if let Some(v) = file.threads { self.threads = v; }
+        if let Some(v) = file.cs { self.cs = v; }
+        if let Some(v) = file.scheme { self.scheme = try!(parse_scheme(&v)); }
+        if let Some(v) = file.powers_of_tau { self.powers_of_tau = Some(v); }
+        if let Some(v) = file.accumulator { self.accumulator = Some(v); }
+        if let Some(v) = file.from_accumulator { self.from_accumulator = v; }
+        if let Some(v) = file.beacon { self.beacon = v; }
+        if let Some(v) = file.beacon_iterations { self.beacon_iterations = v; }
+        if let Some(v) = file.transcript { self.transcript = v; }
+        if let Some(v) = file.directory_prefix { self.directory_prefix = v; }
+        if let Some(v) = file.ask_user_to_record_hashes { self.ask_user_to_record_hashes = v; }
+        if let Some(v) = file.network_timeout { self.network_timeout = v; }
+        if let Some(v) = file.unresponsive_timeout { self.unresponsive_timeout = v; }
+        if let Some(v) = file.turn_limits { self.turn_limits = v; }
+        if let Some(v) = file.on_timeout { self.on_timeout = try!(parse_timeout_action(&v)); }
+        if let Some(v) = file.status_addr { self.status_addr = Some(v); }
+        if let Some(v) = file.metrics { self.metrics = Some(v); }
+        if let Some(v) = file.signing_key { self.signing_key = v; }
+        if let Some(v) = file.coordinator_key { self.coordinator_key = Some(v); }
+        if let Some(v) = file.verify_window { self.verify_window = v; }
+        if let Some(v) = file.report { self.report = Some(v); }
+        if let Some(v) = file.collect_all { self.collect_all = v; }
+        if let Some(v) = file.accept_unconfirmed { self.accept_unconfirmed = v; }
+        if let Some(v) = file.legacy_keypair { self.legacy_keypair = v; }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.players == 0 {
+            return Err("there must be at least one player".into());
+        }
+
+        if self.min_players == 0 || self.min_players > self.players {
+            return Err(format!("min_players must be between 1 and {}", self.players));
+        }
+
+        if self.threads == 0 {
+            return Err("there must be at least one thread".into());
+        }
+
+        if !self.transport.is_valid_addr(&self.listen_addr) {
+            return Err(format!("invalid listen address {}", self.listen_addr));
+        }
+
+        if !self.transport.is_valid_addr(&self.coordinator_addr) {
+            return Err(format!("invalid coordinator address {}", self.coordinator_addr));
+        }
+
+        if !self.use_dummy_cs() && !Path::new(&self.cs).is_file() {
+            return Err(format!("constraint system {} does not exist", self.cs));
+        }
+
+        if let Some(k) = self.powers_of_tau {
+            // Fr has no larger radix-2 domains for a constraint system
+            // to use the powers of tau with.
+            if k == 0 || k > 28 {
+                return Err("the powers of tau must go up to 2^k for k between 1 and 28".into());
+            }
+
+            if self.accumulator.is_some() || self.from_accumulator {
+                return Err("a universal ceremony can't start from an accumulator".into());
+            }
+        }
+
+        if let Some(ref path) = self.accumulator {
+            if !Path::new(path).is_file() {
+                return Err(format!("accumulator {} does not exist", path));
+            }
+        }
+
+        if self.beacon_iterations == 0 {
+            return Err("the beacon must be hashed at least once".into());
+        }
+
+        if !self.directory_prefix.ends_with('/') {
+            return Err("directory prefix must end with '/'".into());
+        }
+
+        if self.network_timeout == 0 || self.unresponsive_timeout == 0 {
+            return Err("timeouts must be nonzero".into());
+        }
+
+        // Otherwise the coordinator's reads time out while a player is
+        // busy with their compute machine.
+        if self.network_timeout <= HEARTBEAT_INTERVAL {
+            return Err(format!("the network timeout must be longer than the {}s between heartbeats", HEARTBEAT_INTERVAL));
+        }
+
+        if self.turn_limits.len() != 3 || self.turn_limits.contains(&0) {
+            return Err("there must be a nonzero turn limit for each of the three stages".into());
+        }
+
+        if let Some(ref addr) = self.status_addr {
+            if addr.parse::<SocketAddr>().is_err() {
+                return Err(format!("invalid status address {}", addr));
+            }
+        }
+
+        if let Some(ref key) = self.coordinator_key {
+            if CoordinatorKey::from_hex(key).is_none() {
+                return Err(format!("invalid coordinator key {}", key));
+            }
+        }
+
+        if self.verify_window == 0 {
+            return Err("the verify window must be nonzero".into());
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_number<T: ::std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value for {}: {}", flag, value))
+}
+
+fn parse_scheme(value: &str) -> Result<ProofSystem, String> {
+    ProofSystem::from_name(value).ok_or_else(|| format!("unknown scheme {}", value))
+}
+
+fn parse_turn_limits(flag: &str, value: &str) -> Result<Vec<u64>, String> {
+    value.split(',').map(|limit| parse_number(flag, limit.trim())).collect()
+}
+
+fn parse_timeout_action(value: &str) -> Result<TimeoutAction, String> {
+    TimeoutAction::from_name(value).ok_or_else(|| format!("unknown timeout action {}", value))
+}
+
+fn parse_transport(value: &str) -> Result<TransportKind, String> {
+    TransportKind::from_name(value).ok_or_else(|| format!("unknown transport {}", value))
+}
+
+#[test]
+fn config_from_args() {
+    let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let config = Config::from_args(Config::default(), &args(&[
+        "--players", "6",
+        "--min-players", "3",
+        "--no-record-hashes",
+        "--threads", "128"
+    ])).unwrap();
+
+    assert_eq!(config.players, 6);
+    assert_eq!(config.min_players, 3);
+    assert_eq!(config.threads, 128);
+    assert!(!config.ask_user_to_record_hashes);
+    assert!(config.scheme == ProofSystem::Pghr13);
+    assert!(config.validate().is_ok());
+
+    let config = Config::from_args(Config::default(), &args(&["--scheme", "groth16"])).unwrap();
+    assert!(config.scheme == ProofSystem::Groth16);
+    assert!(Config::from_args(Config::default(), &args(&["--scheme", "bctv14"])).is_err());
+
+    let config = Config::from_args(Config::default(), &args(&["--powers-of-tau", "21"])).unwrap();
+    assert_eq!(config.powers_of_tau, Some(21));
+    assert!(config.validate().is_ok());
+
+    let config = Config::from_args(Config::default(), &args(&["--powers-of-tau", "29"])).unwrap();
+    assert!(config.validate().is_err());
+
+    let config = Config::from_args(Config::default(), &args(&["--from-accumulator"])).unwrap();
+    assert!(config.from_accumulator);
+    assert!(config.validate().is_ok());
+    let config = Config::from_args(Config::default(), &args(&["--from-accumulator", "--powers-of-tau", "21"])).unwrap();
+    assert!(config.validate().is_err());
+
+    let config = Config::from_args(Config::default(), &args(&["--beacon", "--beacon-iterations", "5"])).unwrap();
+    assert!(config.validate().is_ok());
+    assert!(config.beacon);
+    assert_eq!(config.beacon_iterations, 5);
+
+    let config = Config::from_args(Config::default(), &args(&["--beacon", "--beacon-iterations", "0"])).unwrap();
+    assert!(config.validate().is_err());
+
+    assert!(Config::from_args(Config::default(), &args(&["--players"])).is_err());
+    assert!(Config::from_args(Config::default(), &args(&["--players", "many"])).is_err());
+    assert!(Config::from_args(Config::default(), &args(&["--bogus", "1"])).is_err());
+
+    let config = Config::from_args(Config::default(), &args(&["--min-players", "2"])).unwrap();
+    assert!(config.validate().is_err());
+
+    let config = Config::from_args(Config::default(), &args(&["--listen", "nowhere"])).unwrap();
+    assert!(config.validate().is_err());
+
+    let config = Config::from_args(Config::default(), &args(&[
+        "--transport", "unix",
+        "--listen", "/run/mpc.sock",
+        "--coordinator", "/run/mpc.sock"
+    ])).unwrap();
+    assert!(config.transport == TransportKind::Unix);
+    assert!(config.validate().is_ok());
+    assert!(Config::from_args(Config::default(), &args(&["--transport", "carrier-pigeon"])).is_err());
+
+    let config = Config::from_args(Config::default(), &args(&[
+        "--turn-limits", "7200,3600,3600",
+        "--on-timeout", "wait"
+    ])).unwrap();
+    assert_eq!(config.turn_limits, vec![7200, 3600, 3600]);
+    assert!(config.on_timeout == TimeoutAction::Wait);
+    assert!(config.validate().is_ok());
+
+    let config = Config::from_args(Config::default(), &args(&["--turn-limits", "7200,3600"])).unwrap();
+    assert!(config.validate().is_err());
+    let config = Config::from_args(Config::default(), &args(&["--turn-limits", "7200,0,3600"])).unwrap();
+    assert!(config.validate().is_err());
+    let config = Config::from_args(Config::default(), &args(&["--network-timeout", "10"])).unwrap();
+    assert!(config.validate().is_err());
+    assert!(Config::from_args(Config::default(), &args(&["--turn-limits", "soon"])).is_err());
+    assert!(Config::from_args(Config::default(), &args(&["--on-timeout", "panic"])).is_err());
+
+    let config = Config::from_args(Config::default(), &args(&["--status", "127.0.0.1:8080", "--metrics", "metrics.prom"])).unwrap();
+    assert_eq!(config.status_addr, Some("127.0.0.1:8080".to_string()));
+    assert_eq!(config.metrics, Some("metrics.prom".to_string()));
+    assert!(config.validate().is_ok());
+    let config = Config::from_args(Config::default(), &args(&["--status", "localhost"])).unwrap();
+    assert!(config.validate().is_err());
+
+    let key = ::protocol::SigningKey::new(&mut ::rand::thread_rng()).verifying_key().to_hex();
+    let config = Config::from_args(Config::default(), &args(&["--signing-key", "ceremony.key", "--coordinator-key", &key])).unwrap();
+    assert_eq!(config.signing_key, "ceremony.key");
+    assert_eq!(config.coordinator_key, Some(key));
+    assert!(config.validate().is_ok());
+    let config = Config::from_args(Config::default(), &args(&["--coordinator-key", "c0ffee"])).unwrap();
+    assert!(config.validate().is_err());
+}
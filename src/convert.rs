@@ -0,0 +1,47 @@
+#![allow(non_snake_case, dead_code)]
+
+extern crate bn;
+extern crate rand;
+extern crate snark;
+extern crate crossbeam;
+extern crate rustc_serialize;
+extern crate blake2_rfc;
+extern crate bincode;
+extern crate byteorder;
+
+#[macro_use]
+mod protocol;
+use protocol::*;
+
+use snark::CS;
+use std::env;
+use std::process;
+
+const USAGE: &'static str = "\
+Usage: convert-r1cs <libsnark r1cs> <output>
+
+Converts a constraint system serialized by libsnark to the native format
+the coordinator and verifier load.";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.len() != 2 {
+        println!("{}", USAGE);
+        process::exit(2);
+    }
+
+    let cs = CS::from_file(&args[0]);
+    let r1cs = R1CS::from_libsnark(&cs);
+
+    println!("Inputs: {}", r1cs.num_inputs);
+    println!("Auxiliary variables: {}", r1cs.num_aux);
+    println!("Constraints: {}", r1cs.constraints.len());
+
+    if let Err(e) = r1cs.to_file(&args[1]) {
+        println!("Could not write {}: {}", args[1], e);
+        process::exit(1);
+    }
+
+    println!("Constraint system hash: {}", r1cs.hash().to_string());
+}
@@ -8,6 +8,7 @@ extern crate rustc_serialize;
 extern crate blake2_rfc;
 extern crate bincode;
 extern crate byteorder;
+extern crate snow;
 
 #[macro_use]
 extern crate log;
@@ -22,9 +23,35 @@ use self::protocol::*;
 mod consts;
 use self::consts::*;
 
-use snark::*;
-use std::net::{TcpListener, TcpStream};
+mod exclusion;
+use self::exclusion::*;
+
+mod config;
+use self::config::*;
+
+mod transcript;
+use self::transcript::*;
+
+mod transport;
+use self::transport::*;
+
+mod channel;
+use self::channel::*;
+
+mod wire;
+use self::wire::*;
+
+mod lobby;
+use self::lobby::*;
+
+mod metrics;
+use self::metrics::*;
+
+mod status;
+use self::status::*;
+
 use std::io::{Read, Write};
+use std::fs::File;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::{channel, Sender, Receiver};
@@ -32,93 +59,312 @@ use std::thread;
 use rustc_serialize::{Decodable, Encodable};
 use rustc_serialize::hex::ToHex;
 use bincode::SizeLimit::Infinite;
-use bincode::rustc_serialize::{encode_into, decode_from};
-use std::time::Duration;
+use bincode::rustc_serialize::{encode_into, decode_from, encoded_size};
+use std::time::{Duration, Instant};
+
+const THREADS: usize = 128;
+
+// Number of messages exchanged with a player during each part of the
+// ceremony, which both ends count in their msgids.
+const COMMITMENT_MESSAGES: u64 = 2;
+const STAGE1_MESSAGES: u64 = 7;
+const STAGE2_MESSAGES: u64 = 4;
+const STAGE3_MESSAGES: u64 = 4;
+// In a ceremony started from an accumulator, players reveal their public
+// key in their stage2 turn, which takes this many more messages.
+const REVEAL_MESSAGES: u64 = 3;
+
+/// A player who stopped responding to the coordinator, ran out of time
+/// for their turn, or sent something that can't be what we asked for,
+/// before it knows which stage's `Misbehavior` to blame them for.
+enum Absent {
+    Unresponsive,
+    /// The turn had been given this many seconds.
+    OutOfTime(u64),
+    /// The payload of a message that didn't decode.
+    Malformed(MessageType, Vec<u8>),
+    /// A frame's length, and the limit it went over.
+    Oversized(u64, u64)
+}
 
-const LISTEN_ADDR: &'static str = "0.0.0.0:65530";
-const PLAYERS: usize = 1;
-pub const THREADS: usize = 128;
+impl<S: Scheme> From<Absent> for Misbehavior<S> {
+    fn from(absent: Absent) -> Misbehavior<S> {
+        match absent {
+            Absent::Unresponsive => Misbehavior::Unresponsive,
+            Absent::OutOfTime(secs) => Misbehavior::OutOfTime(secs),
+            // Turns quarantine the payload first, see `read_turn`.
+            Absent::Malformed(ty, payload) => Misbehavior::Malformed(ty.kind(), Digest256::from_reader(&mut &payload[..])),
+            Absent::Oversized(length, limit) => Misbehavior::Oversized(length, limit)
+        }
+    }
+}
 
-#[derive(Clone)]
-struct ConnectionHandler {
-    peers: Arc<Mutex<HashMap<[u8; 8], Option<(TcpStream, u8, u8)>>>>,
-    notifier: Sender<[u8; 8]>
+/// A peer's connection and the msgids we have reached with them, or
+/// `None` once we hang up on them. Each has its own lock, so that writing
+/// to one peer never keeps us from talking to the others.
+type Peer<C> = Arc<Mutex<Option<(SecureConnection<Metered<C>>, u64, u64)>>>;
+
+struct ConnectionHandler<T: Transport> {
+    peers: Arc<Mutex<HashMap<[u8; 8], Peer<T::Connection>>>>,
+    notifier: Sender<[u8; 8]>,
+    /// Players of a resumed ceremony who haven't reconnected yet, and the
+    /// msgid we had reached with them.
+    resumed: Arc<Mutex<HashMap<[u8; 8], u64>>>,
+    /// The identity every peerid has connected with, which it must
+    /// always reconnect with.
+    identities: Arc<Mutex<HashMap<[u8; 8], Digest256>>>,
+    lobby: Lobby,
+    monitor: Monitor,
+    metrics: Metrics,
+    /// Where the operator's beacon value goes, while the coordinator is
+    /// waiting for one.
+    beacon: Arc<Mutex<Option<Sender<Beacon>>>>,
+    /// The long-term key the transcript is signed with.
+    signing_key: Arc<SigningKey>,
+    config: Arc<Config>
 }
 
-impl ConnectionHandler {
-    fn new() -> ConnectionHandler {
+// Not derived, which would require the connections to be `Clone` too.
+impl<T: Transport> Clone for ConnectionHandler<T> {
+    fn clone(&self) -> ConnectionHandler<T> {
+        ConnectionHandler {
+            peers: self.peers.clone(),
+            notifier: self.notifier.clone(),
+            resumed: self.resumed.clone(),
+            identities: self.identities.clone(),
+            lobby: self.lobby.clone(),
+            monitor: self.monitor.clone(),
+            metrics: self.metrics.clone(),
+            beacon: self.beacon.clone(),
+            signing_key: self.signing_key.clone(),
+            config: self.config.clone()
+        }
+    }
+}
+
+impl<T: Transport> ConnectionHandler<T> {
+    fn new(config: Config, signing_key: SigningKey) -> ConnectionHandler<T> {
         let (tx, rx) = channel();
+        let lobby = Lobby::new(config.on_timeout);
+        let metrics = Metrics::new();
 
         let handler = ConnectionHandler {
             peers: Arc::new(Mutex::new(HashMap::new())),
-            notifier: tx
+            notifier: tx,
+            resumed: Arc::new(Mutex::new(HashMap::new())),
+            identities: Arc::new(Mutex::new(HashMap::new())),
+            monitor: Monitor::new(lobby.clone(), metrics.clone()),
+            metrics: metrics,
+            lobby: lobby,
+            beacon: Arc::new(Mutex::new(None)),
+            signing_key: Arc::new(signing_key),
+            config: Arc::new(config)
         };
 
+        if let Some(ref addr) = handler.config.status_addr {
+            match handler.monitor.listen(addr) {
+                Ok(bound) => info!("Serving status on http://{}/status", bound),
+                Err(e) => {
+                    error!("Could not serve status on {}: {}", addr, e);
+                    panic!("cannot recover.");
+                }
+            }
+        }
+
+        {
+            // The operator rearranges the queue by typing commands, and
+            // gives the beacon value when it's asked for.
+            let lobby = handler.lobby.clone();
+            let beacon = handler.beacon.clone();
+            let iterations = handler.config.beacon_iterations;
+            thread::spawn(move || {
+                use std::io::{self, BufRead};
+
+                let stdin = io::stdin();
+                for line in stdin.lock().lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => break
+                    };
+
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+
+                    let mut words = line.split_whitespace();
+
+                    if words.next() == Some("beacon") {
+                        let value = match words.next().and_then(|v| Beacon::from_hex(v, iterations)) {
+                            Some(value) => value,
+                            None => {
+                                warn!("expected the beacon value, in hex");
+                                continue;
+                            }
+                        };
+
+                        match beacon.lock().unwrap().take() {
+                            Some(tx) => { let _ = tx.send(value); },
+                            None => warn!("The coordinator isn't waiting for the beacon value")
+                        }
+
+                        continue;
+                    }
+
+                    match lobby.command(&line) {
+                        Ok(queue) => info!("{}", queue),
+                        Err(e) => warn!("{}", e)
+                    }
+                }
+            });
+        }
+
+        {
+            // Players waiting for their turn may hear nothing else from
+            // us for hours, and would time out and reconnect otherwise.
+            let handler = handler.clone();
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(Duration::from_secs(HEARTBEAT_INTERVAL));
+                    handler.heartbeats();
+                }
+            });
+        }
+
         {
             let handler = handler.clone();
             thread::spawn(move || {
-                handler.run(rx);
+                match handler.config.scheme {
+                    ProofSystem::Pghr13 => handler.run::<Pghr13>(rx),
+                    ProofSystem::Groth16 => handler.run::<Groth16>(rx)
+                }
             });
         }
 
         handler
     }
 
-    fn do_with_stream<T, E, F: FnMut(&mut TcpStream, &mut u8, &u8) -> Result<T, E>>(&self, peerid: &[u8; 8], mut cb: F) -> T
+    /// Runs `cb` on the peer's stream until it succeeds, which is given
+    /// the msgids and the deadline of the current turn, if any.
+    fn do_with_stream<R, F>(&self, peerid: &[u8; 8], mut cb: F) -> Result<R, Absent>
+        where F: FnMut(&mut SecureConnection<Metered<T::Connection>>, &mut u64, &u64, Option<Instant>) -> Result<R, WireError>
     {
         let waittime = Duration::from_secs(10);
+        let deadline = Instant::now() + Duration::from_secs(self.config.unresponsive_timeout);
 
         loop {
-            // The stream is always there, because we put it back
-            // even if it fails.
-            let (mut stream, mut our_msgid, their_msgid): (TcpStream, u8, u8) = {
-                let mut peers = self.peers.lock().unwrap();
-                peers.get_mut(peerid).unwrap().take()
-            }.unwrap();
-
-            let val = cb(&mut stream, &mut our_msgid, &their_msgid);
+            let turn_deadline = self.lobby.deadline();
 
-            {
-                // Put it back
-                let mut peers = self.peers.lock().unwrap();
-                *peers.get_mut(peerid).unwrap() = Some((stream, our_msgid, their_msgid));
+            if turn_deadline.map(|d| Instant::now() >= d).unwrap_or(false) {
+                try!(self.out_of_time(peerid));
+                continue;
             }
 
+            // The stream is always there once the peer has connected,
+            // even if it fails. Players of a resumed ceremony may not
+            // have reconnected yet, though.
+            let val = match self.peer(peerid) {
+                Some(peer) => match *peer.lock().unwrap() {
+                    Some((ref mut stream, ref mut our_msgid, their_msgid)) => {
+                        Some(cb(stream, our_msgid, &their_msgid, turn_deadline))
+                    },
+                    None => None
+                },
+                None => None
+            };
+
+            let val = match val {
+                Some(val) => val,
+                None => {
+                    if Instant::now() > deadline {
+                        warn!("Giving up on peer that never reconnected (peerid={})", peerid.to_hex());
+                        return Err(Absent::Unresponsive);
+                    }
+
+                    thread::sleep(waittime);
+                    continue;
+                }
+            };
+
             match val {
+                // Dealt with at the top of the loop.
+                Err(WireError::Deadline) => continue,
+                Err(WireError::Malformed(ty, e, payload)) => {
+                    error!("Peer sent a malformed {}: {} (peerid={})", ty.name(), e, peerid.to_hex());
+                    return Err(Absent::Malformed(ty, payload));
+                },
+                Err(WireError::TooLong(length, limit)) => {
+                    error!("Peer sent a frame of {} bytes, over the limit of {} (peerid={})", length, limit, peerid.to_hex());
+                    return Err(Absent::Oversized(length, limit));
+                },
+                Err(ref e) if e.is_fatal() => {
+                    // We disagree on where we are in the ceremony, which
+                    // waiting won't fix.
+                    error!("Giving up on peer: {} (peerid={})", e, peerid.to_hex());
+                    return Err(Absent::Unresponsive);
+                },
                 Err(_) => {
+                    if Instant::now() > deadline {
+                        warn!("Giving up on unresponsive peer (peerid={})", peerid.to_hex());
+                        return Err(Absent::Unresponsive);
+                    }
+
+                    self.metrics.inc(&STREAM_RETRIES, &[("peerid", &peerid.to_hex())], 1.0);
                     thread::sleep(waittime);
                 },
                 Ok(v) => {
-                    return v
+                    return Ok(v)
                 }
             }
         }
     }
 
-    fn read<T: Decodable>(&self, peerid: &[u8; 8]) -> T
+    /// Carries out the lobby's timeout action on a player whose turn has
+    /// taken too long.
+    fn out_of_time(&self, peerid: &[u8; 8]) -> Result<(), Absent>
     {
-        self.do_with_stream(peerid, |s, ourid, _| {
-            match decode_from(s, Infinite) {
-                Ok(v) => {
-                    let _ = s.write_all(&NETWORK_ACK);
-                    let _ = s.flush();
+        let elapsed = self.lobby.elapsed().as_secs();
 
-                    *ourid += 1;
-
-                    Ok(v)
-                },
-                Err(e) => {
-                    Err(e)
-                }
+        match self.lobby.on_timeout() {
+            TimeoutAction::Skip => {
+                warn!("Player ran out of time after {}s (peerid={})", elapsed, peerid.to_hex());
+                Err(Absent::OutOfTime(elapsed))
+            },
+            TimeoutAction::Wait => {
+                let extension = self.lobby.extend();
+                warn!("Player ran out of time after {}s, giving them another {}s (peerid={})", elapsed, extension.as_secs(), peerid.to_hex());
+                Ok(())
+            },
+            TimeoutAction::Abort => {
+                error!("Player ran out of time after {}s, aborting the ceremony, which can be resumed from the transcript (peerid={})", elapsed, peerid.to_hex());
+                panic!("cannot recover.");
             }
-        })
+        }
     }
 
-    fn write<T: Encodable>(&self, peerid: &[u8; 8], obj: &T)
+    /// Reads a `ty` of at most `limit` bytes from the peer.
+    fn read<D: Decodable + Encodable>(&self, peerid: &[u8; 8], ty: MessageType, limit: u64) -> Result<D, Absent>
+    {
+        let obj = try!(self.do_with_stream(peerid, |s, ourid, _, deadline| {
+            let obj = try!(match deadline {
+                Some(deadline) => receive_before(s, ty, *ourid + 1, limit, deadline),
+                None => receive(s, ty, *ourid + 1, limit)
+            });
+
+            *ourid += 1;
+
+            Ok(obj)
+        }));
+
+        self.metrics.observe(&MESSAGE_BYTES, &[("direction", "received"), ("type", ty.name())], encoded_size(&obj) as f64);
+
+        Ok(obj)
+    }
+
+    fn write<E: Encodable>(&self, peerid: &[u8; 8], ty: MessageType, obj: &E) -> Result<(), Absent>
     {
         let mut incremented = false;
 
-        self.do_with_stream(peerid, move |s, ourid, theirid| {
+        try!(self.do_with_stream(peerid, move |s, ourid, theirid, _| {
             if !incremented {
                 *ourid += 1;
                 incremented = true;
@@ -129,229 +375,1362 @@ impl ConnectionHandler {
                 return Ok(())
             }
 
-            if encode_into(obj, s, Infinite).is_err() {
-                return Err("couldn't send data".to_string());
+            send(s, ty, *ourid, obj)
+        }));
+
+        self.metrics.observe(&MESSAGE_BYTES, &[("direction", "sent"), ("type", ty.name())], encoded_size(obj) as f64);
+
+        Ok(())
+    }
+
+    /// Reads a message of a player's turn in `stage`. A message that
+    /// doesn't decode is quarantined as the evidence for excluding them.
+    fn read_turn<S: Scheme, D: Decodable + Encodable>(
+        &self,
+        stage: u8,
+        player: usize,
+        peerid: &[u8; 8],
+        ty: MessageType,
+        limit: u64
+    ) -> Result<D, Misbehavior<S>>
+    {
+        self.read(peerid, ty, limit).map_err(|absent| match absent {
+            Absent::Malformed(ty, payload) => {
+                Misbehavior::Malformed(ty.kind(), self.quarantine(stage, player, peerid, &payload))
+            },
+            absent => absent.into()
+        })
+    }
+
+    /// Ends the current turn, recording how long it took.
+    fn end_turn(&self, stage: u8, peerid: &[u8; 8])
+    {
+        let elapsed = self.lobby.elapsed();
+        self.metrics.set(&TURN_SECONDS, &[("stage", &stage.to_string()), ("peerid", &peerid.to_hex())], seconds(elapsed));
+
+        self.lobby.end_turn();
+    }
+
+    fn dump_metrics(&self)
+    {
+        if let Some(ref path) = self.config.metrics {
+            if let Err(e) = self.metrics.dump(path) {
+                warn!("Could not write metrics {}: {}", path, e);
             }
+        }
+    }
+
+    fn turn_limit(&self, stage: u8) -> Duration
+    {
+        Duration::from_secs(self.config.turn_limits[stage as usize - 1])
+    }
+
+    /// Looks up a peer. The map of peers is only locked for as long as
+    /// that takes, and never while talking to one of them.
+    fn peer(&self, peerid: &[u8; 8]) -> Option<Peer<T::Connection>>
+    {
+        self.peers.lock().unwrap().get(peerid).cloned()
+    }
+
+    fn is_connected(&self, peerid: &[u8; 8]) -> bool
+    {
+        match self.peer(peerid) {
+            Some(peer) => match peer.try_lock() {
+                Ok(entry) => entry.is_some(),
+                // Someone is talking to them right now.
+                Err(_) => true
+            },
+            None => false
+        }
+    }
 
-            if s.flush().is_err() {
-                return Err("couldn't flush buffer".to_string());
+    /// Sends a status to a peer, if they're connected. Whether it
+    /// arrives doesn't matter.
+    fn tell(&self, peerid: &[u8; 8], text: &str)
+    {
+        if let Some(peer) = self.peer(peerid) {
+            if let Some((ref mut stream, _, _)) = *peer.lock().unwrap() {
+                let _ = status(stream, text);
             }
+        }
+    }
 
-            let mut ack: [u8; 4] = [0; 4];
-            let _ = s.read_exact(&mut ack);
+    /// Sends a heartbeat to every connected peer whose stream isn't in
+    /// use. Like statuses, whether they arrive doesn't matter.
+    fn heartbeats(&self)
+    {
+        let peers: Vec<Peer<T::Connection>> = self.peers.lock().unwrap().values().cloned().collect();
 
-            if ack != NETWORK_ACK {
-                return Err("bad ack".to_string())
+        for peer in peers {
+            if let Ok(mut entry) = peer.try_lock() {
+                if let Some((ref mut stream, _, _)) = *entry {
+                    let _ = heartbeat(stream);
+                }
             }
+        }
+    }
 
-            Ok(())
-        })
+    /// Waits for the lobby to pick the player who takes the next turn in
+    /// the stage, and tells the others where that leaves them. A player
+    /// the operator excluded is only handed out if excluding them is
+    /// allowed, and otherwise goes back to the front of the queue.
+    fn next_turn(&self, peers: &[[u8; 8]], roster: &Roster) -> Option<Turn>
+    {
+        let patience = Duration::from_secs(self.config.unresponsive_timeout);
+
+        loop {
+            let turn = self.lobby.next(|peerid| self.is_connected(peerid), patience);
+
+            match turn {
+                Some(Turn::Player(i)) => {
+                    info!("Player {} takes their turn (peerid={})", i, peers[i].to_hex());
+
+                    for (peerid, text) in self.lobby.statuses() {
+                        self.tell(&peerid, &text);
+                    }
+                },
+                Some(Turn::Excluded(i)) if roster.remaining() <= self.config.min_players => {
+                    warn!("Excluding player {} would leave fewer than {} players, so they keep their turn (peerid={})",
+                          i, self.config.min_players, peers[i].to_hex());
+                    self.lobby.readmit(i);
+                    continue;
+                },
+                Some(Turn::Excluded(i)) => {
+                    info!("Player {} was excluded by the operator (peerid={})", i, peers[i].to_hex());
+                },
+                None => {}
+            }
+
+            return turn;
+        }
     }
 
-    fn run(&self, new_peers: Receiver<[u8; 8]>)
+    /// Expects a player of a resumed ceremony to reconnect, picking up
+    /// from the msgid we had reached with them. If they already have,
+    /// their connection is brought up to date instead.
+    fn resume_peer(&self, peerid: &[u8; 8], msgid: u64)
     {
-        use std::fs::File;
+        let peer = {
+            // Held until they're in `resumed`, so that they can't
+            // connect in between.
+            let peers = self.peers.lock().unwrap();
+
+            match peers.get(peerid) {
+                Some(peer) => peer.clone(),
+                None => {
+                    self.resumed.lock().unwrap().insert(*peerid, msgid);
+                    return;
+                }
+            }
+        };
 
-        info!("Loading R1CS from disk and performing QAP reduction...");
+        let mut entry = peer.lock().unwrap();
 
-        let cs = {
-            if USE_DUMMY_CS {
-                CS::dummy()
-            } else {
-                CS::from_file()
+        if let Some((_, ref mut our_msgid, _)) = *entry {
+            *our_msgid = msgid;
+        }
+    }
+
+    /// Hangs up on a peer, telling them why. Their reconnection attempts
+    /// will be ignored from now on.
+    fn disconnect(&self, peerid: &[u8; 8], reason: &str)
+    {
+        if let Some(peer) = self.peer(peerid) {
+            let entry = peer.lock().unwrap().take();
+
+            if let Some((mut stream, _, _)) = entry {
+                abort(&mut stream, reason);
+                let _ = stream.shutdown();
             }
+        }
+    }
+
+    /// Sets a rejected contribution, or a message that didn't decode,
+    /// aside on disk so that it can be inspected after the fact, since it
+    /// never makes it into the transcript. Returns the hash of what was
+    /// quarantined, which serves as the evidence in the exclusion record.
+    fn quarantine(&self, stage: u8, player: usize, peerid: &[u8; 8], contents: &[u8]) -> Digest256
+    {
+        use std::fs;
+
+        let path = quarantine_path(stage, player);
+
+        warn!("Quarantining rejected stage{} contribution to {} (peerid={})", stage, path, peerid.to_hex());
+
+        // Losing the quarantined file only leaves the exclusion
+        // unconfirmed, so it's no reason to stop the ceremony.
+        let saved = fs::create_dir_all("quarantine")
+                      .and_then(|_| File::create(&path))
+                      .and_then(|mut f| f.write_all(contents).and_then(|_| f.flush()));
+
+        if let Err(e) = saved {
+            error!("Could not quarantine rejected stage{} contribution to {}: {}", stage, path, e);
+        }
+
+        Digest256::from_reader(&mut &contents[..])
+    }
+
+    /// Drops a player from the ceremony and records why in the transcript,
+    /// unless doing so violates the exclusion policy.
+    fn exclude<S: Scheme>(&self, roster: &mut Roster, transcript: &mut TranscriptWriter<File>, peerid: &[u8; 8], exclusion: Exclusion<S>)
+    {
+        error!("Excluding player {} during stage{}: {} (peerid={})",
+               exclusion.player, exclusion.stage, exclusion.reason.describe(), peerid.to_hex());
+
+        if let Err(e) = roster.exclude(&exclusion) {
+            error!("Cannot exclude player: {}", e);
+            panic!("cannot recover.");
+        }
+
+        self.disconnect(peerid, &format!("excluded during stage{}: {}", exclusion.stage, exclusion.reason.describe()));
+
+        info!("Writing exclusion to transcript");
+        transcript.record(Section::Exclusion(exclusion.stage, exclusion.player), |w| {
+            encode_into(&exclusion, w, Infinite)
+        }).unwrap();
+        transcript.checkpoint().unwrap();
+
+        info!("Continuing with {} remaining players", roster.remaining());
+    }
+
+    /// Reads the public key a player committed to, and their proof that
+    /// they know its secrets, which they send with their first
+    /// transformation.
+    fn read_pubkey<S: Scheme>(
+        &self,
+        stage: u8,
+        player: usize,
+        peerid: &[u8; 8],
+        comm: &Digest256,
+        hash_of_commitments: &Digest512
+    ) -> Result<(S::PublicKey, S::PublicKeyNizks), Misbehavior<S>>
+    {
+        info!("Receiving public key from peerid={}", peerid.to_hex());
+        let pubkey = try!(self.read_turn::<S, S::PublicKey>(stage, player, peerid, MessageType::PublicKey, MAX_MESSAGE));
+
+        info!("Receiving nizks from peerid={}", peerid.to_hex());
+        let nizks = try!(self.read_turn::<S, S::PublicKeyNizks>(stage, player, peerid, MessageType::PublicKeyNizks, MAX_MESSAGE));
+
+        if pubkey.hash() != *comm {
+            error!("Peer did not properly commit to their public key (peerid={})", peerid.to_hex());
+            return Err(Misbehavior::BadCommitment(pubkey));
+        }
+
+        if !nizks.is_valid(&pubkey, hash_of_commitments) {
+            error!("Peer did not provide proof that they possess the secrets! (peerid={})", peerid.to_hex());
+            return Err(Misbehavior::InvalidNizks(pubkey, nizks));
+        }
+
+        Ok((pubkey, nizks))
+    }
+
+    fn stage1_turn<S: Scheme>(
+        &self,
+        player: usize,
+        peerid: &[u8; 8],
+        comm: &Digest256,
+        hash_of_commitments: &Digest512,
+        stage1: &Stage1Contents,
+        last_message_hash: &Digest256
+    ) -> Result<(S::PublicKey, S::PublicKeyNizks, Stage1Contents, Digest256), Misbehavior<S>>
+    {
+        info!("Sending stage1 to peerid={}", peerid.to_hex());
+
+        try!(self.write(peerid, MessageType::HashOfCommitments, hash_of_commitments));
+        try!(self.write(peerid, MessageType::Stage1, stage1));
+        try!(self.write(peerid, MessageType::LastMessageHash, last_message_hash));
+
+        let (pubkey, nizks) = try!(self.read_pubkey::<S>(1, player, peerid, comm, hash_of_commitments));
+
+        info!("Receiving stage1 transformation from peerid={}", peerid.to_hex());
+        // A transformation is exactly as long as what it transforms.
+        let new_stage1 = try!(self.read_turn::<S, Stage1Contents>(1, player, peerid, MessageType::Stage1, encoded_size(stage1)));
+
+        let ihash = try!(self.read_turn::<S, Digest256>(1, player, peerid, MessageType::Ihash, MAX_MESSAGE));
+
+        info!("Verifying transformation of stage1 from peerid={}", peerid.to_hex());
+
+        let start = Instant::now();
+        let valid = new_stage1.verify_transform(stage1, &pubkey);
+        self.metrics.observe(&VERIFY_TRANSFORM_SECONDS, &[("stage", "1")], seconds(start.elapsed()));
+
+        if !valid {
+            error!("Peer did not perform valid stage1 transformation (peerid={})", peerid.to_hex());
+
+            let mut evidence = vec![];
+            encode_into(&pubkey, &mut evidence, Infinite).unwrap();
+            encode_into(&nizks, &mut evidence, Infinite).unwrap();
+            encode_into(&new_stage1, &mut evidence, Infinite).unwrap();
+            encode_into(&ihash, &mut evidence, Infinite).unwrap();
+
+            return Err(Misbehavior::InvalidTransform(self.quarantine(1, player, peerid, &evidence)));
+        }
+
+        Ok((pubkey, nizks, new_stage1, ihash))
+    }
+
+    /// A player without a `pubkey` yet, because the ceremony started from
+    /// an accumulator and they had no stage1 turn, reveals it first, and
+    /// it is returned along with their nizks.
+    fn stage2_turn<S: Scheme>(
+        &self,
+        player: usize,
+        peerid: &[u8; 8],
+        pubkey: Option<&S::PublicKey>,
+        comm: &Digest256,
+        hash_of_commitments: &Digest512,
+        stage2: &S::Stage2,
+        last_message_hash: &Digest256
+    ) -> Result<(Option<(S::PublicKey, S::PublicKeyNizks)>, S::Stage2, Digest256), Misbehavior<S>>
+    {
+        info!("Sending stage2 to peerid={}", peerid.to_hex());
+
+        if pubkey.is_none() {
+            try!(self.write(peerid, MessageType::HashOfCommitments, hash_of_commitments));
+        }
+        try!(self.write(peerid, MessageType::Stage2, stage2));
+        try!(self.write(peerid, MessageType::LastMessageHash, last_message_hash));
+
+        let revealed = match pubkey {
+            Some(_) => None,
+            None => Some(try!(self.read_pubkey::<S>(2, player, peerid, comm, hash_of_commitments)))
         };
 
-        info!("Creating transcript file...");
-        let mut transcript = File::create("transcript").unwrap();
-        encode_into(&PLAYERS, &mut transcript, Infinite).unwrap();
+        info!("Receiving stage2 transformation from peerid={}", peerid.to_hex());
 
-        info!("Waiting for players to connect...");
+        let new_stage2 = try!(self.read_turn::<S, S::Stage2>(2, player, peerid, MessageType::Stage2, encoded_size(stage2)));
+        let ihash = try!(self.read_turn::<S, Digest256>(2, player, peerid, MessageType::Ihash, MAX_MESSAGE));
 
-        let mut peers = vec![];
-        let mut commitments: Vec<Digest256> = vec![];
-        for peerid in new_peers.into_iter().take(PLAYERS) {
-            info!("Initializing new player (peerid={})", peerid.to_hex());
-            info!("Asking for commitment to PublicKey (peerid={})", peerid.to_hex());
-            let comm: Digest256 = self.read(&peerid);
-            info!("PublicKey Commitment received (peerid={})", peerid.to_hex());
+        info!("Verifying transformation of stage2 from peerid={}", peerid.to_hex());
 
-            info!("Writing commitment to transcript");
-            encode_into(&comm, &mut transcript, Infinite).unwrap();
+        let start = Instant::now();
+        let valid = new_stage2.verify_transform(stage2, pubkey.unwrap_or_else(|| &revealed.as_ref().unwrap().0));
+        self.metrics.observe(&VERIFY_TRANSFORM_SECONDS, &[("stage", "2")], seconds(start.elapsed()));
 
-            commitments.push(comm);
-            peers.push(peerid);
+        if !valid {
+            error!("Peer did not perform valid stage2 transformation (peerid={})", peerid.to_hex());
+
+            let mut evidence = vec![];
+            if let Some((ref pubkey, ref nizks)) = revealed {
+                encode_into(pubkey, &mut evidence, Infinite).unwrap();
+                encode_into(nizks, &mut evidence, Infinite).unwrap();
+            }
+            encode_into(&new_stage2, &mut evidence, Infinite).unwrap();
+            encode_into(&ihash, &mut evidence, Infinite).unwrap();
+
+            return Err(Misbehavior::InvalidTransform(self.quarantine(2, player, peerid, &evidence)));
         }
 
-        // The remote end should never hang up, so this should always be `PLAYERS`.
-        assert_eq!(peers.len(), PLAYERS);
+        Ok((revealed, new_stage2, ihash))
+    }
+
+    fn stage3_turn<S: Scheme>(
+        &self,
+        player: usize,
+        peerid: &[u8; 8],
+        pubkey: &S::PublicKey,
+        stage3: &S::Stage3,
+        last_message_hash: &Digest256
+    ) -> Result<(S::Stage3, Digest256), Misbehavior<S>>
+    {
+        info!("Sending stage3 to peerid={}", peerid.to_hex());
 
-        // Hash of all the commitments.
-        let hash_of_commitments = Digest512::from(&commitments).unwrap();
+        try!(self.write(peerid, MessageType::Stage3, stage3));
+        try!(self.write(peerid, MessageType::LastMessageHash, last_message_hash));
 
-        info!("All players are ready");
+        info!("Receiving stage3 transformation from peerid={}", peerid.to_hex());
 
-        // Hash of the last message
-        let mut last_message_hash = Digest256::from(&commitments).unwrap();
+        let new_stage3 = try!(self.read_turn::<S, S::Stage3>(3, player, peerid, MessageType::Stage3, encoded_size(stage3)));
+        let ihash = try!(self.read_turn::<S, Digest256>(3, player, peerid, MessageType::Ihash, MAX_MESSAGE));
+
+        info!("Verifying transformation of stage3 from peerid={}", peerid.to_hex());
 
-        info!("Initializing stage1 with constraint system");
+        let start = Instant::now();
+        let valid = new_stage3.verify_transform(stage3, pubkey);
+        self.metrics.observe(&VERIFY_TRANSFORM_SECONDS, &[("stage", "3")], seconds(start.elapsed()));
 
-        let mut stage1 = Stage1Contents::new(&cs);
-        for (comm, peerid) in commitments.iter().zip(peers.iter()) {
-            info!("Sending stage1 to peerid={}", peerid.to_hex());
+        if !valid {
+            error!("Peer did not perform valid stage3 transformation (peerid={})", peerid.to_hex());
 
-            self.write(peerid, &hash_of_commitments);
-            self.write(peerid, &stage1);
-            self.write(peerid, &last_message_hash);
+            let mut evidence = vec![];
+            encode_into(&new_stage3, &mut evidence, Infinite).unwrap();
+            encode_into(&ihash, &mut evidence, Infinite).unwrap();
 
-            info!("Receiving public key from peerid={}", peerid.to_hex());
-            let pubkey = self.read::<PublicKey>(peerid);
+            return Err(Misbehavior::InvalidTransform(self.quarantine(3, player, peerid, &evidence)));
+        }
 
-            info!("Receiving nizks from peerid={}", peerid.to_hex());
-            let nizks = self.read::<PublicKeyNizks>(peerid);
+        Ok((new_stage3, ihash))
+    }
+
+    /// Waits for the operator to type the beacon value, which they only
+    /// do once it's known, after the last player's first turn.
+    fn await_beacon(&self, stage: u8) -> Beacon {
+        let (tx, rx) = channel();
+        *self.beacon.lock().unwrap() = Some(tx);
+
+        info!("Every player has contributed to stage{}. Type `beacon <hex>` once the beacon value is known.", stage);
+
+        let beacon = rx.recv().unwrap();
+        info!("Using beacon {} ({} iterations)", beacon.to_hex(), beacon.iterations);
+
+        beacon
+    }
+
+    /// The beacon of a ceremony which has one, replayed from the
+    /// transcript being resumed, or awaited once the players have taken
+    /// their turns in `stage` and recorded.
+    fn beacon(
+        &self,
+        stage: u8,
+        replay: &mut Option<TranscriptReader<File>>,
+        replayed: &mut usize,
+        transcript: &mut TranscriptWriter<File>,
+        peers: &[[u8; 8]],
+        roster: &Roster,
+        msgids: &[u64]
+    ) -> Option<Beacon>
+    {
+        let replay_beacon = replay.as_ref().map(|r| r.contains(Section::Beacon)).unwrap_or(false);
+
+        if replay_beacon {
+            let beacon: Beacon = decode_from(&mut replay.as_mut().unwrap().seek(Section::Beacon).unwrap(), Infinite).unwrap();
+            info!("Replaying beacon {} ({} iterations)", beacon.to_hex(), beacon.iterations);
+            *replayed += 1;
+
+            Some(beacon)
+        } else if self.config.beacon {
+            self.end_replay(replay, *replayed, peers, roster, msgids);
+
+            let beacon = self.await_beacon(stage);
+
+            info!("Writing beacon to transcript");
+            transcript.record(Section::Beacon, |w| {
+                encode_into(&beacon, w, Infinite)
+            }).unwrap();
+            transcript.checkpoint().unwrap();
+
+            Some(beacon)
+        } else {
+            None
+        }
+    }
 
-            if pubkey.hash() != *comm {
-                error!("Peer did not properly commit to their public key (peerid={})", peerid.to_hex());
+    /// Finishes replaying the transcript of a resumed ceremony, which
+    /// happens right before the first live turn, and lets the remaining
+    /// players reconnect.
+    fn end_replay(
+        &self,
+        replay: &mut Option<TranscriptReader<File>>,
+        replayed: usize,
+        peers: &[[u8; 8]],
+        roster: &Roster,
+        msgids: &[u64]
+    )
+    {
+        if let Some(reader) = replay.take() {
+            if reader.records() != replayed {
+                error!("Transcript being resumed contains unexpected records");
                 panic!("cannot recover.");
             }
 
-            if !nizks.is_valid(&pubkey, &hash_of_commitments) {
-                error!("Peer did not provide proof that they possess the secrets! (peerid={})", peerid.to_hex());
+            info!("Replayed {} records from the transcript, resuming ceremony", replayed);
+
+            for (i, peerid) in peers.iter().enumerate() {
+                if roster.is_active(i) {
+                    self.resume_peer(peerid, msgids[i]);
+                }
+            }
+        }
+    }
+
+    fn run<S: Scheme>(&self, new_peers: Receiver<[u8; 8]>)
+    {
+        use std::fs::OpenOptions;
+        use std::path::Path;
+
+        // A universal ceremony has no constraint system, and ends
+        // with stage 1.
+        let (qap, params, initial_stage1) = match self.config.powers_of_tau {
+            Some(k) => {
+                info!("Running a universal ceremony for 2^{} powers of tau", k);
+
+                let params = CeremonyParams::universal(k, &self.config);
+                let stage1 = Stage1Contents::with_powers(params.powers);
+
+                (None, params, stage1)
+            },
+            None => {
+                info!("Loading R1CS from disk and performing QAP reduction...");
+
+                let r1cs = {
+                    if self.config.use_dummy_cs() {
+                        R1CS::dummy()
+                    } else {
+                        R1CS::from_file(&self.config.cs).unwrap_or_else(|e| {
+                            panic!("could not load constraint system {}: {}", self.config.cs, e)
+                        })
+                    }
+                };
+                let qap = QAP::new(r1cs).expect("constraint system should reduce to a radix-2 QAP");
+
+                let accumulator = self.config.accumulator.as_ref().map(|path| {
+                    info!("Loading powers of tau accumulator from {}...", path);
+
+                    let accumulator = Accumulator::from_file(path).unwrap_or_else(|e| {
+                        panic!("could not load accumulator {}: {}", path, e)
+                    });
+
+                    if !accumulator.is_well_formed() {
+                        panic!("accumulator {} does not contain powers of tau", path);
+                    }
+
+                    accumulator
+                });
+
+                let params = CeremonyParams::new(&qap, accumulator.as_ref(), &self.config);
+                let stage1 = match accumulator {
+                    Some(ref accumulator) => accumulator.stage1(params.powers).unwrap_or_else(|| {
+                        panic!("the accumulator has fewer than the {} powers of tau the constraint system needs", params.powers)
+                    }),
+                    None => Stage1Contents::new::<S>(&qap)
+                };
+
+                (Some(qap), params, stage1)
+            }
+        };
+
+        let players = self.config.players;
+        let key = (*self.signing_key).clone();
+
+        // The transcript of an interrupted ceremony is replayed to
+        // reconstruct where we were, and then appended to.
+        let mut replay: Option<TranscriptReader<File>> = None;
+        let mut replayed = 0;
+
+        let mut transcript = if Path::new(&self.config.transcript).exists() {
+            info!("Found existing transcript, replaying it to resume the ceremony...");
+
+            let mut reader = TranscriptReader::open(File::open(&self.config.transcript).unwrap()).unwrap();
+
+            if reader.is_complete() {
+                error!("The transcript is already complete, there is nothing to resume.");
                 panic!("cannot recover.");
             }
 
-            info!("Receiving stage1 transformation from peerid={}", peerid.to_hex());
-            let new_stage1 = self.read::<Stage1Contents>(peerid);
+            if reader.header().params != params {
+                error!("The transcript is for a different constraint system or scheme.");
+                panic!("cannot recover.");
+            }
 
-            let ihash = self.read::<Digest256>(peerid);
+            if reader.header().config.players != players ||
+               reader.header().config.min_players != self.config.min_players
+            {
+                error!("The transcript was started with a different number of players.");
+                panic!("cannot recover.");
+            }
 
-            if !new_stage1.is_well_formed(&stage1) {
-                error!("Peer did not perform valid stage1 transformation (peerid={})", peerid.to_hex());
+            if reader.header().config.beacon != self.config.beacon {
+                error!("The transcript was started {} a beacon.", if self.config.beacon { "without" } else { "with" });
                 panic!("cannot recover.");
-            } else {
-                info!("Writing `PublicKey` to transcript");
-                encode_into(&pubkey, &mut transcript, Infinite).unwrap();
-                info!("Writing `PublicKeyNizks` to transcript");
-                encode_into(&nizks, &mut transcript, Infinite).unwrap();
-                info!("Writing new stage1 to transcript");
-                encode_into(&new_stage1, &mut transcript, Infinite).unwrap();
+            }
+
+            let f = OpenOptions::new().read(true).write(true).open(&self.config.transcript).unwrap();
+            let writer = match TranscriptWriter::resume(f, &mut reader, key) {
+                Ok(writer) => writer,
+                Err(e) => {
+                    error!("The transcript can't be resumed with this signing key: {}", e);
+                    panic!("cannot recover.");
+                }
+            };
+            replay = Some(reader);
 
-                encode_into(&ihash, &mut transcript, Infinite).unwrap();
+            writer
+        } else {
+            info!("Creating transcript file...");
+
+            TranscriptWriter::create(
+                File::create(&self.config.transcript).unwrap(),
+                &TranscriptHeader {
+                    config: (*self.config).clone(),
+                    params: params.clone()
+                },
+                key
+            ).unwrap()
+        };
+
+        let replay_commitments = replay.as_ref().map(|r| r.contains(Section::Commitments)).unwrap_or(false);
+
+        let (peers, commitments): (Vec<[u8; 8]>, Vec<Digest256>) = if replay_commitments {
+            info!("Replaying commitments");
+
+            let mut f = replay.as_mut().unwrap().seek(Section::Commitments).unwrap();
+            let commitments: Vec<Digest256> = decode_from(&mut f, Infinite).unwrap();
+            let peers: Vec<[u8; 8]> = decode_from(&mut f, Infinite).unwrap();
+            let identities: Vec<Digest256> = decode_from(&mut f, Infinite).unwrap();
+            let availabilities: Vec<Availability> = decode_from(&mut f, Infinite).unwrap();
+            replayed += 1;
+
+            for ((peerid, availability), comm) in peers.iter().zip(availabilities).zip(&commitments) {
+                self.lobby.register(*peerid, availability);
+                self.monitor.registered(peerid, comm);
+            }
+
+            // Players may only reconnect with the identity they
+            // committed with.
+            self.identities.lock().unwrap().extend(peers.iter().cloned().zip(identities));
+
+            // Only the players in the transcript may take part.
+            drop(new_peers);
+
+            (peers, commitments)
+        } else {
+            info!("Waiting for players to connect...");
+
+            let mut peers = vec![];
+            let mut commitments: Vec<Digest256> = vec![];
+            let mut availabilities: Vec<Availability> = vec![];
+            for peerid in new_peers.iter() {
+                info!("Initializing new player (peerid={})", peerid.to_hex());
+                info!("Asking for commitment to PublicKey (peerid={})", peerid.to_hex());
+                let registration = self.read(&peerid, MessageType::Commitment, MAX_MESSAGE).and_then(|comm: Digest256| {
+                    let availability: Availability = try!(self.read(&peerid, MessageType::Availability, MAX_MESSAGE));
+
+                    Ok((comm, availability))
+                });
+
+                let (comm, availability) = match registration {
+                    Ok(registration) => registration,
+                    Err(_) => {
+                        // Nothing has been recorded about this player yet, so
+                        // we can just wait for somebody else instead.
+                        warn!("Player never finished registering, dropping them (peerid={})", peerid.to_hex());
+                        self.disconnect(&peerid, "never finished registering");
+                        continue;
+                    }
+                };
+                info!("PublicKey Commitment received (peerid={})", peerid.to_hex());
+
+                if !availability.is_valid() {
+                    warn!("Player sent windows of availability that end before they start, dropping them (peerid={})", peerid.to_hex());
+                    self.disconnect(&peerid, "windows of availability must end after they start");
+                    continue;
+                }
+
+                let player = self.lobby.register(peerid, availability.clone());
+                self.monitor.registered(&peerid, &comm);
+
+                commitments.push(comm);
+                peers.push(peerid);
+                availabilities.push(availability);
+
+                if peers.len() == players {
+                    break;
+                }
+
+                self.tell(&peerid, &format!("Registered as player {}, waiting for {} more players to register.", player, players - peers.len()));
+            }
+
+            let identities: Vec<Digest256> = {
+                let identities = self.identities.lock().unwrap();
+
+                peers.iter().map(|peerid| identities[peerid]).collect()
+            };
+
+            info!("Writing commitments to transcript");
+            transcript.record(Section::Commitments, |w| {
+                try!(encode_into(&commitments, w, Infinite));
+                try!(encode_into(&peers, w, Infinite));
+                try!(encode_into(&identities, w, Infinite));
+
+                encode_into(&availabilities, w, Infinite)
+            }).unwrap();
+            transcript.checkpoint().unwrap();
+
+            (peers, commitments)
+        };
+
+        // The remote end should never hang up, so this should always be `players`.
+        assert_eq!(peers.len(), players);
+        assert_eq!(commitments.len(), players);
+
+        // Hash of all the commitments.
+        let hash_of_commitments = params.hash_of_commitments(&commitments);
+
+        info!("All players are ready");
+
+        // Hash of the last message
+        let mut last_message_hash = params.initial_message_hash(&commitments);
+
+        let mut roster = Roster::new(players, self.config.min_players);
+        let mut pubkeys: Vec<Option<S::PublicKey>> = vec![None; players];
+        let mut msgids = vec![COMMITMENT_MESSAGES; players];
+
+        let mut stage1 = initial_stage1;
+
+        // A ceremony started from an accumulator takes its stage1 from
+        // it, so the players have no turn in stage1, and reveal their
+        // public keys in their stage2 turn instead.
+        let accumulated = params.accumulator.is_some();
+
+        // The turns in the transcript being resumed are replayed in the
+        // order they were taken, and the lobby schedules the rest.
+        let turns = if accumulated { vec![] } else { replayed_turns(&replay, 1, players) };
+
+        for &i in &turns {
+            let reader = replay.as_mut().unwrap();
+
+            if reader.contains(Section::Exclusion(1, i)) {
+                replay_exclusion::<S>(reader, &mut roster, 1, i);
+            } else {
+                let (pubkey, nizks, new_stage1, ihash) = replay_stage1::<S>(reader, i, &commitments[i], &hash_of_commitments, &stage1);
 
                 last_message_hash = digest256_from_parts!(
                     pubkey, nizks, new_stage1, ihash
                 );
+                self.monitor.accepted(i, 1, &last_message_hash);
 
                 stage1 = new_stage1;
+                pubkeys[i] = Some(pubkey);
+                msgids[i] += STAGE1_MESSAGES;
             }
+
+            replayed += 1;
         }
 
-        info!("Initializing stage2 with constraint system and stage1");
+        let waiting: Vec<usize> = (0..players).filter(|i| !accumulated && !turns.contains(i)).collect();
 
-        let mut stage2 = Stage2Contents::new(&cs, &stage1);
-        for peerid in peers.iter() {
-            info!("Sending stage2 to peerid={}", peerid.to_hex());
+        if !waiting.is_empty() {
+            self.end_replay(&mut replay, replayed, &peers, &roster, &msgids);
+        }
 
-            self.write(peerid, &stage2);
-            self.write(peerid, &last_message_hash);
+        self.lobby.begin_stage(1, waiting, self.turn_limit(1));
 
-            info!("Receiving stage2 transformation from peerid={}", peerid.to_hex());
+        while let Some(turn) = self.next_turn(&peers, &roster) {
+            let i = turn.player();
+            let peerid = &peers[i];
 
-            let new_stage2 = self.read::<Stage2Contents>(peerid);
-            let ihash = self.read::<Digest256>(peerid);
+            let result = match turn {
+                Turn::Player(_) => self.stage1_turn::<S>(i, peerid, &commitments[i], &hash_of_commitments, &stage1, &last_message_hash),
+                Turn::Excluded(_) => Err(Misbehavior::Operator)
+            };
 
-            if !new_stage2.is_well_formed(&stage2) {
-                error!("Peer did not perform valid stage2 transformation (peerid={})", peerid.to_hex());
-                panic!("cannot recover.");
+            match result {
+                Ok((pubkey, nizks, new_stage1, ihash)) => {
+                    info!("Writing `PublicKey`, `PublicKeyNizks` and new stage1 to transcript");
+                    transcript.record(Section::Contribution(1, i), |w| {
+                        try!(encode_into(&pubkey, w, Infinite));
+                        try!(encode_into(&nizks, w, Infinite));
+                        try!(encode_into(&new_stage1, w, Infinite));
+
+                        encode_into(&ihash, w, Infinite)
+                    }).unwrap();
+                    transcript.checkpoint().unwrap();
+
+                    last_message_hash = digest256_from_parts!(
+                        pubkey, nizks, new_stage1, ihash
+                    );
+                    self.monitor.accepted(i, 1, &last_message_hash);
+
+                    stage1 = new_stage1;
+                    pubkeys[i] = Some(pubkey);
+                },
+                Err(reason) => {
+                    self.exclude(&mut roster, &mut transcript, peerid, Exclusion {
+                        player: i,
+                        stage: 1,
+                        reason: reason
+                    });
+                }
+            }
+
+            self.end_turn(1, peerid);
+        }
+
+        // The beacon contributes after everyone else, with secrets
+        // anyone can recompute from its value, once that is public,
+        // which is after the players' first turns.
+        let beacon = if accumulated {
+            None
+        } else {
+            self.beacon(1, &mut replay, &mut replayed, &mut transcript, &peers, &roster, &msgids)
+        };
+
+        let mut beacon_keys: Option<(S::PrivateKey, S::PublicKey)> = None;
+
+        if let Some(beacon) = beacon {
+            info!("Deriving the beacon's secrets from {} ({} iterations)", beacon.to_hex(), beacon.iterations);
+            let (privkey, pubkey, nizks) = beacon.contribution::<S>(&hash_of_commitments);
+
+            let replay_contribution = replay.as_ref().map(|r| r.contains(Section::Contribution(1, players))).unwrap_or(false);
+
+            let (nizks, new_stage1, ihash) = if replay_contribution {
+                let (_, nizks, new_stage1, ihash) = replay_stage1::<S>(replay.as_mut().unwrap(), players, &pubkey.hash(), &hash_of_commitments, &stage1);
+                replayed += 1;
+
+                (nizks, new_stage1, ihash)
             } else {
-                info!("Writing new stage2 to transcript");
-                encode_into(&new_stage2, &mut transcript, Infinite).unwrap();
-                encode_into(&ihash, &mut transcript, Infinite).unwrap();
+                self.end_replay(&mut replay, replayed, &peers, &roster, &msgids);
 
-                last_message_hash = digest256_from_parts!(
-                    new_stage2, ihash
-                );
+                info!("Transforming stage1 with the beacon's secrets");
+                let ihash = digest256_from_parts!(hash_of_commitments, stage1, last_message_hash);
+                let mut new_stage1 = stage1.clone();
+                new_stage1.transform(&privkey);
+
+                info!("Writing the beacon's contribution to transcript");
+                transcript.record(Section::Contribution(1, players), |w| {
+                    try!(encode_into(&pubkey, w, Infinite));
+                    try!(encode_into(&nizks, w, Infinite));
+                    try!(encode_into(&new_stage1, w, Infinite));
+
+                    encode_into(&ihash, w, Infinite)
+                }).unwrap();
+                transcript.checkpoint().unwrap();
+
+                (nizks, new_stage1, ihash)
+            };
+
+            last_message_hash = digest256_from_parts!(
+                pubkey, nizks, new_stage1, ihash
+            );
+
+            stage1 = new_stage1;
+            beacon_keys = Some((privkey, pubkey));
+        }
+
+        let qap = match qap {
+            Some(qap) => qap,
+            None => {
+                self.end_replay(&mut replay, replayed, &peers, &roster, &msgids);
+
+                info!("Powers of tau complete, flushing transcript to disk.");
+
+                transcript.finish().unwrap();
+                self.dump_metrics();
+
+                info!("Transcript flushed to disk. The verifier exports the accumulator.");
+
+                return;
+            }
+        };
+
+        info!("Initializing stage2 with constraint system and stage1");
+
+        let mut stage2 = S::stage2(&qap, &stage1);
+        let turns = replayed_turns(&replay, 2, players);
+
+        for &i in &turns {
+            let reader = replay.as_mut().unwrap();
+
+            if reader.contains(Section::Exclusion(2, i)) {
+                replay_exclusion::<S>(reader, &mut roster, 2, i);
+            } else {
+                let (revealed, new_stage2, ihash) = replay_stage2::<S>(reader, i, pubkeys[i].as_ref(), &commitments[i], &hash_of_commitments, &stage2);
+
+                last_message_hash = stage2_message_hash::<S>(&revealed, &new_stage2, &ihash);
+                self.monitor.accepted(i, 2, &last_message_hash);
+
+                if let Some((pubkey, _)) = revealed {
+                    pubkeys[i] = Some(pubkey);
+                    msgids[i] += REVEAL_MESSAGES;
+                }
 
                 stage2 = new_stage2;
+                msgids[i] += STAGE2_MESSAGES;
             }
+
+            replayed += 1;
         }
 
-        info!("Initializing stage3 with constraint system and stage2");
+        let waiting: Vec<usize> = (0..players).filter(|&i| roster.is_active(i) && !turns.contains(&i)).collect();
 
-        let mut stage3 = Stage3Contents::new(&cs, &stage2);
-        for peerid in peers.iter() {
-            info!("Sending stage3 to peerid={}", peerid.to_hex());
+        if !waiting.is_empty() {
+            self.end_replay(&mut replay, replayed, &peers, &roster, &msgids);
+        }
 
-            self.write(peerid, &stage3);
-            self.write(peerid, &last_message_hash);
+        self.lobby.begin_stage(2, waiting, self.turn_limit(2));
 
-            info!("Receiving stage3 transformation from peerid={}", peerid.to_hex());
+        while let Some(turn) = self.next_turn(&peers, &roster) {
+            let i = turn.player();
+            let peerid = &peers[i];
 
-            let new_stage3 = self.read::<Stage3Contents>(peerid);
-            let ihash = self.read::<Digest256>(peerid);
+            let result = match turn {
+                Turn::Player(_) => self.stage2_turn::<S>(i, peerid, pubkeys[i].as_ref(), &commitments[i], &hash_of_commitments, &stage2, &last_message_hash),
+                Turn::Excluded(_) => Err(Misbehavior::Operator)
+            };
 
-            info!("Verifying transformation of stage3 from peerid={}", peerid.to_hex());
+            match result {
+                Ok((revealed, new_stage2, ihash)) => {
+                    info!("Writing new stage2 to transcript");
+                    record_stage2::<S>(&mut transcript, i, &revealed, &new_stage2, &ihash);
 
-            if !new_stage3.is_well_formed(&stage3) {
-                error!("Peer did not perform valid stage3 transformation (peerid={})", peerid.to_hex());
-                panic!("cannot recover.");
+                    last_message_hash = stage2_message_hash::<S>(&revealed, &new_stage2, &ihash);
+                    self.monitor.accepted(i, 2, &last_message_hash);
+
+                    if let Some((pubkey, _)) = revealed {
+                        pubkeys[i] = Some(pubkey);
+                    }
+
+                    stage2 = new_stage2;
+                },
+                Err(reason) => {
+                    self.exclude(&mut roster, &mut transcript, peerid, Exclusion {
+                        player: i,
+                        stage: 2,
+                        reason: reason
+                    });
+                }
+            }
+
+            self.end_turn(2, peerid);
+        }
+
+        // Without a stage1 turn, the beacon reveals its public key in
+        // its stage2 turn too.
+        let mut beacon_nizks: Option<S::PublicKeyNizks> = None;
+
+        if accumulated {
+            if let Some(beacon) = self.beacon(2, &mut replay, &mut replayed, &mut transcript, &peers, &roster, &msgids) {
+                info!("Deriving the beacon's secrets from {} ({} iterations)", beacon.to_hex(), beacon.iterations);
+                let (privkey, pubkey, nizks) = beacon.contribution::<S>(&hash_of_commitments);
+
+                beacon_keys = Some((privkey, pubkey));
+                beacon_nizks = Some(nizks);
+            }
+        }
+
+        if let Some((ref privkey, ref pubkey)) = beacon_keys {
+            let replay_contribution = replay.as_ref().map(|r| r.contains(Section::Contribution(2, players))).unwrap_or(false);
+
+            let (revealed, new_stage2, ihash) = if replay_contribution {
+                replayed += 1;
+
+                let known = if accumulated { None } else { Some(pubkey) };
+
+                replay_stage2::<S>(replay.as_mut().unwrap(), players, known, &pubkey.hash(), &hash_of_commitments, &stage2)
+            } else {
+                self.end_replay(&mut replay, replayed, &peers, &roster, &msgids);
+
+                info!("Transforming stage2 with the beacon's secrets");
+                let revealed = beacon_nizks.take().map(|nizks| (pubkey.clone(), nizks));
+                let (new_stage2, ihash) = if revealed.is_some() {
+                    beacon_turn(Some(&hash_of_commitments), &stage2, privkey, &last_message_hash)
+                } else {
+                    beacon_turn(None, &stage2, privkey, &last_message_hash)
+                };
+
+                info!("Writing the beacon's contribution to transcript");
+                record_stage2::<S>(&mut transcript, players, &revealed, &new_stage2, &ihash);
+
+                (revealed, new_stage2, ihash)
+            };
+
+            last_message_hash = stage2_message_hash::<S>(&revealed, &new_stage2, &ihash);
+
+            stage2 = new_stage2;
+        }
+
+        info!("Initializing stage3 with constraint system and stage2");
+
+        let mut stage3 = S::stage3(&qap, &stage1, &stage2);
+        let turns = replayed_turns(&replay, 3, players);
+
+        for &i in &turns {
+            let reader = replay.as_mut().unwrap();
+
+            if reader.contains(Section::Exclusion(3, i)) {
+                replay_exclusion::<S>(reader, &mut roster, 3, i);
             } else {
-                info!("Writing new stage3 to transcript");
-                encode_into(&new_stage3, &mut transcript, Infinite).unwrap();
-                encode_into(&ihash, &mut transcript, Infinite).unwrap();
+                let (new_stage3, ihash) = replay_stage3::<S>(reader, i, pubkeys[i].as_ref().unwrap(), &stage3);
 
                 last_message_hash = digest256_from_parts!(
                     new_stage3, ihash
                 );
+                self.monitor.accepted(i, 3, &last_message_hash);
 
                 stage3 = new_stage3;
+                msgids[i] += STAGE3_MESSAGES;
             }
+
+            replayed += 1;
+        }
+
+        let waiting: Vec<usize> = (0..players).filter(|&i| roster.is_active(i) && !turns.contains(&i)).collect();
+
+        if !waiting.is_empty() {
+            self.end_replay(&mut replay, replayed, &peers, &roster, &msgids);
         }
 
+        self.lobby.begin_stage(3, waiting, self.turn_limit(3));
+
+        while let Some(turn) = self.next_turn(&peers, &roster) {
+            let i = turn.player();
+            let peerid = &peers[i];
+            let pubkey = pubkeys[i].as_ref().unwrap();
+
+            let result = match turn {
+                Turn::Player(_) => self.stage3_turn::<S>(i, peerid, pubkey, &stage3, &last_message_hash),
+                Turn::Excluded(_) => Err(Misbehavior::Operator)
+            };
+
+            match result {
+                Ok((new_stage3, ihash)) => {
+                    info!("Writing new stage3 to transcript");
+                    transcript.record(Section::Contribution(3, i), |w| {
+                        try!(encode_into(&new_stage3, w, Infinite));
+
+                        encode_into(&ihash, w, Infinite)
+                    }).unwrap();
+                    transcript.checkpoint().unwrap();
+
+                    last_message_hash = digest256_from_parts!(
+                        new_stage3, ihash
+                    );
+                    self.monitor.accepted(i, 3, &last_message_hash);
+
+                    stage3 = new_stage3;
+                },
+                Err(reason) => {
+                    self.exclude(&mut roster, &mut transcript, peerid, Exclusion {
+                        player: i,
+                        stage: 3,
+                        reason: reason
+                    });
+                }
+            }
+
+            self.end_turn(3, peerid);
+        }
+
+        if let Some((ref privkey, ref pubkey)) = beacon_keys {
+            let replay_contribution = replay.as_ref().map(|r| r.contains(Section::Contribution(3, players))).unwrap_or(false);
+
+            let (new_stage3, ihash) = if replay_contribution {
+                replayed += 1;
+
+                replay_stage3::<S>(replay.as_mut().unwrap(), players, pubkey, &stage3)
+            } else {
+                self.end_replay(&mut replay, replayed, &peers, &roster, &msgids);
+
+                info!("Transforming stage3 with the beacon's secrets");
+                let (new_stage3, ihash) = beacon_turn(None, &stage3, privkey, &last_message_hash);
+
+                info!("Writing the beacon's contribution to transcript");
+                transcript.record(Section::Contribution(3, players), |w| {
+                    try!(encode_into(&new_stage3, w, Infinite));
+
+                    encode_into(&ihash, w, Infinite)
+                }).unwrap();
+                transcript.checkpoint().unwrap();
+
+                (new_stage3, ihash)
+            };
+
+            last_message_hash = digest256_from_parts!(
+                new_stage3, ihash
+            );
+
+            stage3 = new_stage3;
+        }
+
+        // Every turn may have been replayed if we were interrupted
+        // right before finishing the transcript.
+        self.end_replay(&mut replay, replayed, &peers, &roster, &msgids);
+
         info!("MPC complete, flushing transcript to disk.");
 
-        transcript.flush().unwrap();
+        transcript.finish().unwrap();
+        self.dump_metrics();
 
         info!("Transcript flushed to disk.");
     }
 
-    fn accept(&self, peerid: [u8; 8], mut stream: TcpStream, remote_msgid: u8) {
+    fn accept(&self, identity: Digest256, mut stream: SecureConnection<Metered<T::Connection>>, remote_msgid: u64) {
         use std::collections::hash_map::Entry::{Occupied, Vacant};
+        use byteorder::{WriteBytesExt, LittleEndian};
 
-        fn send_msgid(stream: &mut TcpStream, msgid: u8) {
-            let _ = stream.write_all(&[msgid]);
+        fn send_msgid<W: Write>(stream: &mut W, msgid: u64) {
+            let _ = stream.write_u64::<LittleEndian>(msgid);
             let _ = stream.flush();
         }
 
-        let mut peers = self.peers.lock().unwrap();
+        let peerid = peerid(&identity);
 
-        match peers.entry(peerid) {
-            Occupied(mut already) => {
-                if already.get().is_none() {
+        // A peerid is only bound to an identity once it's admitted, so
+        // that those who are turned away leave nothing behind.
+        if let Some(known) = self.identities.lock().unwrap().get(&peerid) {
+            if *known != identity {
+                warn!("Rejecting connection with the wrong identity for peerid={}", peerid.to_hex());
+                let _ = stream.shutdown();
+                return;
+            }
+        }
+
+        if let Some(peer) = self.peer(&peerid) {
+            // We're in the middle of talking to them over their old
+            // connection, or have hung up on them.
+            let mut entry = match peer.try_lock() {
+                Ok(entry) => entry,
+                Err(_) => {
                     warn!("Ignoring duplicate connection attempt (peerid={})", peerid.to_hex());
-                } else {
-                    let our_msgid = match already.get() {
-                        &Some((_, our_msgid, _)) => our_msgid,
-                        _ => unreachable!()
-                    };
-                    send_msgid(&mut stream, our_msgid);
-                    already.insert(Some((stream, our_msgid, remote_msgid)));
+                    return;
                 }
-            },
-            Vacant(vacant) => {
+            };
+
+            let our_msgid = match *entry {
+                Some((_, our_msgid, _)) => our_msgid,
+                None => {
+                    warn!("Ignoring duplicate connection attempt (peerid={})", peerid.to_hex());
+                    return;
+                }
+            };
+
+            send_msgid(&mut stream, our_msgid);
+            *entry = Some((stream, our_msgid, remote_msgid));
+            self.metrics.inc(&RECONNECTS, &[("peerid", &peerid.to_hex())], 1.0);
+
+            return;
+        }
+
+        // They're in the map before we've told them their msgid, but
+        // nobody can talk to them until we have.
+        let peer: Peer<T::Connection> = Arc::new(Mutex::new(None));
+        let mut entry = peer.lock().unwrap();
+
+        let our_msgid = {
+            let mut peers = self.peers.lock().unwrap();
+
+            let vacant = match peers.entry(peerid) {
+                Occupied(_) => {
+                    warn!("Ignoring duplicate connection attempt (peerid={})", peerid.to_hex());
+                    return;
+                },
+                Vacant(vacant) => vacant
+            };
+
+            let our_msgid = if let Some(our_msgid) = self.resumed.lock().unwrap().remove(&peerid) {
+                info!("Player reconnected to resumed ceremony (peerid={})", peerid.to_hex());
+                our_msgid
+            } else {
                 match self.notifier.send(peerid) {
                     Ok(_) => {
                         info!("Accepted new connection (peerid={})", peerid.to_hex());
-                        send_msgid(&mut stream, 0);
-                        vacant.insert(Some((stream, 0, remote_msgid)));
+                        self.identities.lock().unwrap().insert(peerid, identity);
+                        0
                     },
                     Err(_) => {
                         warn!("Rejecting connection from peerid={}, no longer accepting new players.", peerid.to_hex());
+                        return;
                     }
                 }
+            };
+
+            vacant.insert(peer.clone());
+
+            our_msgid
+        };
+
+        send_msgid(&mut stream, our_msgid);
+        *entry = Some((stream, our_msgid, remote_msgid));
+    }
+}
+
+/// The players whose turns in `stage` are in the transcript being
+/// resumed, in the order they took them, leaving out the beacon.
+fn replayed_turns(replay: &Option<TranscriptReader<File>>, stage: u8, players: usize) -> Vec<usize>
+{
+    match *replay {
+        Some(ref reader) => reader.order(stage).into_iter().filter(|&i| i < players).collect(),
+        None => vec![]
+    }
+}
+
+fn replay_exclusion<S: Scheme>(reader: &mut TranscriptReader<File>, roster: &mut Roster, stage: u8, player: usize)
+{
+    let exclusion: Exclusion<S> = decode_from(&mut reader.seek(Section::Exclusion(stage, player)).unwrap(), Infinite).unwrap();
+
+    info!("Replaying exclusion of player {} during stage{}: {}", player, stage, exclusion.reason.describe());
+
+    if exclusion.player != player || exclusion.stage != stage {
+        error!("Transcript being resumed contains a misplaced exclusion");
+        panic!("cannot recover.");
+    }
+
+    if let Err(e) = roster.exclude(&exclusion) {
+        error!("Transcript being resumed contains an invalid exclusion: {}", e);
+        panic!("cannot recover.");
+    }
+}
+
+/// Reads back a player's stage1 contribution from the transcript being
+/// resumed, checking it as if it had just been received.
+fn replay_stage1<S: Scheme>(
+    reader: &mut TranscriptReader<File>,
+    player: usize,
+    comm: &Digest256,
+    hash_of_commitments: &Digest512,
+    stage1: &Stage1Contents
+) -> (S::PublicKey, S::PublicKeyNizks, Stage1Contents, Digest256)
+{
+    info!("Replaying stage1 contribution of player {}", player);
+
+    let mut f = reader.seek(Section::Contribution(1, player)).unwrap();
+    let pubkey: S::PublicKey = decode_from(&mut f, Infinite).unwrap();
+    let nizks: S::PublicKeyNizks = decode_from(&mut f, Infinite).unwrap();
+    let new_stage1: Stage1Contents = decode_from(&mut f, Infinite).unwrap();
+    let ihash: Digest256 = decode_from(&mut f, Infinite).unwrap();
+
+    if pubkey.hash() != *comm ||
+       !nizks.is_valid(&pubkey, hash_of_commitments) ||
+       !new_stage1.verify_transform(stage1, &pubkey)
+    {
+        error!("Transcript being resumed contains an invalid stage1 contribution from player {}", player);
+        panic!("cannot recover.");
+    }
+
+    (pubkey, nizks, new_stage1, ihash)
+}
+
+/// Reads back a stage2 contribution like `replay_stage1`, along with the
+/// public key and nizks it starts with if the player had no `pubkey` yet.
+fn replay_stage2<S: Scheme>(
+    reader: &mut TranscriptReader<File>,
+    player: usize,
+    pubkey: Option<&S::PublicKey>,
+    comm: &Digest256,
+    hash_of_commitments: &Digest512,
+    stage2: &S::Stage2
+) -> (Option<(S::PublicKey, S::PublicKeyNizks)>, S::Stage2, Digest256)
+{
+    info!("Replaying stage2 contribution of player {}", player);
+
+    let mut f = reader.seek(Section::Contribution(2, player)).unwrap();
+
+    let revealed: Option<(S::PublicKey, S::PublicKeyNizks)> = match pubkey {
+        Some(_) => None,
+        None => {
+            let pubkey: S::PublicKey = decode_from(&mut f, Infinite).unwrap();
+            let nizks: S::PublicKeyNizks = decode_from(&mut f, Infinite).unwrap();
+
+            if pubkey.hash() != *comm || !nizks.is_valid(&pubkey, hash_of_commitments) {
+                error!("Transcript being resumed contains an invalid public key from player {}", player);
+                panic!("cannot recover.");
             }
+
+            Some((pubkey, nizks))
         }
+    };
+
+    let new_stage2: S::Stage2 = decode_from(&mut f, Infinite).unwrap();
+    let ihash: Digest256 = decode_from(&mut f, Infinite).unwrap();
+
+    if !new_stage2.verify_transform(stage2, pubkey.unwrap_or_else(|| &revealed.as_ref().unwrap().0)) {
+        error!("Transcript being resumed contains an invalid stage2 contribution from player {}", player);
+        panic!("cannot recover.");
     }
+
+    (revealed, new_stage2, ihash)
+}
+
+/// Writes a stage2 contribution to the transcript, starting with the
+/// public key and nizks revealed with it, if any.
+fn record_stage2<S: Scheme>(
+    transcript: &mut TranscriptWriter<File>,
+    player: usize,
+    revealed: &Option<(S::PublicKey, S::PublicKeyNizks)>,
+    stage2: &S::Stage2,
+    ihash: &Digest256
+)
+{
+    transcript.record(Section::Contribution(2, player), |w| {
+        if let Some((ref pubkey, ref nizks)) = *revealed {
+            try!(encode_into(pubkey, w, Infinite));
+            try!(encode_into(nizks, w, Infinite));
+        }
+        try!(encode_into(stage2, w, Infinite));
+
+        encode_into(ihash, w, Infinite)
+    }).unwrap();
+    transcript.checkpoint().unwrap();
+}
+
+/// The hash of a stage2 contribution as `record_stage2` writes it.
+fn stage2_message_hash<S: Scheme>(
+    revealed: &Option<(S::PublicKey, S::PublicKeyNizks)>,
+    stage2: &S::Stage2,
+    ihash: &Digest256
+) -> Digest256
+{
+    match *revealed {
+        Some((ref pubkey, ref nizks)) => digest256_from_parts!(pubkey, nizks, stage2, ihash),
+        None => digest256_from_parts!(stage2, ihash)
+    }
+}
+
+fn replay_stage3<S: Scheme>(
+    reader: &mut TranscriptReader<File>,
+    player: usize,
+    pubkey: &S::PublicKey,
+    stage3: &S::Stage3
+) -> (S::Stage3, Digest256)
+{
+    info!("Replaying stage3 contribution of player {}", player);
+
+    let mut f = reader.seek(Section::Contribution(3, player)).unwrap();
+    let new_stage3: S::Stage3 = decode_from(&mut f, Infinite).unwrap();
+    let ihash: Digest256 = decode_from(&mut f, Infinite).unwrap();
+
+    if !new_stage3.verify_transform(stage3, pubkey) {
+        error!("Transcript being resumed contains an invalid stage3 contribution from player {}", player);
+        panic!("cannot recover.");
+    }
+
+    (new_stage3, ihash)
+}
+
+/// Loads the coordinator's long-term signing key, creating it if this is
+/// the first ceremony it coordinates.
+fn signing_key(path: &str) -> SigningKey {
+    use std::fs::OpenOptions;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::Path;
+
+    let key = if Path::new(path).exists() {
+        match SigningKey::load(&mut File::open(path).unwrap()) {
+            Some(key) => key,
+            None => {
+                error!("{} does not contain a signing key.", path);
+                panic!("cannot recover.");
+            }
+        }
+    } else {
+        info!("Creating signing key in {}", path);
+
+        let key = SigningKey::new(&mut rand::thread_rng());
+        let mut f = OpenOptions::new().write(true).create_new(true).mode(0o600).open(path).unwrap();
+        key.save(&mut f).unwrap();
+
+        key
+    };
+
+    info!("Signing the transcript and channel with coordinator key {}", key.verifying_key().to_hex());
+
+    key
+}
+
+/// The beacon's turn in stage 2 or 3, which the coordinator takes
+/// itself. The `hash_of_commitments` is given for a turn the beacon
+/// reveals its public key in, as it's part of what players are sent then.
+fn beacon_turn<T: Stage>(hash_of_commitments: Option<&Digest512>, stage: &T, privkey: &T::PrivateKey, last_message_hash: &Digest256) -> (T, Digest256)
+{
+    let ihash = match hash_of_commitments {
+        Some(hash_of_commitments) => digest256_from_parts!(hash_of_commitments, stage, last_message_hash),
+        None => digest256_from_parts!(stage, last_message_hash)
+    };
+
+    let mut new_stage = stage.clone();
+    new_stage.transform(privkey);
+
+    (new_stage, ihash)
 }
 
 fn main() {
@@ -386,52 +1765,56 @@ fn main() {
         builder.init().unwrap();
     }
 
-    info!("Opening TCP listener on {}", LISTEN_ADDR);
-    let listener = TcpListener::bind(LISTEN_ADDR).unwrap();
-
-    let handler = ConnectionHandler::new();
-
-    for stream in listener.incoming() {
-        match stream {
-            Ok(mut stream) => {
-                let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
-                let _ = stream.set_write_timeout(Some(Duration::from_secs(5)));
-
-                match stream.peer_addr() {
-                    Ok(addr) => {
-                        let mut magic = [0; 8];
-                        let mut peerid = [0; 8];
-                        let mut msgi_buf: [u8; 1] = [0];
-
-                        match stream.read_exact(&mut magic)
-                                    .and_then(|_| stream.read_exact(&mut peerid))
-                                    .and_then(|_| stream.read_exact(&mut msgi_buf))
-                        {
-                            Err(e) => {
-                                warn!("Remote host {} did not handshake; {}", addr, e);
-                            },
-                            Ok(_) => {
-                                if magic != NETWORK_MAGIC {
-                                    warn!("Remote host {} did not supply correct network magic.", addr);
-                                } else {
-                                    if
-                                        stream.write_all(&COORDINATOR_MAGIC).is_ok() &&
-                                        stream.flush().is_ok() &&
-                                        stream.set_read_timeout(Some(Duration::from_secs(NETWORK_TIMEOUT))).is_ok() &&
-                                        stream.set_write_timeout(Some(Duration::from_secs(NETWORK_TIMEOUT))).is_ok()
-                                    {
-                                        handler.accept(peerid, stream, msgi_buf[0]);
-                                    } else {
-                                        warn!("Failed to set read/write timeouts for remote host {}", addr);
-                                    }
-                                }
-                            }
+    let config = Config::load("coordinator", Config {
+        threads: THREADS,
+        .. Config::default()
+    });
+
+    set_threads(config.threads);
+
+    match config.transport {
+        TransportKind::Tcp => serve(Tcp, config),
+        TransportKind::Unix => serve(Unix, config)
+    }
+}
+
+/// Accepts connections from the network machines, handing them to the
+/// `ConnectionHandler` once they've completed the handshake.
+fn serve<T: Transport>(transport: T, config: Config) {
+    let network_timeout = config.network_timeout;
+
+    info!("Opening {} listener on {}", config.transport.name(), config.listen_addr);
+    let listener = transport.listen(&config.listen_addr).unwrap();
+
+    // The channel's static key is vouched for by the signing key, which
+    // the network machines know.
+    let key = signing_key(&config.signing_key);
+    let identity = Arc::new(CoordinatorIdentity::new(&key));
+
+    let handler = ConnectionHandler::<T>::new(config, key);
+
+    loop {
+        match listener.accept() {
+            Ok(stream) => {
+                let remote = stream.remote();
+
+                let stream = Metered::new(stream, handler.monitor.traffic());
+
+                // Anyone can start a handshake and then stall it, so each
+                // gets a thread of its own rather than holding up the
+                // players trying to reconnect.
+                let handler = handler.clone();
+                let identity = identity.clone();
+                thread::spawn(move || {
+                    match accept_handshake(stream, &identity, network_timeout) {
+                        Ok((stream, identity, msgid)) => {
+                            handler.accept(identity, stream, msgid);
+                        },
+                        Err(e) => {
+                            warn!("Remote host {} {}", remote, e);
                         }
-                    },
-                    Err(e) => {
-                        warn!("Failed connection attempt from unknown host: {}", e);
                     }
-                }
+                });
             },
             Err(e) => {
                 warn!("Failed to establish connection with remote client, {}", e);
@@ -4,6 +4,7 @@ use std::time::Duration;
 use std::fs::{self, File};
 use std::process::Command;
 use protocol::*;
+use config::Config;
 
 const REMOTEPATH_ALPINE_RELEASE: &'static str = ".alpine-release";
 const REMOTEPATH_TEST_BURN: &'static str = "mpc_testburn";
@@ -148,9 +149,9 @@ pub fn eject() {
             .output();
 }
 
-pub fn perform_diagnostics() {
+pub fn perform_diagnostics(config: &Config) {
     loop {
-        if let DvdStatus::File(_) = read_from_dvd(REMOTEPATH_ALPINE_RELEASE, &format!("{}read_from_iso", ::DIRECTORY_PREFIX)) {
+        if let DvdStatus::File(_) = read_from_dvd(REMOTEPATH_ALPINE_RELEASE, &format!("{}read_from_iso", config.directory_prefix)) {
             break;
         } else {
             println!("ERROR! There was a problem reading from the drive or you removed the boot disk from the drive.");
@@ -159,7 +160,7 @@ pub fn perform_diagnostics() {
     }
 
     loop {
-        if let DvdStatus::File(_) = read_from_dvd(REMOTEPATH_ALPINE_RELEASE, &format!("{}read_from_iso", ::DIRECTORY_PREFIX)) {
+        if let DvdStatus::File(_) = read_from_dvd(REMOTEPATH_ALPINE_RELEASE, &format!("{}read_from_iso", config.directory_prefix)) {
             eject();
             prompt("Please remove the disk from the drive and place it somewhere safe.\n\n \
                     Press [ENTER] when ready to continue.");
@@ -172,7 +173,7 @@ pub fn perform_diagnostics() {
     prompt("Please place a blank DVD into the drive. Press [ENTER] when ready to continue.");
 
     loop {
-        if let DvdStatus::Blank = read_from_dvd(REMOTEPATH_ALPINE_RELEASE, &format!("{}read_from_iso", ::DIRECTORY_PREFIX)) {
+        if let DvdStatus::Blank = read_from_dvd(REMOTEPATH_ALPINE_RELEASE, &format!("{}read_from_iso", config.directory_prefix)) {
             break;
         } else {
             eject();
@@ -181,25 +182,25 @@ pub fn perform_diagnostics() {
     }
 
     {
-        let mut f = File::create(&format!("{}mpc_testburn", ::DIRECTORY_PREFIX)).unwrap();
+        let mut f = File::create(&format!("{}mpc_testburn", config.directory_prefix)).unwrap();
         f.write(&[0xff, 0xff, 0xfa, 0x00]).unwrap();
         f.flush().unwrap();
     }
 
     loop {
-        if write_to_dvd(REMOTEPATH_TEST_BURN, &format!("{}mpc_testburn", ::DIRECTORY_PREFIX)) {
+        if write_to_dvd(REMOTEPATH_TEST_BURN, &format!("{}mpc_testburn", config.directory_prefix)) {
             break;
         }
 
         thread::sleep(Duration::from_secs(3));
     }
 
-    if !fs::remove_file(&format!("{}mpc_testburn", ::DIRECTORY_PREFIX)).is_ok() {
+    if !fs::remove_file(&format!("{}mpc_testburn", config.directory_prefix)).is_ok() {
         panic!("could not remove local testburn file");
     }
 
     loop {
-        if let DvdStatus::File(mut f) = read_from_dvd(REMOTEPATH_TEST_BURN, &format!("{}mpc_testburn", ::DIRECTORY_PREFIX)) {
+        if let DvdStatus::File(mut f) = read_from_dvd(REMOTEPATH_TEST_BURN, &format!("{}mpc_testburn", config.directory_prefix)) {
             let mut contents = vec![];
             f.read_to_end(&mut contents).unwrap();
             assert!((&*contents) == &[0xff, 0xff, 0xfa, 0x00]);
@@ -215,7 +216,7 @@ pub fn perform_diagnostics() {
             Press [ENTER] when the drive is clear.");
 
     loop {
-        if let DvdStatus::Error = read_from_dvd(REMOTEPATH_ALPINE_RELEASE, &format!("{}read_from_iso", ::DIRECTORY_PREFIX)) {
+        if let DvdStatus::Error = read_from_dvd(REMOTEPATH_ALPINE_RELEASE, &format!("{}read_from_iso", config.directory_prefix)) {
             break;
         } else {
             eject();
@@ -235,19 +236,20 @@ pub fn exchange_disc<
     F1: Fn(&mut File) -> Result<(), R1>,
     F2: Fn(&mut TemporaryFile, Option<Digest256>) -> Result<T, R2>
 >(
+    config: &Config,
     our_disc: &str,
     their_disc: &str,
     our_cb: F1,
     their_cb: F2
 ) -> T
 {
-    let newdisc_localpath = &format!("{}disc{}", ::DIRECTORY_PREFIX, our_disc);
+    let newdisc_localpath = &format!("{}disc{}", config.directory_prefix, our_disc);
     let newdisc_remotepath = &format!("disc{}", our_disc);
     {
         let mut newdisc = File::create(newdisc_localpath).unwrap();
         our_cb(&mut newdisc).ok().unwrap();
     }
-    if ::ASK_USER_TO_RECORD_HASHES {
+    if config.ask_user_to_record_hashes {
         let mut newdisc = File::open(newdisc_localpath).unwrap();
         let h = hash_of_file(&mut newdisc);
 
@@ -268,10 +270,10 @@ pub fn exchange_disc<
                             our_disc));
         }
 
-        match read_from_dvd(&format!("disc{}", their_disc), &format!("{}disc{}", ::DIRECTORY_PREFIX, their_disc)) {
+        match read_from_dvd(&format!("disc{}", their_disc), &format!("{}disc{}", config.directory_prefix, their_disc)) {
             DvdStatus::File(mut f) => {
                 let h;
-                if ::ASK_USER_TO_RECORD_HASHES {
+                if config.ask_user_to_record_hashes {
                     h = Some(hash_of_file(&mut f));
                     write_down_disc_please(&h.clone().unwrap(), their_disc);
                     f.reset();
@@ -312,17 +314,18 @@ pub fn write_disc<
     R,
     F: Fn(&mut File) -> Result<(), R>
 >(
+    config: &Config,
     our_disc: &str,
     our_cb: F
 )
 {
-    let newdisc_localpath = &format!("{}disc{}", ::DIRECTORY_PREFIX, our_disc);
+    let newdisc_localpath = &format!("{}disc{}", config.directory_prefix, our_disc);
     let newdisc_remotepath = &format!("disc{}", our_disc);
     {
         let mut newdisc = File::create(newdisc_localpath).unwrap();
         our_cb(&mut newdisc).ok().unwrap();
     }
-    if ::ASK_USER_TO_RECORD_HASHES {
+    if config.ask_user_to_record_hashes {
         let mut newdisc = File::open(newdisc_localpath).unwrap();
         let h = hash_of_file(&mut newdisc);
 
@@ -359,14 +362,14 @@ pub fn write_disc<
     }
 }
 
-pub fn read_disc<T, R, F: Fn(&mut TemporaryFile, Option<Digest256>) -> Result<T, R>>(name: &str, message: &str, cb: F) -> T {
+pub fn read_disc<T, R, F: Fn(&mut TemporaryFile, Option<Digest256>) -> Result<T, R>>(config: &Config, name: &str, message: &str, cb: F) -> T {
     prompt(message);
 
     loop {
-        match read_from_dvd(&format!("disc{}", name), &format!("{}disc{}", ::DIRECTORY_PREFIX, name)) {
+        match read_from_dvd(&format!("disc{}", name), &format!("{}disc{}", config.directory_prefix, name)) {
             DvdStatus::File(mut f) => {
                 let h;
-                if ::ASK_USER_TO_RECORD_HASHES {
+                if config.ask_user_to_record_hashes {
                     h = Some(hash_of_file(&mut f));
                     write_down_disc_please(&h.clone().unwrap(), name);
                     f.reset();
@@ -0,0 +1,136 @@
+//! Players who misbehave or vanish during the ceremony are excluded
+//! instead of aborting it. Every exclusion is recorded in the transcript
+//! along with whatever evidence the coordinator had, so that the verifier
+//! can check that the coordinator did not drop honest players.
+//!
+//! Each player's turn in a stage ends in either a contribution or an
+//! exclusion record in the transcript. Once excluded, a player has no
+//! further turns.
+
+use protocol::*;
+
+/// Why a player was excluded.
+#[derive(RustcEncodable, RustcDecodable)]
+pub enum Misbehavior<S: Scheme> {
+    /// The player stopped responding to the coordinator.
+    Unresponsive,
+    /// The revealed `PublicKey` does not match the player's commitment.
+    BadCommitment(S::PublicKey),
+    /// The nizks do not prove knowledge of the secrets of the `PublicKey`.
+    InvalidNizks(S::PublicKey, S::PublicKeyNizks),
+    /// The stage transformation did not verify. The rejected contribution
+    /// is too large for the transcript, so it is kept in quarantine and
+    /// only its hash is recorded here.
+    InvalidTransform(Digest256),
+    /// The player's turn took longer than the coordinator gave them, in
+    /// seconds.
+    OutOfTime(u64),
+    /// A message of kind `.0`, as in its frame, did not decode. It is kept
+    /// in quarantine like a rejected contribution, and `.1` is its hash.
+    Malformed(u8, Digest256),
+    /// A message was `.0` bytes long, more than the limit of `.1` bytes
+    /// for it, so the coordinator turned it away without reading it.
+    Oversized(u64, u64),
+    /// The coordinator's operator excluded the player from the stage.
+    Operator
+}
+
+impl<S: Scheme> Misbehavior<S> {
+    pub fn describe(&self) -> &'static str {
+        match *self {
+            Misbehavior::Unresponsive => "unresponsive",
+            Misbehavior::BadCommitment(_) => "public key does not match commitment",
+            Misbehavior::InvalidNizks(_, _) => "invalid nizks",
+            Misbehavior::InvalidTransform(_) => "invalid transformation",
+            Misbehavior::OutOfTime(_) => "ran out of time",
+            Misbehavior::Malformed(_, _) => "malformed message",
+            Misbehavior::Oversized(_, _) => "message too long",
+            Misbehavior::Operator => "excluded by the operator"
+        }
+    }
+}
+
+#[derive(RustcEncodable, RustcDecodable)]
+pub struct Exclusion<S: Scheme> {
+    /// Index of the player, in commitment order.
+    pub player: usize,
+    pub stage: u8,
+    pub reason: Misbehavior<S>
+}
+
+/// Path of the quarantine file for a rejected contribution or message.
+pub fn quarantine_path(stage: u8, player: usize) -> String {
+    format!("quarantine/stage{}-player{}", stage, player)
+}
+
+/// Tracks which players are still taking part in the ceremony, and
+/// enforces the exclusion policy.
+pub struct Roster {
+    excluded: Vec<bool>,
+    min_players: usize
+}
+
+impl Roster {
+    /// The ceremony is aborted rather than continued with fewer than
+    /// `min_players` remaining players.
+    pub fn new(players: usize, min_players: usize) -> Roster {
+        assert!(min_players >= 1);
+
+        Roster {
+            excluded: vec![false; players],
+            min_players: min_players
+        }
+    }
+
+    pub fn is_active(&self, player: usize) -> bool {
+        !self.excluded[player]
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.excluded.iter().filter(|e| !**e).count()
+    }
+
+    pub fn exclude<S: Scheme>(&mut self, exclusion: &Exclusion<S>) -> Result<(), String> {
+        if exclusion.player >= self.excluded.len() {
+            return Err(format!("player {} does not exist", exclusion.player));
+        }
+
+        if self.excluded[exclusion.player] {
+            return Err(format!("player {} was already excluded", exclusion.player));
+        }
+
+        if self.remaining() <= self.min_players {
+            return Err(format!("excluding player {} would leave fewer than {} players",
+                               exclusion.player, self.min_players));
+        }
+
+        self.excluded[exclusion.player] = true;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn roster_policy() {
+    let mut roster = Roster::new(3, 2);
+
+    let exclusion = |player| Exclusion::<Pghr13> {
+        player: player,
+        stage: 1,
+        reason: Misbehavior::Unresponsive
+    };
+
+    assert_eq!(roster.remaining(), 3);
+    assert!(roster.exclude(&exclusion(1)).is_ok());
+    assert!(!roster.is_active(1));
+    assert!(roster.is_active(0));
+    assert_eq!(roster.remaining(), 2);
+
+    // Can't exclude twice, or beyond the end of the roster.
+    assert!(roster.exclude(&exclusion(1)).is_err());
+    assert!(roster.exclude(&exclusion(3)).is_err());
+
+    // Can't go below the minimum number of players.
+    assert!(roster.exclude(&exclusion(0)).is_err());
+    assert!(roster.is_active(0));
+}
@@ -0,0 +1,555 @@
+//! The lobby keeps track of the players who have registered for the
+//! ceremony, and decides who takes the next turn in each stage.
+//!
+//! Players register by connecting to the coordinator and sending their
+//! commitment, followed by the windows of time they are available in.
+//! Within a stage, players take their turns in queue order, except that
+//! a player who isn't connected, or is outside all of their windows, is
+//! passed over for the next one in line who is. If nobody in line turns
+//! up for long enough, the first of them gets their turn anyway, which
+//! they will likely be excluded from as unresponsive.
+//!
+//! Each turn has a deadline, the stage's turn limit after it starts.
+//! When a player misses it, the coordinator skips them, giving them an
+//! exclusion in the transcript, gives them as long again, or aborts the
+//! ceremony, which can then be resumed from its transcript.
+//!
+//! The coordinator's operator can rearrange the queue of the current
+//! stage while the ceremony runs, and change what happens on a timeout,
+//! by typing commands at the coordinator:
+//!
+//! ```text
+//! queue                       lists the players yet to take their turn
+//! move <peerid> <position>    moves a player to a position in the queue
+//! skip <peerid>               sends a player to the back of the queue
+//! exclude <peerid>            excludes a player waiting in the queue from
+//!                             the stage, and from the rest of the ceremony
+//! on-timeout <skip|wait|abort>
+//!                             changes what happens when a turn takes too long
+//! ```
+//!
+//! An excluded player's turn comes up next, and ends in an exclusion
+//! without them, unless that would leave too few players.
+//!
+//! Players waiting for their turn are told where they are in the queue,
+//! and when their turn is expected, estimated from how long the turns of
+//! the stage have taken so far.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use rustc_serialize::hex::{ToHex, FromHex};
+use config::TimeoutAction;
+
+/// How long a turn is assumed to take, until one has been timed.
+const DEFAULT_TURN: u64 = 30 * 60;
+
+/// The current time, in seconds since the unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// The windows of time a player is available in, as pairs of unix times.
+/// A player without any windows is always available.
+#[derive(Clone, PartialEq, Debug, RustcEncodable, RustcDecodable)]
+pub struct Availability {
+    pub windows: Vec<(u64, u64)>
+}
+
+impl Availability {
+    pub fn always() -> Availability {
+        Availability {
+            windows: vec![]
+        }
+    }
+
+    /// Parses windows given in hours from `now`, like `0-4, 10-14`.
+    /// Windows that end too far in the future to be represented are
+    /// rejected.
+    pub fn parse(s: &str, now: u64) -> Option<Availability> {
+        let mut windows = vec![];
+
+        for window in s.split(',').map(|w| w.trim()).filter(|w| !w.is_empty()) {
+            let bounds: Vec<Option<u64>> = window.splitn(2, '-').map(|b| b.trim().parse().ok()).collect();
+
+            match (bounds.get(0), bounds.get(1)) {
+                (Some(&Some(start)), Some(&Some(end))) if start < end => {
+                    let hours_from_now = |h: u64| h.checked_mul(3600).and_then(|secs| now.checked_add(secs));
+
+                    match (hours_from_now(start), hours_from_now(end)) {
+                        (Some(start), Some(end)) => windows.push((start, end)),
+                        _ => return None
+                    }
+                },
+                _ => return None
+            }
+        }
+
+        Some(Availability {
+            windows: windows
+        })
+    }
+
+    /// Whether every window ends after it starts, which is all the lobby
+    /// needs of the windows a player sends.
+    pub fn is_valid(&self) -> bool {
+        self.windows.iter().all(|&(start, end)| start < end)
+    }
+
+    pub fn is_available(&self, at: u64) -> bool {
+        self.windows.is_empty() || self.windows.iter().any(|&(start, end)| start <= at && at < end)
+    }
+
+    /// The earliest time from `at` on that the player is available, if
+    /// any of their windows are left.
+    pub fn next_available(&self, at: u64) -> Option<u64> {
+        if self.is_available(at) {
+            return Some(at);
+        }
+
+        self.windows.iter().map(|&(start, _)| start).filter(|&start| start > at).min()
+    }
+}
+
+struct Player {
+    peerid: [u8; 8],
+    availability: Availability
+}
+
+/// A turn in a stage, as the lobby hands them out.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Turn {
+    /// The player takes their turn.
+    Player(usize),
+    /// The operator excluded the player, whose turn ends in their
+    /// exclusion without them.
+    Excluded(usize)
+}
+
+impl Turn {
+    pub fn player(&self) -> usize {
+        match *self {
+            Turn::Player(i) | Turn::Excluded(i) => i
+        }
+    }
+}
+
+struct Queue {
+    /// Everyone who registered, in commitment order.
+    players: Vec<Player>,
+    stage: u8,
+    /// The players yet to take their turn in the current stage, in order.
+    waiting: Vec<usize>,
+    /// The players the operator excluded from the current stage, whose
+    /// exclusions are yet to be recorded.
+    excluded: Vec<usize>,
+    /// The player taking their turn, and when they started.
+    current: Option<(usize, Instant)>,
+    /// How long each finished turn of the current stage took.
+    turns: Vec<Duration>,
+    /// How long a turn of the current stage may take.
+    limit: Duration,
+    /// When the current turn has to be over by.
+    deadline: Option<Instant>,
+    on_timeout: TimeoutAction
+}
+
+impl Queue {
+    /// How long the next turn is expected to take, in seconds.
+    fn turn_estimate(&self) -> u64 {
+        if self.turns.is_empty() {
+            return DEFAULT_TURN;
+        }
+
+        self.turns.iter().map(|d| d.as_secs()).sum::<u64>() / (self.turns.len() as u64)
+    }
+
+    /// Position in the queue of the player with the given peerid.
+    fn position(&self, peerid: Option<&str>) -> Result<usize, String> {
+        let peerid = try!(peerid.ok_or("no peerid given".to_string()));
+        let bytes = try!(peerid.from_hex().map_err(|_| format!("{} is not a peerid", peerid)));
+
+        self.waiting.iter()
+                    .position(|&i| &self.players[i].peerid[..] == &bytes[..])
+                    .ok_or(format!("peerid={} is not waiting for a turn in stage{}", peerid, self.stage))
+    }
+
+    fn describe(&self) -> String {
+        let mut s = match (self.current, self.deadline) {
+            (Some((i, started)), Some(deadline)) => {
+                let now = Instant::now();
+                let left = if deadline > now { deadline - now } else { Duration::from_secs(0) };
+
+                format!("Stage{}: player {} (peerid={}) has been taking their turn for {}, with {} left",
+                        self.stage, i + 1, self.players[i].peerid.to_hex(),
+                        describe_duration(started.elapsed().as_secs()), describe_duration(left.as_secs()))
+            },
+            _ => format!("Stage{}: nobody is taking their turn", self.stage)
+        };
+
+        s.push_str(&format!("\nOn timeout: {}", self.on_timeout.name()));
+
+        for (position, &i) in self.waiting.iter().enumerate() {
+            s.push_str(&format!("\n{}. player {} (peerid={})", position + 1, i + 1, self.players[i].peerid.to_hex()));
+        }
+
+        s
+    }
+}
+
+fn describe_duration(secs: u64) -> String {
+    match (secs / 3600, (secs / 60) % 60) {
+        (0, 0) => "less than a minute".to_string(),
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h{}m", h, m)
+    }
+}
+
+#[derive(Clone)]
+pub struct Lobby {
+    queue: Arc<Mutex<Queue>>
+}
+
+impl Lobby {
+    pub fn new(on_timeout: TimeoutAction) -> Lobby {
+        Lobby {
+            queue: Arc::new(Mutex::new(Queue {
+                players: vec![],
+                stage: 0,
+                waiting: vec![],
+                excluded: vec![],
+                current: None,
+                turns: vec![],
+                limit: Duration::from_secs(DEFAULT_TURN),
+                deadline: None,
+                on_timeout: on_timeout
+            }))
+        }
+    }
+
+    /// Registers a player, returning their index.
+    pub fn register(&self, peerid: [u8; 8], availability: Availability) -> usize {
+        let mut queue = self.queue.lock().unwrap();
+
+        queue.players.push(Player {
+            peerid: peerid,
+            availability: availability
+        });
+
+        queue.players.len() - 1
+    }
+
+    pub fn registered(&self) -> usize {
+        self.queue.lock().unwrap().players.len()
+    }
+
+    /// Starts a stage, in which `players` take their turns in that order,
+    /// unless the operator rearranges them, and each turn may take up to
+    /// `limit`.
+    pub fn begin_stage(&self, stage: u8, players: Vec<usize>, limit: Duration) {
+        let mut queue = self.queue.lock().unwrap();
+
+        queue.stage = stage;
+        queue.waiting = players;
+        queue.excluded = vec![];
+        queue.current = None;
+        queue.turns = vec![];
+        queue.limit = limit;
+        queue.deadline = None;
+    }
+
+    /// Waits for the first player in line who is connected and available,
+    /// and starts their turn. If there is none for `patience`, the first
+    /// one in line has their turn anyway. Players the operator excluded
+    /// come first, without waiting for them. Returns `None` once everyone
+    /// has had their turn in the stage.
+    pub fn next<F: Fn(&[u8; 8]) -> bool>(&self, is_connected: F, patience: Duration) -> Option<Turn> {
+        let deadline = Instant::now() + patience;
+
+        loop {
+            {
+                let mut queue = self.queue.lock().unwrap();
+
+                if !queue.excluded.is_empty() {
+                    return Some(Turn::Excluded(queue.excluded.remove(0)));
+                }
+
+                if queue.waiting.is_empty() {
+                    return None;
+                }
+
+                let at = now();
+                let ready = queue.waiting.iter().position(|&i| {
+                    is_connected(&queue.players[i].peerid) && queue.players[i].availability.is_available(at)
+                });
+
+                let ready = match ready {
+                    Some(position) => Some(position),
+                    None if Instant::now() >= deadline => Some(0),
+                    None => None
+                };
+
+                if let Some(position) = ready {
+                    let player = queue.waiting.remove(position);
+                    let started = Instant::now();
+                    queue.current = Some((player, started));
+                    queue.deadline = Some(started + queue.limit);
+
+                    return Some(Turn::Player(player));
+                }
+            }
+
+            // The operator may rearrange the queue in the meantime.
+            thread::sleep(Duration::from_secs(5));
+        }
+    }
+
+    /// Puts a player the operator excluded back at the front of the queue,
+    /// when excluding them isn't allowed after all.
+    pub fn readmit(&self, player: usize) {
+        self.queue.lock().unwrap().waiting.insert(0, player);
+    }
+
+    /// Ends the current turn, which is timed for the estimates.
+    pub fn end_turn(&self) {
+        let mut queue = self.queue.lock().unwrap();
+
+        if let Some((_, started)) = queue.current.take() {
+            queue.turns.push(started.elapsed());
+        }
+
+        queue.deadline = None;
+    }
+
+    /// When the current turn has to be over by, if there is one.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.queue.lock().unwrap().deadline
+    }
+
+    pub fn on_timeout(&self) -> TimeoutAction {
+        self.queue.lock().unwrap().on_timeout
+    }
+
+    /// Gives the current turn as long again, returning how long that is.
+    pub fn extend(&self) -> Duration {
+        let mut queue = self.queue.lock().unwrap();
+        let limit = queue.limit;
+
+        queue.deadline = queue.deadline.map(|d| d + limit);
+
+        limit
+    }
+
+    /// How long the current turn has taken so far.
+    pub fn elapsed(&self) -> Duration {
+        match self.queue.lock().unwrap().current {
+            Some((_, started)) => started.elapsed(),
+            None => Duration::from_secs(0)
+        }
+    }
+
+    /// The current stage, and the peerid of whoever is taking their
+    /// turn in it.
+    pub fn turn(&self) -> (u8, Option<[u8; 8]>) {
+        let queue = self.queue.lock().unwrap();
+
+        (queue.stage, queue.current.map(|(i, _)| queue.players[i].peerid))
+    }
+
+    /// The peerids of the players waiting for their turn in the current
+    /// stage, in order.
+    pub fn waiting(&self) -> Vec<[u8; 8]> {
+        let queue = self.queue.lock().unwrap();
+
+        queue.waiting.iter().map(|&i| queue.players[i].peerid).collect()
+    }
+
+    /// What to tell each player waiting for their turn in the current
+    /// stage about where they are in the queue.
+    pub fn statuses(&self) -> Vec<([u8; 8], String)> {
+        let queue = self.queue.lock().unwrap();
+        let turn = queue.turn_estimate();
+        let at = now();
+
+        let mut start = match queue.current {
+            Some((_, started)) => at.saturating_add(turn.saturating_sub(started.elapsed().as_secs())),
+            None => at
+        };
+
+        let mut statuses = vec![];
+
+        for (position, &i) in queue.waiting.iter().enumerate() {
+            let player = &queue.players[i];
+
+            let text = match player.availability.next_available(start) {
+                Some(begins) => {
+                    start = begins.saturating_add(turn);
+
+                    format!("Stage{}: you are number {} in line, and your turn is expected in about {}.",
+                            queue.stage, position + 1, describe_duration(begins - at))
+                },
+                None => {
+                    format!("Stage{}: you are number {} in line, but none of your windows of availability are left.",
+                            queue.stage, position + 1)
+                }
+            };
+
+            statuses.push((player.peerid, text));
+        }
+
+        statuses
+    }
+
+    /// Carries out a command of the operator, returning what to tell
+    /// them.
+    pub fn command(&self, line: &str) -> Result<String, String> {
+        let mut queue = self.queue.lock().unwrap();
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("queue") => {},
+            Some("move") => {
+                let from = try!(queue.position(words.next()));
+                let to = match words.next().and_then(|p| p.parse::<usize>().ok()) {
+                    Some(to) if to >= 1 => ::std::cmp::min(to - 1, queue.waiting.len() - 1),
+                    _ => return Err("expected a position, counting from 1".to_string())
+                };
+
+                let player = queue.waiting.remove(from);
+                queue.waiting.insert(to, player);
+            },
+            Some("skip") => {
+                let from = try!(queue.position(words.next()));
+
+                let player = queue.waiting.remove(from);
+                queue.waiting.push(player);
+            },
+            Some("exclude") => {
+                let from = try!(queue.position(words.next()));
+
+                let player = queue.waiting.remove(from);
+                queue.excluded.push(player);
+            },
+            Some("on-timeout") => {
+                queue.on_timeout = match words.next().and_then(TimeoutAction::from_name) {
+                    Some(action) => action,
+                    None => return Err("expected skip, wait or abort".to_string())
+                };
+            },
+            Some(other) => return Err(format!("unknown command {}, expected queue, move, skip, exclude or on-timeout", other)),
+            None => return Err("expected queue, move, skip, exclude or on-timeout".to_string())
+        }
+
+        Ok(queue.describe())
+    }
+}
+
+#[test]
+fn lobby_queue() {
+    let lobby = Lobby::new(TimeoutAction::Skip);
+
+    for i in 0..4 {
+        assert_eq!(lobby.register([i as u8; 8], Availability::always()), i);
+    }
+
+    assert_eq!(lobby.registered(), 4);
+
+    let limit = Duration::from_secs(3600);
+    lobby.begin_stage(2, vec![0, 1, 2, 3], limit);
+    assert!(lobby.deadline().is_none());
+
+    // Player 1 isn't connected, so player 2 goes ahead of them.
+    let connected = |peerid: &[u8; 8]| peerid[0] != 1;
+    let patience = Duration::from_secs(60);
+
+    assert_eq!(lobby.next(&connected, patience), Some(Turn::Player(0)));
+    lobby.end_turn();
+    assert_eq!(lobby.next(&connected, patience), Some(Turn::Player(2)));
+    assert_eq!(lobby.turn(), (2, Some([2; 8])));
+    assert_eq!(lobby.waiting(), vec![[1; 8], [3; 8]]);
+
+    // The operator sees players numbered from 1, as they are everywhere
+    // else.
+    let queue = lobby.command("queue").unwrap();
+    assert!(queue.starts_with("Stage2: player 3 (peerid=0202020202020202) has been taking their turn"));
+    assert!(queue.ends_with("1. player 2 (peerid=0101010101010101)\n2. player 4 (peerid=0303030303030303)"));
+
+    // The turn has a deadline, which can be pushed back.
+    let deadline = lobby.deadline().unwrap();
+    assert!(deadline > Instant::now() + limit - Duration::from_secs(60));
+    assert_eq!(lobby.extend(), limit);
+    assert_eq!(lobby.deadline(), Some(deadline + limit));
+
+    let statuses = lobby.statuses();
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[0].0, [1; 8]);
+    assert!(statuses[0].1.starts_with("Stage2: you are number 1 in line"));
+    lobby.end_turn();
+    assert!(lobby.deadline().is_none());
+
+    // The operator sends player 1 to the back of the queue, and then
+    // player 3 to the back too.
+    assert!(lobby.command("skip 0101010101010101").is_ok());
+    assert!(lobby.command("move 0303030303030303 5").is_ok());
+    assert!(lobby.command("skip 0202020202020202").is_err());
+    assert!(lobby.command("move 0101010101010101").is_err());
+    assert!(lobby.command("shuffle").is_err());
+    assert!(lobby.command("on-timeout wait").is_ok());
+    assert!(lobby.on_timeout() == TimeoutAction::Wait);
+    assert!(lobby.command("on-timeout never").is_err());
+    assert!(lobby.command("queue").unwrap().ends_with("1. player 2 (peerid=0101010101010101)\n2. player 4 (peerid=0303030303030303)"));
+
+    // Nobody connected turns up, so the first in line gets their turn.
+    assert_eq!(lobby.next(|_| false, Duration::from_secs(0)), Some(Turn::Player(1)));
+    assert_eq!(lobby.next(&connected, patience), Some(Turn::Player(3)));
+    assert_eq!(lobby.next(&connected, patience), None);
+
+    // A player the operator excludes has their turn first, without
+    // waiting for them, and is no longer in the queue.
+    lobby.begin_stage(3, vec![0, 1, 2], limit);
+    assert!(lobby.command("exclude 0101010101010101").is_ok());
+    assert!(lobby.command("exclude 0101010101010101").is_err());
+    assert_eq!(lobby.waiting(), vec![[0; 8], [2; 8]]);
+    assert_eq!(lobby.next(&connected, Duration::from_secs(0)), Some(Turn::Excluded(1)));
+    assert!(lobby.deadline().is_none());
+
+    // Unless that isn't allowed, when they get back in line.
+    assert!(lobby.command("exclude 0202020202020202").is_ok());
+    assert_eq!(lobby.next(&connected, patience), Some(Turn::Excluded(2)));
+    lobby.readmit(2);
+    assert_eq!(lobby.next(&connected, patience), Some(Turn::Player(2)));
+    lobby.end_turn();
+    assert_eq!(lobby.next(&connected, patience), Some(Turn::Player(0)));
+    lobby.end_turn();
+    assert_eq!(lobby.next(&connected, patience), None);
+}
+
+#[test]
+fn availability_windows() {
+    let availability = Availability::parse("0-4, 10-14", 1000).unwrap();
+
+    assert_eq!(availability.windows, vec![(1000, 1000 + 4 * 3600), (1000 + 10 * 3600, 1000 + 14 * 3600)]);
+    assert!(availability.is_available(1000));
+    assert!(!availability.is_available(1000 + 5 * 3600));
+    assert_eq!(availability.next_available(1000 + 5 * 3600), Some(1000 + 10 * 3600));
+    assert_eq!(availability.next_available(1000 + 14 * 3600), None);
+
+    assert_eq!(Availability::parse("", 1000), Some(Availability::always()));
+    assert!(Availability::always().is_available(0));
+    assert!(Availability::parse("4-2", 1000).is_none());
+    assert!(Availability::parse("soon", 1000).is_none());
+
+    // Windows that don't fit are rejected rather than wrapping around.
+    assert!(Availability::parse(&format!("0-{}", u64::max_value() / 3600 + 1), 1000).is_none());
+    assert!(Availability::parse(&format!("0-{}", u64::max_value() / 3600), 3600).is_none());
+
+    assert!(availability.is_valid());
+    assert!(!Availability { windows: vec![(10, 10)] }.is_valid());
+
+    // Nor do windows far in the future overflow the estimates.
+    let lobby = Lobby::new(TimeoutAction::Skip);
+    lobby.register([0; 8], Availability { windows: vec![(u64::max_value() - 1, u64::max_value())] });
+    lobby.register([1; 8], Availability::always());
+    lobby.begin_stage(1, vec![0, 1], Duration::from_secs(3600));
+    assert_eq!(lobby.statuses().len(), 2);
+}
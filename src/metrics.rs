@@ -0,0 +1,255 @@
+//! Counters, gauges and histograms for rehearsal ceremonies, in the
+//! Prometheus text format. The coordinator serves them at `/metrics` on
+//! its status endpoint (see `status.rs`), and the coordinator and the
+//! verifier write them to the file given by `--metrics <path>` when
+//! they finish.
+//!
+//! ```text
+//! mpc_turn_seconds{stage,peerid}           gauge: how long each turn took
+//! mpc_message_bytes{direction,type}        histogram: encoded size of each message
+//! mpc_stream_retries_total{peerid}         counter: failed reads and writes retried
+//! mpc_reconnects_total{peerid}             counter: connections replacing a previous one
+//! mpc_verify_transform_seconds{stage}      histogram: verifying one transformation
+//! mpc_pairings_total{check}                counter: pairings computed by same_power,
+//!                                          checkvec and batched checks
+//! ```
+//!
+//! The verifier only reports the last two, since it doesn't talk to
+//! anyone.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use protocol::pairing_counts;
+
+/// Buckets of the histograms of durations, in seconds.
+const SECONDS: &'static [f64] = &[0.1, 1.0, 10.0, 60.0, 300.0, 900.0, 3600.0, 14400.0];
+
+/// Buckets of the histograms of sizes, in bytes.
+const BYTES: &'static [f64] = &[64.0, 1024.0, 65536.0, 1048576.0, 16777216.0, 268435456.0, 4294967296.0];
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Counter,
+    Gauge,
+    /// The upper bounds of the buckets, in increasing order.
+    Histogram(&'static [f64])
+}
+
+impl Kind {
+    fn name(&self) -> &'static str {
+        match *self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+            Kind::Histogram(_) => "histogram"
+        }
+    }
+}
+
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: Kind
+}
+
+pub static TURN_SECONDS: Metric = Metric {
+    name: "mpc_turn_seconds",
+    help: "How long a player's turn took, from the coordinator's point of view.",
+    kind: Kind::Gauge
+};
+
+pub static MESSAGE_BYTES: Metric = Metric {
+    name: "mpc_message_bytes",
+    help: "Encoded size of the messages exchanged with the network machines.",
+    kind: Kind::Histogram(BYTES)
+};
+
+pub static STREAM_RETRIES: Metric = Metric {
+    name: "mpc_stream_retries_total",
+    help: "Reads and writes which failed and were tried again.",
+    kind: Kind::Counter
+};
+
+pub static RECONNECTS: Metric = Metric {
+    name: "mpc_reconnects_total",
+    help: "Connections from a player which replaced their previous one.",
+    kind: Kind::Counter
+};
+
+pub static VERIFY_TRANSFORM_SECONDS: Metric = Metric {
+    name: "mpc_verify_transform_seconds",
+    help: "How long verifying one transformation of a stage took.",
+    kind: Kind::Histogram(SECONDS)
+};
+
+pub static PAIRINGS: Metric = Metric {
+    name: "mpc_pairings_total",
+    help: "Pairings computed, by the check that computed them.",
+    kind: Kind::Counter
+};
+
+/// A duration in (fractional) seconds, as the metrics measure them.
+pub fn seconds(d: Duration) -> f64 {
+    d.as_secs() as f64 + (d.subsec_nanos() as f64) / 1e9
+}
+
+/// The value of a metric with one set of labels. Counters and gauges
+/// only use `sum`.
+struct Series {
+    sum: f64,
+    count: u64,
+    /// How many observations fell at or below each bucket's bound.
+    buckets: Vec<u64>
+}
+
+struct Family {
+    metric: &'static Metric,
+    /// Keyed by the rendered labels, so that they come out in order.
+    series: BTreeMap<String, Series>
+}
+
+#[derive(Clone)]
+pub struct Metrics {
+    families: Arc<Mutex<BTreeMap<&'static str, Family>>>
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics {
+            families: Arc::new(Mutex::new(BTreeMap::new()))
+        }
+    }
+
+    fn record<F: FnOnce(&mut Series)>(&self, metric: &'static Metric, labels: &[(&str, &str)], f: F) {
+        let mut families = self.families.lock().unwrap();
+
+        let family = families.entry(metric.name).or_insert_with(|| Family {
+            metric: metric,
+            series: BTreeMap::new()
+        });
+
+        let labels = labels.iter()
+                           .map(|&(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+                           .collect::<Vec<_>>()
+                           .join(",");
+
+        let series = family.series.entry(labels).or_insert_with(|| Series {
+            sum: 0.0,
+            count: 0,
+            buckets: match metric.kind {
+                Kind::Histogram(bounds) => vec![0; bounds.len()],
+                _ => vec![]
+            }
+        });
+
+        f(series);
+    }
+
+    /// Adds to a counter.
+    pub fn inc(&self, metric: &'static Metric, labels: &[(&str, &str)], by: f64) {
+        assert!(match metric.kind { Kind::Counter => true, _ => false });
+
+        self.record(metric, labels, |s| s.sum += by);
+    }
+
+    /// Sets a gauge.
+    pub fn set(&self, metric: &'static Metric, labels: &[(&str, &str)], value: f64) {
+        assert!(match metric.kind { Kind::Gauge => true, _ => false });
+
+        self.record(metric, labels, |s| s.sum = value);
+    }
+
+    /// Adds an observation to a histogram.
+    pub fn observe(&self, metric: &'static Metric, labels: &[(&str, &str)], value: f64) {
+        let bounds = match metric.kind {
+            Kind::Histogram(bounds) => bounds,
+            _ => panic!("{} is not a histogram", metric.name)
+        };
+
+        self.record(metric, labels, |s| {
+            s.sum += value;
+            s.count += 1;
+
+            for (bucket, &bound) in s.buckets.iter_mut().zip(bounds) {
+                if value <= bound {
+                    *bucket += 1;
+                }
+            }
+        });
+    }
+
+    /// Everything recorded so far, in the Prometheus text format.
+    pub fn render(&self) -> String {
+        // The pairings are counted by the protocol itself.
+        let pairings = pairing_counts();
+        for &(check, count) in &[("same_power", pairings.same_power),
+                                 ("checkvec", pairings.checkvec),
+                                 ("batch", pairings.batch)] {
+            self.record(&PAIRINGS, &[("check", check)], |s| s.sum = count as f64);
+        }
+
+        let families = self.families.lock().unwrap();
+        let mut out = String::new();
+
+        for family in families.values() {
+            let metric = family.metric;
+
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", metric.name, metric.help, metric.name, metric.kind.name()));
+
+            for (labels, series) in &family.series {
+                let bounds = match metric.kind {
+                    Kind::Histogram(bounds) => bounds,
+                    _ => {
+                        out.push_str(&format!("{}{{{}}} {}\n", metric.name, labels, series.sum));
+                        continue;
+                    }
+                };
+
+                // Histogram buckets take the series' labels, and their own.
+                let sep = if labels.is_empty() { "" } else { "," };
+
+                for (&bound, bucket) in bounds.iter().zip(&series.buckets) {
+                    out.push_str(&format!("{}_bucket{{{}{}le=\"{}\"}} {}\n", metric.name, labels, sep, bound, bucket));
+                }
+
+                out.push_str(&format!("{}_bucket{{{}{}le=\"+Inf\"}} {}\n", metric.name, labels, sep, series.count));
+                out.push_str(&format!("{}_sum{{{}}} {}\n", metric.name, labels, series.sum));
+                out.push_str(&format!("{}_count{{{}}} {}\n", metric.name, labels, series.count));
+            }
+        }
+
+        out
+    }
+
+    pub fn dump(&self, path: &str) -> io::Result<()> {
+        let mut f = try!(File::create(path));
+
+        f.write_all(self.render().as_bytes())
+    }
+}
+
+#[test]
+fn metrics_text_format() {
+    let metrics = Metrics::new();
+
+    metrics.inc(&RECONNECTS, &[("peerid", "0101010101010101")], 1.0);
+    metrics.inc(&RECONNECTS, &[("peerid", "0101010101010101")], 1.0);
+    metrics.set(&TURN_SECONDS, &[("stage", "1"), ("peerid", "0101010101010101")], 42.0);
+    metrics.observe(&VERIFY_TRANSFORM_SECONDS, &[("stage", "2")], 5.0);
+    metrics.observe(&VERIFY_TRANSFORM_SECONDS, &[("stage", "2")], 7200.0);
+
+    let text = metrics.render();
+
+    assert!(text.contains("# TYPE mpc_reconnects_total counter\nmpc_reconnects_total{peerid=\"0101010101010101\"} 2\n"));
+    assert!(text.contains("mpc_turn_seconds{stage=\"1\",peerid=\"0101010101010101\"} 42\n"));
+    assert!(text.contains("# TYPE mpc_verify_transform_seconds histogram\n"));
+    assert!(text.contains("mpc_verify_transform_seconds_bucket{stage=\"2\",le=\"1\"} 0\n"));
+    assert!(text.contains("mpc_verify_transform_seconds_bucket{stage=\"2\",le=\"10\"} 1\n"));
+    assert!(text.contains("mpc_verify_transform_seconds_bucket{stage=\"2\",le=\"14400\"} 2\n"));
+    assert!(text.contains("mpc_verify_transform_seconds_bucket{stage=\"2\",le=\"+Inf\"} 2\n"));
+    assert!(text.contains("mpc_verify_transform_seconds_sum{stage=\"2\"} 7205\n"));
+    assert!(text.contains("mpc_verify_transform_seconds_count{stage=\"2\"} 2\n"));
+    assert!(text.contains("mpc_pairings_total{check=\"same_power\"} "));
+}
@@ -7,6 +7,7 @@ extern crate rustc_serialize;
 extern crate blake2_rfc;
 extern crate bincode;
 extern crate byteorder;
+extern crate snow;
 
 mod protocol;
 use self::protocol::*;
@@ -14,137 +15,156 @@ mod consts;
 use self::consts::*;
 mod dvd;
 use self::dvd::*;
+mod config;
+use self::config::*;
+mod transport;
+use self::transport::*;
+mod channel;
+use self::channel::*;
+mod wire;
+use self::wire::*;
+mod lobby;
+use self::lobby::*;
 
-use rand::Rng;
-use std::io::{Read,Write};
-use std::net::{TcpStream};
 use std::thread;
 use std::time::Duration;
+use std::sync::mpsc::{channel, RecvTimeoutError};
 use bincode::SizeLimit::Infinite;
 use bincode::rustc_serialize::{encode_into, decode_from};
 use rustc_serialize::{Decodable, Encodable};
 
-pub const THREADS: usize = 8;
-pub const DIRECTORY_PREFIX: &'static str = "/";
-pub const ASK_USER_TO_RECORD_HASHES: bool = false;
-const COORDINATOR_ADDR: &'static str = "0.0.0.0:65530";
-
-struct ConnectionHandler {
-    peerid: [u8; 8],
-    s: TcpStream,
-    msgid: u8
+struct ConnectionHandler<T: Transport> {
+    transport: T,
+    identity: Identity,
+    /// The coordinator's published key, which it has to prove it holds
+    /// every time we connect.
+    coordinator_key: CoordinatorKey,
+    s: SecureConnection<T::Connection>,
+    msgid: u64,
+    /// How many messages the coordinator had received from us when we
+    /// last reconnected, so that we don't send it one it already has,
+    /// even when the reconnect happened during `busy`.
+    their_msgid: u64,
+    coordinator_addr: String,
+    timeout: u64
 }
 
-impl ConnectionHandler {
-    fn new() -> ConnectionHandler {
-        let peerid = rand::thread_rng().gen();
-
-        let mut tmp = ConnectionHandler {
-            peerid: peerid,
-            s: TcpStream::connect(COORDINATOR_ADDR).unwrap(),
-            msgid: 0
-        };
-
-        tmp.handshake().expect("could not handshake with coordinator");
-
-        tmp
-    }
-
-    fn handshake(&mut self) -> Option<u8> {
-        if self.s.set_read_timeout(Some(Duration::from_secs(5))).is_err() {
-            return None;
-        }
-        if self.s.set_write_timeout(Some(Duration::from_secs(5))).is_err() {
-            return None;
-        }
-        if self.s.write_all(&NETWORK_MAGIC).is_err() {
-            return None;
-        }
-        if self.s.write_all(&self.peerid).is_err() {
-            return None;
-        }
-        if self.s.write_all(&[self.msgid]).is_err() {
-            return None;
-        }
-        if self.s.flush().is_err() {
-            return None;
-        }
-        let _ = self.s.set_read_timeout(Some(Duration::from_secs(NETWORK_TIMEOUT)));
-        let _ = self.s.set_write_timeout(Some(Duration::from_secs(NETWORK_TIMEOUT)));
-
-        let mut buf: [u8; 8] = [0; 8];
-        if self.s.read_exact(&mut buf).is_err() {
-            return None;
-        }
-
-        if buf != COORDINATOR_MAGIC {
-            return None;
-        }
-
-        let mut buf: [u8; 1] = [0];
-        match self.s.read_exact(&mut buf) {
-            Ok(_) => Some(buf[0]),
-            Err(_) => None
+impl<T: Transport> ConnectionHandler<T> {
+    fn new(transport: T, config: &Config, coordinator_key: CoordinatorKey) -> ConnectionHandler<T> {
+        let identity = Identity::new();
+
+        let (s, _) = handshake(
+            transport.connect(&config.coordinator_addr).unwrap(),
+            &identity,
+            &coordinator_key,
+            0,
+            config.network_timeout
+        ).expect("could not handshake with coordinator");
+
+        ConnectionHandler {
+            transport: transport,
+            identity: identity,
+            coordinator_key: coordinator_key,
+            s: s,
+            msgid: 0,
+            their_msgid: 0,
+            coordinator_addr: config.coordinator_addr.clone(),
+            timeout: config.network_timeout
         }
     }
 
-    fn do_with_stream<T, E, F: Fn(&mut TcpStream, u8) -> Result<T, E>>(&mut self, cb: F) -> T
+    fn do_with_stream<R, F: Fn(&mut SecureConnection<T::Connection>, u64) -> Result<R, WireError>>(&mut self, cb: F) -> R
     {
-        let mut their_msgid = 0;
-
         loop {
-            let val = cb(&mut self.s, their_msgid);
+            let val = cb(&mut self.s, self.their_msgid);
 
             match val {
                 Ok(s) => {
                     return s;
                 },
+                Err(ref e) if e.is_fatal() => {
+                    // Reconnecting won't help, the coordinator and
+                    // this machine disagree about the ceremony.
+                    println!("The coordinator and this machine can't continue: {}", e);
+                    std::process::exit(1);
+                },
                 Err(_) => {
-                    let mut failed = false;
-
-                    loop {
-                        match TcpStream::connect(COORDINATOR_ADDR) {
-                            Ok(s) => {
-                                self.s = s;
-                                match self.handshake() {
-                                    Some(id) => {
-                                        their_msgid = id;
-                                        if failed {
-                                            println!("Reconnected to coordinator.");
-                                        }
-                                        break;
-                                    },
-                                    None => {
-                                        thread::sleep(Duration::from_secs(2));
-                                    }
-                                }
-                            },
-                            Err(_) => {
-                                failed = true;
-                                println!("Failed to connect to coordinator, trying again...");
-                                thread::sleep(Duration::from_secs(2));
+                    self.their_msgid = self.reconnect();
+                }
+            }
+        }
+    }
+
+    /// Connects to the coordinator again, returning how many messages
+    /// it has received from us.
+    fn reconnect(&mut self) -> u64 {
+        let mut failed = false;
+
+        loop {
+            match self.transport.connect(&self.coordinator_addr) {
+                Ok(s) => {
+                    match handshake(s, &self.identity, &self.coordinator_key, self.msgid, self.timeout) {
+                        Some((s, id)) => {
+                            self.s = s;
+                            if failed {
+                                println!("Reconnected to coordinator.");
                             }
+                            return id;
+                        },
+                        None => {
+                            thread::sleep(Duration::from_secs(2));
                         }
                     }
+                },
+                Err(_) => {
+                    failed = true;
+                    println!("Failed to connect to coordinator, trying again...");
+                    thread::sleep(Duration::from_secs(2));
                 }
             }
         }
     }
 
-    fn read<T: Decodable>(&mut self) -> T {
+    /// Runs `f`, which may take a long time without talking to the
+    /// coordinator, sending heartbeats in the meantime so the coordinator
+    /// knows we're still there.
+    fn busy<R, F: FnOnce() -> R>(&mut self, f: F) -> R {
+        let (done, waiting) = channel::<()>();
+        let interval = Duration::from_secs(HEARTBEAT_INTERVAL);
+
+        crossbeam::scope(|scope| {
+            scope.spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = waiting.recv_timeout(interval) {
+                    if heartbeat(&mut self.s).is_err() {
+                        self.their_msgid = self.reconnect();
+                    }
+                }
+            });
+
+            let result = f();
+            let _ = done.send(());
+
+            result
+        })
+    }
+
+    fn read<D: Decodable>(&mut self, ty: MessageType) -> D {
+        let msgid = self.msgid + 1;
+
+        // While waiting for our turn, the coordinator tells us where
+        // we are in the queue, and sends heartbeats often enough that
+        // our reads don't time out. Its key is pinned, so whatever it
+        // sends is as long as the ceremony needs.
         let msg = self.do_with_stream(|s, _| {
-            decode_from(s, Infinite)
+            receive_with_status(s, ty, msgid, MAX_PAYLOAD, |text| println!("{}", text))
         });
 
-        self.msgid += 1;
-
-        let _ = self.s.write_all(&NETWORK_ACK);
-        let _ = self.s.flush();
+        self.msgid = msgid;
 
         msg
     }
 
-    fn write<T: Encodable>(&mut self, obj: &T) {
+    fn write<E: Encodable>(&mut self, ty: MessageType, obj: &E) {
         self.msgid += 1;
 
         let msgid = self.msgid;
@@ -155,38 +175,48 @@ impl ConnectionHandler {
                 return Ok(());
             }
 
-            if encode_into(obj, s, Infinite).is_err() {
-                // Couldn't write to the socket, this error will trigger
-                // a reconnect.
-                return Err("couldn't send data".to_string())
-            }
-
-            if s.flush().is_err() {
-                // Couldn't flush the buffer, assume the connection failed.
-                return Err("couldn't flush buffer".to_string())
-            }
-
-            // We expect an ACK now.
-            let mut ack: [u8; 4] = [0; 4];
-            let _ = s.read_exact(&mut ack);
-
-            if ack != NETWORK_ACK {
-                // Bad ACK, this error will trigger reconnect.
-                return Err("bad or no ack".to_string())
-            }
-
-            // All good.
-            Ok(())
+            send(s, ty, msgid, obj)
         })
     }
 }
 
 fn main() {
+    let config = Config::load("network", Config::default());
+
+    set_threads(config.threads);
+
+    // Validated along with the rest of the config, if it's given.
+    let coordinator_key = match config.coordinator_key {
+        Some(ref key) => CoordinatorKey::from_hex(key).unwrap(),
+        None => {
+            println!("The coordinator's published key is needed to connect to it; pass it with --coordinator-key <hex>.");
+            std::process::exit(2);
+        }
+    };
+
     prompt("Press [ENTER] when you're ready to perform diagnostics of the DVD drive.");
-    //perform_diagnostics();
+    //perform_diagnostics(&config);
     prompt("Diagnostics complete. Press [ENTER] when you're ready to begin the ceremony.");
 
-    let mut handler = ConnectionHandler::new();
+    match config.transport {
+        TransportKind::Tcp => participate(Tcp, &config, coordinator_key),
+        TransportKind::Unix => participate(Unix, &config, coordinator_key)
+    }
+
+    eject();
+
+    loop {
+        prompt("Done! Both machines can be shut down.\n\
+                Do not destroy any DVDs, and ensure there are no DVDs still\n\
+                inside of either machine. Place them all in a safe and secure\n\
+                place.");
+    }
+}
+
+/// Connects to the coordinator, commits to the compute machine's
+/// public key and relays its discs.
+fn participate<T: Transport>(transport: T, config: &Config, coordinator_key: CoordinatorKey) {
+    let mut handler = ConnectionHandler::new(transport, config, coordinator_key);
 
     let comm;
     {
@@ -205,73 +235,132 @@ fn main() {
         }
     }
 
-    handler.write(&comm);
+    let availability;
+    {
+        let mut entered_wrong = false;
+        loop {
+            let msg = prompt(&format!("Please enter when you are available to take your turns, in hours from now,\n\
+                              like \"0-4, 10-14\". Leave it empty if you are available throughout.{}\n\n",
+                              if entered_wrong { "\n\nInvalid, try again!"} else { "" }));
+
+            if let Some(a) = Availability::parse(&msg, now()) {
+                availability = a;
+                break;
+            } else {
+                entered_wrong = true;
+            }
+        }
+    }
 
-    println!("Waiting to receive disc 'A' from coordinator server...");
-    let hash_of_commitments = handler.read::<Digest512>();
-    let stage1_before = handler.read::<Stage1Contents>();
-    let prev_msg_hash = handler.read::<Digest256>();
+    handler.write(MessageType::Commitment, &comm);
+    handler.write(MessageType::Availability, &availability);
 
-    let (pubkey, nizks, stage1_after, ihash): (PublicKey, PublicKeyNizks, Stage1Contents, Digest256) = exchange_disc(
-        "A",
-        "B",
-        |f| -> Result<(), bincode::rustc_serialize::EncodingError> {
-            try!(encode_into(&hash_of_commitments, f, Infinite));
-            try!(encode_into(&stage1_before, f, Infinite));
+    match config.scheme {
+        ProofSystem::Pghr13 => relay::<Pghr13, T>(config, &mut handler),
+        ProofSystem::Groth16 => relay::<Groth16, T>(config, &mut handler)
+    }
+}
 
-            encode_into(&prev_msg_hash, f, Infinite)
-        },
-        |f, _| -> Result<(PublicKey, PublicKeyNizks, Stage1Contents, Digest256), bincode::rustc_serialize::DecodingError> {
-            let pubkey: PublicKey = try!(decode_from(f, Infinite));
-            let nizks: PublicKeyNizks = try!(decode_from(f, Infinite));
-            let stage: Stage1Contents = try!(decode_from(f, Infinite));
-            let ihash: Digest256 = try!(decode_from(f, Infinite));
+/// Relays the discs between the compute machine and the coordinator,
+/// decoding them as the contents of the ceremony's scheme.
+fn relay<S: Scheme, T: Transport>(config: &Config, handler: &mut ConnectionHandler<T>) {
+    // A ceremony started from an accumulator has no stage 1 turns, so
+    // the public key is revealed in the stage 2 turn instead.
+    if !config.from_accumulator {
+        println!("Waiting to receive disc 'A' from coordinator server...");
+        let hash_of_commitments = handler.read::<Digest512>(MessageType::HashOfCommitments);
+        let stage1_before = handler.read::<Stage1Contents>(MessageType::Stage1);
+        let prev_msg_hash = handler.read::<Digest256>(MessageType::LastMessageHash);
+
+        let (pubkey, nizks, stage1_after, ihash): (S::PublicKey, S::PublicKeyNizks, Stage1Contents, Digest256) = handler.busy(|| exchange_disc(
+            config,
+            "A",
+            "B",
+            |f| -> Result<(), bincode::rustc_serialize::EncodingError> {
+                try!(encode_into(&hash_of_commitments, f, Infinite));
+                try!(encode_into(&stage1_before, f, Infinite));
+
+                encode_into(&prev_msg_hash, f, Infinite)
+            },
+            |f, _| -> Result<(S::PublicKey, S::PublicKeyNizks, Stage1Contents, Digest256), bincode::rustc_serialize::DecodingError> {
+                let pubkey: S::PublicKey = try!(decode_from(f, Infinite));
+                let nizks: S::PublicKeyNizks = try!(decode_from(f, Infinite));
+                let stage: Stage1Contents = try!(decode_from(f, Infinite));
+                let ihash: Digest256 = try!(decode_from(f, Infinite));
+
+                Ok((pubkey, nizks, stage, ihash))
+            }
+        ));
 
-            Ok((pubkey, nizks, stage, ihash))
-        }
-    );
+        println!("Sending disc 'B' to the coordinator server...");
+        handler.write(MessageType::PublicKey, &pubkey);
+        handler.write(MessageType::PublicKeyNizks, &nizks);
+        handler.write(MessageType::Stage1, &stage1_after);
+        handler.write(MessageType::Ihash, &ihash);
 
-    println!("Sending disc 'B' to the coordinator server...");
-    handler.write(&pubkey);
-    handler.write(&nizks);
-    handler.write(&stage1_after);
-    handler.write(&ihash);
+        drop(stage1_before);
+        drop(stage1_after);
 
-    drop(stage1_before);
-    drop(stage1_after);
+        // A universal ceremony ends with stage 1.
+        if config.powers_of_tau.is_some() {
+            return;
+        }
+    }
 
     println!("Waiting to receive disc 'C' from coordinator server...");
-    let stage2_before = handler.read::<Stage2Contents>();
-    let prev_msg_hash = handler.read::<Digest256>();
-
-    let (stage2_after, ihash): (Stage2Contents, Digest256) = exchange_disc(
+    let hash_of_commitments = if config.from_accumulator {
+        Some(handler.read::<Digest512>(MessageType::HashOfCommitments))
+    } else {
+        None
+    };
+    let stage2_before = handler.read::<S::Stage2>(MessageType::Stage2);
+    let prev_msg_hash = handler.read::<Digest256>(MessageType::LastMessageHash);
+
+    let (revealed, stage2_after, ihash): (Option<(S::PublicKey, S::PublicKeyNizks)>, S::Stage2, Digest256) = handler.busy(|| exchange_disc(
+        config,
         "C",
         "D",
         |f| {
+            if let Some(ref hash_of_commitments) = hash_of_commitments {
+                try!(encode_into(hash_of_commitments, f, Infinite));
+            }
             try!(encode_into(&stage2_before, f, Infinite));
 
             encode_into(&prev_msg_hash, f, Infinite)
         },
-        |f, _| -> Result<(Stage2Contents, Digest256), bincode::rustc_serialize::DecodingError> {
-            let stage2_after: Stage2Contents = try!(decode_from(f, Infinite));
+        |f, _| -> Result<(Option<(S::PublicKey, S::PublicKeyNizks)>, S::Stage2, Digest256), bincode::rustc_serialize::DecodingError> {
+            let revealed = if config.from_accumulator {
+                let pubkey: S::PublicKey = try!(decode_from(f, Infinite));
+                let nizks: S::PublicKeyNizks = try!(decode_from(f, Infinite));
+
+                Some((pubkey, nizks))
+            } else {
+                None
+            };
+            let stage2_after: S::Stage2 = try!(decode_from(f, Infinite));
             let ihash: Digest256 = try!(decode_from(f, Infinite));
 
-            Ok((stage2_after, ihash))
+            Ok((revealed, stage2_after, ihash))
         }
-    );
+    ));
 
     println!("Sending disc 'D' to the coordinator server...");
-    handler.write(&stage2_after);
-    handler.write(&ihash);
+    if let Some((pubkey, nizks)) = revealed {
+        handler.write(MessageType::PublicKey, &pubkey);
+        handler.write(MessageType::PublicKeyNizks, &nizks);
+    }
+    handler.write(MessageType::Stage2, &stage2_after);
+    handler.write(MessageType::Ihash, &ihash);
 
     drop(stage2_before);
     drop(stage2_after);
 
     println!("Waiting to receive disc 'E' from coordinator server...");
-    let stage3_before = handler.read::<Stage3Contents>();
-    let prev_msg_hash = handler.read::<Digest256>();
+    let stage3_before = handler.read::<S::Stage3>(MessageType::Stage3);
+    let prev_msg_hash = handler.read::<Digest256>(MessageType::LastMessageHash);
 
-    let (stage3_after, ihash): (Stage3Contents, Digest256) = exchange_disc(
+    let (stage3_after, ihash): (S::Stage3, Digest256) = handler.busy(|| exchange_disc(
+        config,
         "E",
         "F",
         |f| {
@@ -279,27 +368,18 @@ fn main() {
 
             encode_into(&prev_msg_hash, f, Infinite)
         },
-        |f, _| -> Result<(Stage3Contents, Digest256), bincode::rustc_serialize::DecodingError> {
-            let stage3_after: Stage3Contents = try!(decode_from(f, Infinite));
+        |f, _| -> Result<(S::Stage3, Digest256), bincode::rustc_serialize::DecodingError> {
+            let stage3_after: S::Stage3 = try!(decode_from(f, Infinite));
             let ihash: Digest256 = try!(decode_from(f, Infinite));
 
             Ok((stage3_after, ihash))
         }
-    );
+    ));
 
     println!("Sending disc 'F' to the coordinator server...");
-    handler.write(&stage3_after);
-    handler.write(&ihash);
+    handler.write(MessageType::Stage3, &stage3_after);
+    handler.write(MessageType::Ihash, &ihash);
 
     drop(stage3_before);
     drop(stage3_after);
-
-    eject();
-
-    loop {
-        prompt("Done! Both machines can be shut down.\n\
-                Do not destroy any DVDs, and ensure there are no DVDs still\n\
-                inside of either machine. Place them all in a safe and secure\n\
-                place.");
-    }
 }
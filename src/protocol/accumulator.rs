@@ -0,0 +1,253 @@
+//! The result of a universal powers of tau ceremony, which isn't tied to
+//! any constraint system: stage 1 alone, run up to 2^k powers of tau.
+//!
+//! A ceremony for a particular constraint system can take its stage 1
+//! from the first powers of the accumulator, as long as there are enough
+//! of them for its scheme and QAP, and start right away with stage 2, so
+//! that one expensive stage 1 serves many constraint systems. Its tau is
+//! then only as secret as the universal ceremony kept it.
+//!
+//! The verifier exports the accumulator once it has verified the
+//! transcript of the universal ceremony, in the following format. All
+//! integers are little endian.
+//!
+//! ```text
+//! magic [u8; 8] = "mpcptau\0" | version u32 | k u32 | Stage1Contents (bincode)
+//! ```
+//!
+//! There is nothing after the powers, so the hash of the file is the
+//! hash of the accumulator.
+
+use bn::*;
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::fs::File;
+use std::fmt;
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::{encode_into, decode_from, EncodingError, DecodingError};
+use super::spair::*;
+use super::digest::{Digest256, Digest256Writer};
+use super::Stage1Contents;
+
+pub const ACCUMULATOR_MAGIC: [u8; 8] = [0x6d, 0x70, 0x63, 0x70, 0x74, 0x61, 0x75, 0x00];
+pub const ACCUMULATOR_VERSION: u32 = 1;
+
+pub struct Accumulator {
+    /// There are 2^k powers of tau, starting from tau^0.
+    k: u32,
+    powers: Stage1Contents
+}
+
+impl Accumulator {
+    /// The final contents of stage 1 of a universal ceremony, which must
+    /// have a power of two number of powers of tau.
+    pub fn new(powers: Stage1Contents) -> Accumulator {
+        let n = powers.v1.len();
+        assert!(n.is_power_of_two() && n >= 2);
+
+        Accumulator {
+            k: n.trailing_zeros(),
+            powers: powers
+        }
+    }
+
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// The first `n` powers of tau, to take stage 1 of a ceremony from,
+    /// or `None` if there aren't enough.
+    pub fn stage1(&self, n: usize) -> Option<Stage1Contents> {
+        if n > self.powers.v1.len() {
+            return None;
+        }
+
+        Some(Stage1Contents {
+            v1: self.powers.v1[0..n].to_vec(),
+            v2: self.powers.v2[0..n].to_vec()
+        })
+    }
+
+    /// Checks that the accumulator really contains consecutive powers
+    /// of some tau, in both groups, as the transcript it came from
+    /// should have guaranteed.
+    pub fn is_well_formed(&self) -> bool {
+        let (v1, v2) = (&self.powers.v1, &self.powers.v2);
+
+        v1.len() == 1 << self.k &&
+        v2.len() == 1 << self.k &&
+        v1[0] == G1::one() &&
+        v2[0] == G2::one() &&
+        !v1[1].is_zero() &&
+        !v2[1].is_zero() &&
+        checkseq(v1, &Spair::new(v2[0], v2[1]).unwrap()) &&
+        checkseq(v2, &Spair::new(v1[0], v1[1]).unwrap())
+    }
+
+    pub fn hash(&self) -> Digest256 {
+        let mut h = Digest256Writer::new();
+        self.write(&mut h).unwrap();
+
+        h.finish()
+    }
+
+    pub fn from_file(path: &str) -> Result<Accumulator, AccumulatorError> {
+        Accumulator::read(&mut BufReader::new(try!(File::open(path))))
+    }
+
+    pub fn to_file(&self, path: &str) -> Result<(), AccumulatorError> {
+        let mut f = BufWriter::new(try!(File::create(path)));
+
+        try!(self.write(&mut f));
+        try!(f.flush());
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> Result<Accumulator, AccumulatorError> {
+        let mut magic = [0; 8];
+        try!(r.read_exact(&mut magic));
+
+        if magic != ACCUMULATOR_MAGIC {
+            return Err(AccumulatorError::BadMagic);
+        }
+
+        let version = try!(r.read_u32::<LittleEndian>());
+        if version != ACCUMULATOR_VERSION {
+            return Err(AccumulatorError::UnsupportedVersion(version));
+        }
+
+        let k = try!(r.read_u32::<LittleEndian>());
+        if k == 0 || k > 28 {
+            return Err(AccumulatorError::Corrupt("unsupported number of powers"));
+        }
+
+        let powers: Stage1Contents = try!(decode_from(r, Infinite));
+        if powers.v1.len() != 1 << k || powers.v2.len() != 1 << k {
+            return Err(AccumulatorError::Corrupt("wrong number of powers"));
+        }
+
+        if try!(r.read(&mut [0])) != 0 {
+            return Err(AccumulatorError::Corrupt("trailing data"));
+        }
+
+        Ok(Accumulator {
+            k: k,
+            powers: powers
+        })
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> Result<(), AccumulatorError> {
+        try!(w.write_all(&ACCUMULATOR_MAGIC));
+        try!(w.write_u32::<LittleEndian>(ACCUMULATOR_VERSION));
+        try!(w.write_u32::<LittleEndian>(self.k));
+        try!(encode_into(&self.powers, w, Infinite));
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum AccumulatorError {
+    Io(io::Error),
+    Encoding(EncodingError),
+    Decoding(DecodingError),
+    BadMagic,
+    UnsupportedVersion(u32),
+    Corrupt(&'static str)
+}
+
+impl fmt::Display for AccumulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AccumulatorError::Io(ref e) => write!(f, "i/o error: {}", e),
+            AccumulatorError::Encoding(ref e) => write!(f, "encoding error: {}", e),
+            AccumulatorError::Decoding(ref e) => write!(f, "decoding error: {}", e),
+            AccumulatorError::BadMagic => write!(f, "not a powers of tau accumulator"),
+            AccumulatorError::UnsupportedVersion(v) => write!(f, "unsupported accumulator version {}", v),
+            AccumulatorError::Corrupt(e) => write!(f, "corrupt accumulator: {}", e)
+        }
+    }
+}
+
+impl From<io::Error> for AccumulatorError {
+    fn from(e: io::Error) -> AccumulatorError {
+        AccumulatorError::Io(e)
+    }
+}
+
+impl From<EncodingError> for AccumulatorError {
+    fn from(e: EncodingError) -> AccumulatorError {
+        AccumulatorError::Encoding(e)
+    }
+}
+
+impl From<DecodingError> for AccumulatorError {
+    fn from(e: DecodingError) -> AccumulatorError {
+        AccumulatorError::Decoding(e)
+    }
+}
+
+#[test]
+fn accumulator_roundtrip() {
+    use super::scheme::*;
+    use super::r1cs::{R1CS, Constraint, QAP};
+    use super::{PrivateKey, Pghr13};
+    use super::groth16::Groth16;
+
+    let rng = &mut ::rand::thread_rng();
+
+    let mut powers = Stage1Contents::with_powers(32);
+    for _ in 0..2 {
+        let privkey = PrivateKey::new(rng);
+        let pubkey = privkey.pubkey(rng);
+        let prev = powers.clone();
+        powers.transform(&privkey);
+        assert!(powers.verify_transform(&prev, &pubkey));
+    }
+
+    let accumulator = Accumulator::new(powers);
+    assert_eq!(accumulator.k(), 5);
+    assert!(accumulator.is_well_formed());
+
+    let mut encoded = vec![];
+    accumulator.write(&mut encoded).unwrap();
+    assert!(accumulator.hash() == Digest256::from_reader(&mut &encoded[..]));
+
+    let decoded = Accumulator::read(&mut &encoded[..]).unwrap();
+    assert!(decoded.is_well_formed());
+    assert!(decoded.hash() == accumulator.hash());
+
+    let mut longer = encoded.clone();
+    longer.push(0);
+    assert!(Accumulator::read(&mut &longer[..]).is_err());
+    assert!(Accumulator::read(&mut &encoded[..encoded.len() - 1]).is_err());
+
+    // Powers out of order aren't consecutive powers of anything.
+    let mut swapped = Accumulator::read(&mut &encoded[..]).unwrap();
+    swapped.powers.v1.swap(2, 3);
+    assert!(!swapped.is_well_formed());
+
+    // Both schemes can start from the accumulator, as long as it's
+    // large enough.
+    let qap = QAP::new(R1CS {
+        num_inputs: 2,
+        num_aux: 3,
+        constraints: (0..10).map(|i| {
+            Constraint {
+                a: vec![(i % 6, Fr::random(rng))],
+                b: vec![(0, Fr::random(rng))],
+                c: vec![(i % 5, Fr::random(rng))]
+            }
+        }).collect()
+    }).unwrap();
+
+    for &n in &[Pghr13::powers_of_tau(&qap), Groth16::powers_of_tau(&qap)] {
+        let stage1 = accumulator.stage1(n).unwrap();
+        assert_eq!(stage1.v1.len(), n);
+        assert!(stage1.v1[..] == accumulator.powers.v1[0..n]);
+        assert!(stage1.v2[..] == accumulator.powers.v2[0..n]);
+    }
+
+    assert!(accumulator.stage1(33).is_none());
+}
@@ -0,0 +1,140 @@
+//! A public random beacon, which takes part in the ceremony as a final
+//! player whose secrets anyone can recompute.
+//!
+//! The secrets are sampled from ChaCha, seeded with the beacon value
+//! hashed `iterations` times with blake2s. The value should be something
+//! nobody can predict or influence before the other players have made
+//! their contributions, like the hash of a future block, and the number
+//! of iterations large enough that nobody could have tried out values
+//! in the time they had to choose one.
+//!
+//! Since its secrets are public, the beacon's contribution adds no
+//! secrecy of its own; it only guarantees that the final parameters
+//! aren't chosen by the last human player.
+
+use rand::SeedableRng;
+use rand::chacha::ChaChaRng;
+use rustc_serialize::{Encodable, Encoder, Decodable, Decoder};
+use rustc_serialize::hex::{FromHex, ToHex};
+use blake2_rfc::blake2s::blake2s;
+use byteorder::{ByteOrder, LittleEndian};
+use super::digest::Digest512;
+use super::scheme::*;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Beacon {
+    pub value: Vec<u8>,
+    pub iterations: u64
+}
+
+impl Encodable for Beacon {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        try!(self.value.encode(s));
+        try!(self.iterations.encode(s));
+
+        Ok(())
+    }
+}
+
+impl Decodable for Beacon {
+    fn decode<S: Decoder>(s: &mut S) -> Result<Beacon, S::Error> {
+        let value = try!(Vec::<u8>::decode(s));
+        let iterations = try!(u64::decode(s));
+
+        Beacon::new(value, iterations).ok_or_else(|| s.error("the beacon must be hashed at least once"))
+    }
+}
+
+impl Beacon {
+    /// The value is hashed at least once, so that `seed` hashes it
+    /// exactly `iterations` times.
+    pub fn new(value: Vec<u8>, iterations: u64) -> Option<Beacon> {
+        if iterations == 0 {
+            None
+        } else {
+            Some(Beacon {
+                value: value,
+                iterations: iterations
+            })
+        }
+    }
+
+    /// Parses the beacon value from hex.
+    pub fn from_hex(value: &str, iterations: u64) -> Option<Beacon> {
+        value.from_hex().ok().and_then(|value| Beacon::new(value, iterations))
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.value.to_hex()
+    }
+
+    /// The seed of the beacon's randomness.
+    pub fn seed(&self) -> [u32; 8] {
+        let mut hash = blake2s(32, &[], &self.value);
+
+        for _ in 1..self.iterations {
+            hash = blake2s(32, &[], hash.as_bytes());
+        }
+
+        let mut seed = [0; 8];
+
+        for i in 0..8 {
+            seed[i] = LittleEndian::read_u32(&hash.as_bytes()[(i*4)..]);
+        }
+
+        seed
+    }
+
+    /// The beacon's secrets, and its public key and nizks, sampled just
+    /// as the compute node samples a player's.
+    pub fn contribution<S: Scheme>(&self, hash_of_commitments: &Digest512) -> (S::PrivateKey, S::PublicKey, S::PublicKeyNizks) {
+        let rng = &mut ChaChaRng::from_seed(&self.seed());
+
+        let privkey = <S::PrivateKey as SchemePrivateKey>::new(rng);
+        let pubkey = privkey.pubkey(rng);
+        let nizks = pubkey.nizks(rng, &privkey, hash_of_commitments);
+
+        (privkey, pubkey, nizks)
+    }
+}
+
+#[test]
+fn beacon_is_deterministic() {
+    use bincode::rustc_serialize::{encode, decode};
+    use bincode::SizeLimit::Infinite;
+    use super::Pghr13;
+    use super::groth16::Groth16;
+
+    let hash_of_commitments = Digest512::from(&"commitments").unwrap();
+
+    let beacon = Beacon::from_hex("00000000000000000004a1b2c3d4e5f6", 1000).unwrap();
+    assert_eq!(beacon.to_hex(), "00000000000000000004a1b2c3d4e5f6");
+    assert!(Beacon::from_hex("not hex", 1000).is_none());
+    assert!(Beacon::from_hex("00", 0).is_none());
+
+    let encoded = encode(&beacon, Infinite).unwrap();
+    assert!(decode::<Beacon>(&encoded).unwrap() == beacon);
+
+    let unhashed = encode(&Beacon { iterations: 0, .. beacon.clone() }, Infinite).unwrap();
+    assert!(decode::<Beacon>(&unhashed).is_err());
+
+    let (_, pubkey, nizks) = beacon.contribution::<Pghr13>(&hash_of_commitments);
+    let (_, again, _) = beacon.contribution::<Pghr13>(&hash_of_commitments);
+    assert!(pubkey == again);
+    assert!(nizks.is_valid(&pubkey, &hash_of_commitments));
+
+    let (_, pubkey, _) = beacon.contribution::<Groth16>(&hash_of_commitments);
+    let (_, again, _) = beacon.contribution::<Groth16>(&hash_of_commitments);
+    assert!(pubkey == again);
+
+    // Every iteration counts.
+    let other = Beacon {
+        iterations: 1001,
+        .. beacon.clone()
+    };
+    assert!(other.seed() != beacon.seed());
+
+    let (_, pubkey, _) = beacon.contribution::<Pghr13>(&hash_of_commitments);
+    let (_, different, _) = other.contribution::<Pghr13>(&hash_of_commitments);
+    assert!(pubkey != different);
+}
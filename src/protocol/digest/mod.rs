@@ -1,6 +1,6 @@
 use bn::Fr;
 
-use std::io::Read;
+use std::io::{self, Read, Write};
 use rustc_serialize::{Encodable, Encoder, Decodable, Decoder};
 use bincode::SizeLimit::Infinite;
 use bincode::rustc_serialize::encode;
@@ -92,14 +92,24 @@ impl Digest512 {
 
 impl Digest256 {
     pub fn from_reader<R: Read>(r: &mut R) -> Digest256 {
-        use blake2_rfc::blake2s::blake2s;
-
-        let mut contents = vec![];
-
-        r.read_to_end(&mut contents).unwrap();
+        use blake2_rfc::blake2s::Blake2s;
+
+        // Hash in chunks, since this is used on files which may
+        // not comfortably fit in memory.
+        let mut state = Blake2s::new(32);
+        let mut buf = [0; 65536];
+
+        loop {
+            match r.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => state.update(&buf[0..n]),
+                Err(ref e) if e.kind() == ::std::io::ErrorKind::Interrupted => {},
+                Err(e) => panic!("couldn't read contents to hash: {}", e)
+            }
+        }
 
         let mut output = [0; 32];
-        output.copy_from_slice(&blake2s(32, &[], &contents).as_bytes());
+        output.copy_from_slice(&state.finalize().as_bytes());
 
         Digest256(output)
     }
@@ -125,9 +135,39 @@ impl Digest256 {
     }
 }
 
+/// Hashes whatever is written to it, for things which are encoded
+/// piece by piece and may not comfortably fit in memory.
+pub struct Digest256Writer(::blake2_rfc::blake2s::Blake2s);
+
+impl Digest256Writer {
+    pub fn new() -> Digest256Writer {
+        Digest256Writer(::blake2_rfc::blake2s::Blake2s::new(32))
+    }
+
+    pub fn finish(self) -> Digest256 {
+        let mut output = [0; 32];
+        output.copy_from_slice(&self.0.finalize().as_bytes());
+
+        Digest256(output)
+    }
+}
+
+impl Write for Digest256Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 #[test]
 fn digest_string_repr() {
     use super::secrets::*;
+    use super::scheme::{SchemePrivateKey, SchemePublicKey};
 
     let rng = &mut ::rand::thread_rng();
 
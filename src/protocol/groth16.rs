@@ -0,0 +1,618 @@
+//! Parameters for Groth16 ["On the Size of Pairing-based Non-interactive
+//! Arguments", 2016], constructed from the same powers of tau as those of
+//! `Pghr13`.
+//!
+//! 1. *Stage 1: Powers of Tau* - As for `Pghr13`, except that it goes up
+//!    to tau^(2d-2), which the H query needs.
+//! 2. *Stage 2: Alpha and beta* - The coordinator evaluates the QAP at
+//!    tau, and the players multiply the A polynomials by beta and the B
+//!    polynomials by alpha.
+//! 3. *Stage 3: Delta* - The coordinator combines the evaluations into
+//!    the L query, and the players divide it and the H query by delta.
+//!
+//! Gamma is left as 1, which Bowe, Gabizon and Miers show to be safe, so
+//! the coordinator computes the verifying key's input consistency query
+//! without anyone's secrets. Delta needs a stage of its own because the
+//! L query sums terms in alpha and beta, which can't be multiplied in
+//! afterwards.
+//!
+//! The keys are stored like `Pghr13`'s (see `keypair.rs`), with their
+//! own magic:
+//!
+//! ```text
+//! pk:  magic [u8; 8] = "mpcg16p\0" | version u32 | ProvingKey (bincode)
+//! vk:  magic [u8; 8] = "mpcg16v\0" | version u32 | VerifyingKey (bincode)
+//! ```
+
+use bn::*;
+use rand::Rng;
+use std::io::{Read, BufReader};
+use std::fs::File;
+use bincode::rustc_serialize::DecodingError;
+use super::spair::*;
+use super::nizk::Nizk;
+use super::multicore::*;
+use super::stream::*;
+use super::digest::{Digest256, Digest512};
+use super::r1cs::QAP;
+use super::qap;
+use super::scheme::*;
+use super::keypair::{write_key, read_key, KeyError};
+use super::Stage1Contents;
+
+pub const PK_MAGIC: [u8; 8] = [0x6d, 0x70, 0x63, 0x67, 0x31, 0x36, 0x70, 0x00];
+pub const VK_MAGIC: [u8; 8] = [0x6d, 0x70, 0x63, 0x67, 0x31, 0x36, 0x76, 0x00];
+
+#[derive(RustcEncodable, RustcDecodable)]
+pub struct Groth16;
+
+impl Scheme for Groth16 {
+    type PrivateKey = PrivateKey;
+    type PublicKey = PublicKey;
+    type PublicKeyNizks = PublicKeyNizks;
+    type Stage2 = Stage2Contents;
+    type Stage3 = Stage3Contents;
+
+    fn proof_system() -> ProofSystem {
+        ProofSystem::Groth16
+    }
+
+    fn powers_of_tau(qap: &QAP) -> usize {
+        2 * qap.d - 1
+    }
+
+    fn stage2(qap: &QAP, stage1: &Stage1Contents) -> Stage2Contents {
+        Stage2Contents::new(qap, stage1)
+    }
+
+    fn stage3(qap: &QAP, stage1: &Stage1Contents, stage2: &Stage2Contents) -> Stage3Contents {
+        Stage3Contents::new(qap, stage1, stage2)
+    }
+}
+
+/// The secrets sampled by the player.
+pub struct PrivateKey {
+    pub tau: Fr,
+    pub alpha: Fr,
+    pub beta: Fr,
+    pub delta: Fr
+}
+
+impl SchemePrivateKey for PrivateKey {
+    type PublicKey = PublicKey;
+
+    fn new<R: Rng>(rng: &mut R) -> PrivateKey {
+        PrivateKey {
+            tau: Fr::random(rng),
+            alpha: Fr::random(rng),
+            beta: Fr::random(rng),
+            delta: Fr::random(rng)
+        }
+    }
+
+    fn pubkey<R: Rng>(&self, rng: &mut R) -> PublicKey {
+        PublicKey {
+            tau: Spair::random(rng, self.tau).unwrap(),
+            alpha: Spair::random(rng, self.alpha).unwrap(),
+            beta: Spair::random(rng, self.beta).unwrap(),
+            delta: Spair::random(rng, self.delta).unwrap()
+        }
+    }
+
+    fn tau(&self) -> Fr {
+        self.tau
+    }
+}
+
+/// Each secret is needed on its own, so unlike `Pghr13`'s, the public
+/// key has no relationships between its parts to check.
+#[derive(Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct PublicKey {
+    tau: Spair<G2>,
+    alpha: Spair<G2>,
+    beta: Spair<G2>,
+    delta: Spair<G2>
+}
+
+impl PublicKey {
+    pub fn alpha_g2(&self) -> Spair<G2> {
+        self.alpha.clone()
+    }
+
+    pub fn beta_g2(&self) -> Spair<G2> {
+        self.beta.clone()
+    }
+
+    pub fn delta_g2(&self) -> Spair<G2> {
+        self.delta.clone()
+    }
+}
+
+impl SchemePublicKey for PublicKey {
+    type PrivateKey = PrivateKey;
+    type Nizks = PublicKeyNizks;
+
+    fn hash(&self) -> Digest256 {
+        Digest256::from(self).expect("PublicKey should never fail to encode")
+    }
+
+    fn nizks<R: Rng>(&self, rng: &mut R, privkey: &PrivateKey, extra: &Digest512) -> PublicKeyNizks {
+        PublicKeyNizks {
+            tau: self.tau.nizk(rng, privkey.tau, extra),
+            alpha: self.alpha.nizk(rng, privkey.alpha, extra),
+            beta: self.beta.nizk(rng, privkey.beta, extra),
+            delta: self.delta.nizk(rng, privkey.delta, extra)
+        }
+    }
+
+    fn tau_g2(&self) -> Spair<G2> {
+        self.tau.clone()
+    }
+}
+
+#[derive(RustcEncodable, RustcDecodable)]
+pub struct PublicKeyNizks {
+    tau: Nizk<G2>,
+    alpha: Nizk<G2>,
+    beta: Nizk<G2>,
+    delta: Nizk<G2>
+}
+
+impl SchemeNizks for PublicKeyNizks {
+    type PublicKey = PublicKey;
+
+    fn verify_each(&self, pubkey: &PublicKey, extra: &Digest512) -> Vec<(&'static str, bool)> {
+        vec![
+            ("tau", pubkey.tau.verify_nizk(&self.tau, extra)),
+            ("alpha", pubkey.alpha.verify_nizk(&self.alpha, extra)),
+            ("beta", pubkey.beta.verify_nizk(&self.beta, extra)),
+            ("delta", pubkey.delta.verify_nizk(&self.delta, extra))
+        ]
+    }
+}
+
+/// Evaluates the QAP at tau, without libsnark's extension by Z(tau),
+/// which Groth16 has no use for.
+fn evaluate(qap: &QAP, stage1: &Stage1Contents) -> (Vec<G1>, Vec<G1>, Vec<G2>, Vec<G1>) {
+    let (mut at, mut bt1, mut bt2, mut ct) = qap::evaluate(&stage1.v1[0..qap.d+1], &stage1.v2[0..qap.d+1], qap);
+
+    at.truncate(qap.num_vars);
+    bt1.truncate(qap.num_vars);
+    bt2.truncate(qap.num_vars);
+    ct.truncate(qap.num_vars);
+
+    (at, bt1, bt2, ct)
+}
+
+/// Alpha and beta.
+#[derive(Clone, RustcEncodable, RustcDecodable)]
+pub struct Stage2Contents {
+    alpha_g1: G1,
+    beta_g1: G1,
+    beta_g2: G2,
+    /// beta * A_i(tau), for every variable.
+    beta_a: Vec<G1>,
+    /// alpha * B_i(tau), for every variable.
+    alpha_b: Vec<G1>
+}
+
+impl Stage2Contents {
+    pub fn new(qap: &QAP, stage1: &Stage1Contents) -> Self {
+        let (at, bt1, _, _) = evaluate(qap, stage1);
+
+        Stage2Contents {
+            alpha_g1: G1::one(),
+            beta_g1: G1::one(),
+            beta_g2: G2::one(),
+            beta_a: at,
+            alpha_b: bt1
+        }
+    }
+
+    pub fn is_well_formed(&self, prev: &Self) -> bool {
+        !prev.alpha_g1.is_zero() &&
+        !prev.beta_g1.is_zero() &&
+        !prev.beta_g2.is_zero() &&
+        !self.alpha_g1.is_zero() &&
+        !self.beta_g1.is_zero() &&
+        !self.beta_g2.is_zero() &&
+        self.beta_a.len() == prev.beta_a.len() &&
+        self.alpha_b.len() == prev.alpha_b.len()
+    }
+}
+
+impl Stage for Stage2Contents {
+    type PrivateKey = PrivateKey;
+    type PublicKey = PublicKey;
+
+    fn transform(&mut self, s: &PrivateKey) {
+        self.alpha_g1 = self.alpha_g1 * s.alpha;
+        self.beta_g1 = self.beta_g1 * s.beta;
+        self.beta_g2 = self.beta_g2 * s.beta;
+        mul_all_by(&mut self.beta_a, s.beta);
+        mul_all_by(&mut self.alpha_b, s.alpha);
+    }
+
+    fn verify_transform(&self, prev: &Self, p: &PublicKey) -> bool {
+        self.is_well_formed(prev) &&
+        same_power(
+            &Spair::new(prev.alpha_g1, self.alpha_g1).unwrap(),
+            &p.alpha_g2()
+        ) &&
+        same_power(
+            &Spair::new(prev.beta_g1, self.beta_g1).unwrap(),
+            &p.beta_g2()
+        ) &&
+        same_power(
+            &Spair::new(prev.beta_g2, self.beta_g2).unwrap(),
+            &Spair::new(prev.beta_g1, self.beta_g1).unwrap()
+        ) &&
+        checkvec(
+            &prev.beta_a,
+            &self.beta_a,
+            &p.beta_g2()
+        ) &&
+        checkvec(
+            &prev.alpha_b,
+            &self.alpha_b,
+            &p.alpha_g2()
+        )
+    }
+
+    fn verify_transform_batched<P: Read, N: Read>(
+        prev: &mut P,
+        next: &mut N,
+        p: &PublicKey,
+        window: usize,
+        batch: &mut Batch
+    ) -> Result<bool, DecodingError>
+    {
+        let prev_alpha_g1: G1 = try!(read_element(prev));
+        let prev_beta_g1: G1 = try!(read_element(prev));
+        let prev_beta_g2: G2 = try!(read_element(prev));
+        let alpha_g1: G1 = try!(read_element(next));
+        let beta_g1: G1 = try!(read_element(next));
+        let beta_g2: G2 = try!(read_element(next));
+
+        if prev_alpha_g1.is_zero() || prev_beta_g1.is_zero() || prev_beta_g2.is_zero() ||
+           alpha_g1.is_zero() || beta_g1.is_zero() || beta_g2.is_zero()
+        {
+            return Ok(false);
+        }
+
+        batch.same_power(
+            &Spair::new(prev_alpha_g1, alpha_g1).unwrap(),
+            &p.alpha_g2()
+        );
+        batch.same_power(
+            &Spair::new(prev_beta_g1, beta_g1).unwrap(),
+            &p.beta_g2()
+        );
+        batch.same_power(
+            &Spair::new(prev_beta_g2, beta_g2).unwrap(),
+            &Spair::new(prev_beta_g1, beta_g1).unwrap()
+        );
+
+        Ok(
+            try!(checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.beta_g2(), batch)) &&
+            try!(checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.alpha_g2(), batch))
+        )
+    }
+}
+
+/// Delta.
+#[derive(Clone, RustcEncodable, RustcDecodable)]
+pub struct Stage3Contents {
+    delta_g1: G1,
+    delta_g2: G2,
+    /// (beta * A_i(tau) + alpha * B_i(tau) + C_i(tau)) / delta, for
+    /// every auxiliary variable.
+    l: Vec<G1>,
+    /// tau^i * Z(tau) / delta, for i from 0 to d-2.
+    h: Vec<G1>
+}
+
+impl Stage3Contents {
+    pub fn new(qap: &QAP, stage1: &Stage1Contents, stage2: &Stage2Contents) -> Self {
+        assert_eq!(stage1.v1.len(), 2 * qap.d - 1);
+        assert_eq!(stage2.beta_a.len(), qap.num_vars);
+        assert_eq!(stage2.alpha_b.len(), qap.num_vars);
+
+        let (_, _, _, ct) = evaluate(qap, stage1);
+
+        let mut l = ct[qap.num_inputs+1..].to_vec();
+        add_all_to(&mut l, &stage2.beta_a[qap.num_inputs+1..]);
+        add_all_to(&mut l, &stage2.alpha_b[qap.num_inputs+1..]);
+
+        // Z(tau) = tau^d - 1
+        let h = (0..qap.d-1).map(|i| stage1.v1[i + qap.d] - stage1.v1[i]).collect();
+
+        Stage3Contents {
+            delta_g1: G1::one(),
+            delta_g2: G2::one(),
+            l: l,
+            h: h
+        }
+    }
+
+    pub fn is_well_formed(&self, prev: &Self) -> bool {
+        !prev.delta_g1.is_zero() &&
+        !prev.delta_g2.is_zero() &&
+        !self.delta_g1.is_zero() &&
+        !self.delta_g2.is_zero() &&
+        self.l.len() == prev.l.len() &&
+        self.h.len() == prev.h.len()
+    }
+}
+
+impl Stage for Stage3Contents {
+    type PrivateKey = PrivateKey;
+    type PublicKey = PublicKey;
+
+    fn transform(&mut self, s: &PrivateKey) {
+        let delta_inverse = s.delta.inverse().expect("delta should never be zero");
+
+        self.delta_g1 = self.delta_g1 * s.delta;
+        self.delta_g2 = self.delta_g2 * s.delta;
+        mul_all_by(&mut self.l, delta_inverse);
+        mul_all_by(&mut self.h, delta_inverse);
+    }
+
+    fn verify_transform(&self, prev: &Self, p: &PublicKey) -> bool {
+        self.is_well_formed(prev) &&
+        same_power(
+            &Spair::new(prev.delta_g1, self.delta_g1).unwrap(),
+            &p.delta_g2()
+        ) &&
+        same_power(
+            &Spair::new(prev.delta_g2, self.delta_g2).unwrap(),
+            &Spair::new(prev.delta_g1, self.delta_g1).unwrap()
+        ) &&
+        // The queries are divided by delta, so it's the previous ones
+        // which are the new ones times delta.
+        checkvec(
+            &self.l,
+            &prev.l,
+            &p.delta_g2()
+        ) &&
+        checkvec(
+            &self.h,
+            &prev.h,
+            &p.delta_g2()
+        )
+    }
+
+    fn verify_transform_batched<P: Read, N: Read>(
+        prev: &mut P,
+        next: &mut N,
+        p: &PublicKey,
+        window: usize,
+        batch: &mut Batch
+    ) -> Result<bool, DecodingError>
+    {
+        let prev_delta_g1: G1 = try!(read_element(prev));
+        let prev_delta_g2: G2 = try!(read_element(prev));
+        let delta_g1: G1 = try!(read_element(next));
+        let delta_g2: G2 = try!(read_element(next));
+
+        if prev_delta_g1.is_zero() || prev_delta_g2.is_zero() || delta_g1.is_zero() || delta_g2.is_zero() {
+            return Ok(false);
+        }
+
+        batch.same_power(
+            &Spair::new(prev_delta_g1, delta_g1).unwrap(),
+            &p.delta_g2()
+        );
+        batch.same_power(
+            &Spair::new(prev_delta_g2, delta_g2).unwrap(),
+            &Spair::new(prev_delta_g1, delta_g1).unwrap()
+        );
+
+        Ok(
+            try!(checkvec_streaming::<G1, _, _, _>(next, prev, window, &p.delta_g2(), batch)) &&
+            try!(checkvec_streaming::<G1, _, _, _>(next, prev, window, &p.delta_g2(), batch))
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct ProvingKey {
+    pub alpha_g1: G1,
+    pub beta_g1: G1,
+    pub beta_g2: G2,
+    pub delta_g1: G1,
+    pub delta_g2: G2,
+    /// A_i(tau), for every variable.
+    pub a_query: Vec<G1>,
+    /// B_i(tau), for every variable.
+    pub b_g1_query: Vec<G1>,
+    pub b_g2_query: Vec<G2>,
+    pub h_query: Vec<G1>,
+    pub l_query: Vec<G1>
+}
+
+#[derive(Clone, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct VerifyingKey {
+    pub alpha_g1: G1,
+    pub beta_g2: G2,
+    pub gamma_g2: G2,
+    pub delta_g2: G2,
+    /// beta * A_i(tau) + alpha * B_i(tau) + C_i(tau), for the constant
+    /// one and every input.
+    pub ic_query: Vec<G1>
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Keypair {
+    pub pk: ProvingKey,
+    pub vk: VerifyingKey
+}
+
+impl Keypair {
+    /// Writes the proving and verifying keys, returning the digest of
+    /// each file.
+    pub fn write_to_disk(&self, pk_path: &str, vk_path: &str) -> Result<(Digest256, Digest256), KeyError> {
+        try!(write_key(pk_path, &PK_MAGIC, &self.pk));
+        try!(write_key(vk_path, &VK_MAGIC, &self.vk));
+
+        let pk_hash = Digest256::from_reader(&mut BufReader::new(try!(File::open(pk_path))));
+        let vk_hash = Digest256::from_reader(&mut BufReader::new(try!(File::open(vk_path))));
+
+        Ok((pk_hash, vk_hash))
+    }
+
+    pub fn read_from_disk(pk_path: &str, vk_path: &str) -> Result<Keypair, KeyError> {
+        Ok(Keypair {
+            pk: try!(read_key(&mut BufReader::new(try!(File::open(pk_path))), &PK_MAGIC)),
+            vk: try!(read_key(&mut BufReader::new(try!(File::open(vk_path))), &VK_MAGIC))
+        })
+    }
+}
+
+pub fn keypair(
+    qap: &QAP,
+    stage1: &Stage1Contents,
+    stage2: &Stage2Contents,
+    stage3: &Stage3Contents
+) -> Keypair {
+    let (at, bt1, bt2, ct) = evaluate(qap, stage1);
+
+    let mut ic_query = ct[0..qap.num_inputs+1].to_vec();
+    add_all_to(&mut ic_query, &stage2.beta_a[0..qap.num_inputs+1]);
+    add_all_to(&mut ic_query, &stage2.alpha_b[0..qap.num_inputs+1]);
+
+    Keypair {
+        pk: ProvingKey {
+            alpha_g1: stage2.alpha_g1,
+            beta_g1: stage2.beta_g1,
+            beta_g2: stage2.beta_g2,
+            delta_g1: stage3.delta_g1,
+            delta_g2: stage3.delta_g2,
+            a_query: at,
+            b_g1_query: bt1,
+            b_g2_query: bt2,
+            h_query: stage3.h.clone(),
+            l_query: stage3.l.clone()
+        },
+        vk: VerifyingKey {
+            alpha_g1: stage2.alpha_g1,
+            beta_g2: stage2.beta_g2,
+            gamma_g2: G2::one(),
+            delta_g2: stage3.delta_g2,
+            ic_query: ic_query
+        }
+    }
+}
+
+#[test]
+fn groth16_keypair() {
+    use bincode::SizeLimit::Infinite;
+    use bincode::rustc_serialize::encode;
+    use super::r1cs::{R1CS, Constraint};
+
+    let rng = &mut ::rand::thread_rng();
+
+    let privkeys: Vec<_> = (0..3).map(|_| PrivateKey::new(rng)).collect();
+    let pubkeys: Vec<_> = privkeys.iter().map(|p| p.pubkey(rng)).collect();
+
+    let qap = QAP::new(R1CS {
+        num_inputs: 2,
+        num_aux: 3,
+        constraints: (0..10).map(|i| {
+            Constraint {
+                a: vec![(i % 6, Fr::random(rng)), (5, Fr::one())],
+                b: vec![(0, Fr::random(rng)), (i % 4, Fr::random(rng))],
+                c: vec![(i % 5, Fr::random(rng))]
+            }
+        }).collect()
+    }).unwrap();
+
+    let mut stage1 = Stage1Contents::new::<Groth16>(&qap);
+    for (private, public) in privkeys.iter().zip(pubkeys.iter()) {
+        let prev = stage1.clone();
+        stage1.transform(private);
+        assert!(stage1.verify_transform(&prev, public));
+    }
+
+    let mut stage2 = Groth16::stage2(&qap, &stage1);
+    for (private, public) in privkeys.iter().zip(pubkeys.iter()) {
+        let prev = stage2.clone();
+        stage2.transform(private);
+        assert!(stage2.verify_transform(&prev, public));
+        assert!(Stage2Contents::verify_transform_streaming(
+            &mut &encode(&prev, Infinite).unwrap()[..],
+            &mut &encode(&stage2, Infinite).unwrap()[..],
+            public,
+            3
+        ).unwrap());
+
+        // Somebody else's secrets don't verify.
+        let mut wrong = prev.clone();
+        wrong.transform(&PrivateKey::new(rng));
+        assert!(!wrong.verify_transform(&prev, public));
+    }
+
+    let mut stage3 = Groth16::stage3(&qap, &stage1, &stage2);
+    for (private, public) in privkeys.iter().zip(pubkeys.iter()) {
+        let prev = stage3.clone();
+        stage3.transform(private);
+        assert!(stage3.verify_transform(&prev, public));
+        assert!(Stage3Contents::verify_transform_streaming(
+            &mut &encode(&prev, Infinite).unwrap()[..],
+            &mut &encode(&stage3, Infinite).unwrap()[..],
+            public,
+            3
+        ).unwrap());
+
+        let mut wrong = prev.clone();
+        wrong.transform(&PrivateKey::new(rng));
+        assert!(!Stage3Contents::verify_transform_streaming(
+            &mut &encode(&prev, Infinite).unwrap()[..],
+            &mut &encode(&wrong, Infinite).unwrap()[..],
+            public,
+            3
+        ).unwrap());
+    }
+
+    let kp = keypair(&qap, &stage1, &stage2, &stage3);
+
+    // Compare to the keypair constructed from everyone's secrets at once.
+    let (tau, alpha, beta, delta) = privkeys.iter().fold(
+        (Fr::one(), Fr::one(), Fr::one(), Fr::one()),
+        |acc, p| (acc.0 * p.tau, acc.1 * p.alpha, acc.2 * p.beta, acc.3 * p.delta)
+    );
+    let delta_inverse = delta.inverse().unwrap();
+
+    let mut powers = vec![Fr::one()];
+    for _ in 1..(2 * qap.d - 1) {
+        let next = *powers.last().unwrap() * tau;
+        powers.push(next);
+    }
+
+    let (at, bt1, _, ct) = evaluate(&qap, &Stage1Contents {
+        v1: powers.iter().map(|p| G1::one() * *p).collect(),
+        v2: powers.iter().map(|p| G2::one() * *p).collect()
+    });
+
+    assert!(kp.pk.alpha_g1 == G1::one() * alpha);
+    assert!(kp.pk.beta_g2 == G2::one() * beta);
+    assert!(kp.pk.delta_g1 == G1::one() * delta);
+    assert!(kp.vk.delta_g2 == G2::one() * delta);
+    assert!(kp.pk.a_query == at);
+
+    for i in 0..qap.num_vars {
+        let combined = at[i] * beta + bt1[i] * alpha + ct[i];
+
+        if i <= qap.num_inputs {
+            assert!(kp.vk.ic_query[i] == combined);
+        } else {
+            assert!(kp.pk.l_query[i - qap.num_inputs - 1] == combined * delta_inverse);
+        }
+    }
+
+    let z = powers[qap.d] - Fr::one();
+    assert_eq!(kp.pk.h_query.len(), qap.d - 1);
+    for i in 0..(qap.d - 1) {
+        assert!(kp.pk.h_query[i] == G1::one() * (powers[i] * z * delta_inverse));
+    }
+}
@@ -0,0 +1,315 @@
+//! The zk-SNARK keypair the ceremony produces, with the same contents
+//! as libsnark's `r1cs_ppzksnark_keypair`, but in a documented format
+//! that doesn't depend on libsnark.
+//!
+//! The proving and verifying keys are stored in separate files, laid
+//! out as follows. All integers are little endian.
+//!
+//! ```text
+//! pk:  magic [u8; 8] = "mpcpkey\0" | version u32 | ProvingKey (bincode)
+//! vk:  magic [u8; 8] = "mpcvkey\0" | version u32 | VerifyingKey (bincode)
+//! ```
+//!
+//! The queries of the proving key are sparse, like libsnark's: only
+//! their nonzero entries are stored, along with their index.
+
+use bn::*;
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::fs::File;
+use std::fmt;
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::{encode_into, decode_from, EncodingError, DecodingError};
+use super::digest::Digest256;
+
+#[cfg(feature = "snark")]
+use snark::{self, CS};
+
+pub const PK_MAGIC: [u8; 8] = [0x6d, 0x70, 0x63, 0x70, 0x6b, 0x65, 0x79, 0x00];
+pub const VK_MAGIC: [u8; 8] = [0x6d, 0x70, 0x63, 0x76, 0x6b, 0x65, 0x79, 0x00];
+pub const KEY_VERSION: u32 = 1;
+
+#[derive(Clone, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct ProvingKey {
+    /// Number of entries in each of the A, B and C queries, counting
+    /// the ones which are zero.
+    pub domain_size: u64,
+    /// Index, then the knowledge commitment.
+    pub a_query: Vec<(u64, G1, G1)>,
+    pub b_query: Vec<(u64, G2, G1)>,
+    pub c_query: Vec<(u64, G1, G1)>,
+    pub k_query: Vec<G1>,
+    pub h_query: Vec<G1>
+}
+
+#[derive(Clone, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct VerifyingKey {
+    pub alpha_a_g2: G2,
+    pub alpha_b_g1: G1,
+    pub alpha_c_g2: G2,
+    pub gamma_g2: G2,
+    pub gamma_beta_g1: G1,
+    pub gamma_beta_g2: G2,
+    pub rc_z_g2: G2,
+    /// The base of the input consistency query, followed by one element
+    /// per input.
+    pub ic_query: Vec<G1>
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Keypair {
+    pub pk: ProvingKey,
+    pub vk: VerifyingKey
+}
+
+impl Keypair {
+    /// Constructs the keypair from the final contents of the stages,
+    /// exactly as libsnark would from the same elements.
+    pub fn new(
+        num_inputs: usize,
+        pk_a: &[G1],
+        pk_a_prime: &[G1],
+        pk_b: &[G2],
+        pk_b_prime: &[G1],
+        pk_c: &[G1],
+        pk_c_prime: &[G1],
+        pk_k: &[G1],
+        pk_h: &[G1],
+        vk_a: &G2,
+        vk_b: &G1,
+        vk_c: &G2,
+        vk_gamma: &G2,
+        vk_beta_gamma_1: &G1,
+        vk_beta_gamma_2: &G2,
+        vk_z: &G2
+    ) -> Keypair
+    {
+        assert_eq!(pk_a.len(), pk_a_prime.len());
+        assert_eq!(pk_a.len(), pk_b.len());
+        assert_eq!(pk_a.len(), pk_b_prime.len());
+        assert_eq!(pk_a.len(), pk_c.len());
+        assert_eq!(pk_a.len(), pk_c_prime.len());
+        assert!(pk_a.len() > num_inputs + 1);
+
+        let mut pk = ProvingKey {
+            domain_size: pk_a.len() as u64,
+            a_query: vec![],
+            b_query: vec![],
+            c_query: vec![],
+            k_query: pk_k.to_vec(),
+            h_query: pk_h.to_vec()
+        };
+
+        for i in 0..pk_a.len() {
+            // The inputs are accounted for by the verifying key instead.
+            if i > num_inputs && !(pk_a[i].is_zero() && pk_a_prime[i].is_zero()) {
+                pk.a_query.push((i as u64, pk_a[i], pk_a_prime[i]));
+            }
+
+            if !(pk_b[i].is_zero() && pk_b_prime[i].is_zero()) {
+                pk.b_query.push((i as u64, pk_b[i], pk_b_prime[i]));
+            }
+
+            if !(pk_c[i].is_zero() && pk_c_prime[i].is_zero()) {
+                pk.c_query.push((i as u64, pk_c[i], pk_c_prime[i]));
+            }
+        }
+
+        let vk = VerifyingKey {
+            alpha_a_g2: *vk_a,
+            alpha_b_g1: *vk_b,
+            alpha_c_g2: *vk_c,
+            gamma_g2: *vk_gamma,
+            gamma_beta_g1: *vk_beta_gamma_1,
+            gamma_beta_g2: *vk_beta_gamma_2,
+            rc_z_g2: *vk_z,
+            ic_query: pk_a[0..num_inputs+1].to_vec()
+        };
+
+        Keypair {
+            pk: pk,
+            vk: vk
+        }
+    }
+
+    /// Writes the proving and verifying keys, returning the digest of
+    /// each file.
+    pub fn write_to_disk(&self, pk_path: &str, vk_path: &str) -> Result<(Digest256, Digest256), KeyError> {
+        try!(write_key(pk_path, &PK_MAGIC, &self.pk));
+        try!(write_key(vk_path, &VK_MAGIC, &self.vk));
+
+        let pk_hash = Digest256::from_reader(&mut BufReader::new(try!(File::open(pk_path))));
+        let vk_hash = Digest256::from_reader(&mut BufReader::new(try!(File::open(vk_path))));
+
+        Ok((pk_hash, vk_hash))
+    }
+
+    pub fn read_from_disk(pk_path: &str, vk_path: &str) -> Result<Keypair, KeyError> {
+        Ok(Keypair {
+            pk: try!(read_key(&mut BufReader::new(try!(File::open(pk_path))), &PK_MAGIC)),
+            vk: try!(read_key(&mut BufReader::new(try!(File::open(vk_path))), &VK_MAGIC))
+        })
+    }
+
+    /// Converts the keypair to libsnark's, which can write it in the
+    /// legacy format.
+    #[cfg(feature = "snark")]
+    pub fn to_libsnark(&self, cs: &CS) -> snark::Keypair {
+        let n = self.pk.domain_size as usize;
+
+        let mut pk_a = vec![G1::zero(); n];
+        let mut pk_a_prime = vec![G1::zero(); n];
+        let mut pk_b = vec![G2::zero(); n];
+        let mut pk_b_prime = vec![G1::zero(); n];
+        let mut pk_c = vec![G1::zero(); n];
+        let mut pk_c_prime = vec![G1::zero(); n];
+
+        pk_a[0..self.vk.ic_query.len()].copy_from_slice(&self.vk.ic_query);
+
+        for &(i, a, a_prime) in &self.pk.a_query {
+            pk_a[i as usize] = a;
+            pk_a_prime[i as usize] = a_prime;
+        }
+
+        for &(i, b, b_prime) in &self.pk.b_query {
+            pk_b[i as usize] = b;
+            pk_b_prime[i as usize] = b_prime;
+        }
+
+        for &(i, c, c_prime) in &self.pk.c_query {
+            pk_c[i as usize] = c;
+            pk_c_prime[i as usize] = c_prime;
+        }
+
+        snark::Keypair::from(
+            cs,
+            &pk_a,
+            &pk_a_prime,
+            &pk_b,
+            &pk_b_prime,
+            &pk_c,
+            &pk_c_prime,
+            &self.pk.k_query,
+            &self.pk.h_query,
+            &self.vk.alpha_a_g2,
+            &self.vk.alpha_b_g1,
+            &self.vk.alpha_c_g2,
+            &self.vk.gamma_g2,
+            &self.vk.gamma_beta_g1,
+            &self.vk.gamma_beta_g2,
+            &self.vk.rc_z_g2
+        )
+    }
+}
+
+pub fn write_key<T: ::rustc_serialize::Encodable>(path: &str, magic: &[u8; 8], key: &T) -> Result<(), KeyError> {
+    let mut f = BufWriter::new(try!(File::create(path)));
+
+    try!(f.write_all(magic));
+    try!(f.write_u32::<LittleEndian>(KEY_VERSION));
+    try!(encode_into(key, &mut f, Infinite));
+    try!(f.flush());
+
+    Ok(())
+}
+
+pub fn read_key<T: ::rustc_serialize::Decodable, R: Read>(r: &mut R, magic: &[u8; 8]) -> Result<T, KeyError> {
+    let mut found = [0; 8];
+    try!(r.read_exact(&mut found));
+
+    if found != *magic {
+        return Err(KeyError::BadMagic);
+    }
+
+    let version = try!(r.read_u32::<LittleEndian>());
+    if version != KEY_VERSION {
+        return Err(KeyError::UnsupportedVersion(version));
+    }
+
+    Ok(try!(decode_from(r, Infinite)))
+}
+
+#[derive(Debug)]
+pub enum KeyError {
+    Io(io::Error),
+    Encoding(EncodingError),
+    Decoding(DecodingError),
+    BadMagic,
+    UnsupportedVersion(u32)
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KeyError::Io(ref e) => write!(f, "i/o error: {}", e),
+            KeyError::Encoding(ref e) => write!(f, "encoding error: {}", e),
+            KeyError::Decoding(ref e) => write!(f, "decoding error: {}", e),
+            KeyError::BadMagic => write!(f, "not a key, or the wrong kind of key"),
+            KeyError::UnsupportedVersion(v) => write!(f, "unsupported key version {}", v)
+        }
+    }
+}
+
+impl From<io::Error> for KeyError {
+    fn from(e: io::Error) -> KeyError {
+        KeyError::Io(e)
+    }
+}
+
+impl From<EncodingError> for KeyError {
+    fn from(e: EncodingError) -> KeyError {
+        KeyError::Encoding(e)
+    }
+}
+
+impl From<DecodingError> for KeyError {
+    fn from(e: DecodingError) -> KeyError {
+        KeyError::Decoding(e)
+    }
+}
+
+#[test]
+fn key_file_roundtrip() {
+    use std::env;
+
+    let rng = &mut ::rand::thread_rng();
+
+    let kp = Keypair::new(
+        1,
+        &[G1::random(rng), G1::random(rng), G1::zero(), G1::random(rng)],
+        &[G1::random(rng), G1::random(rng), G1::zero(), G1::random(rng)],
+        &[G2::random(rng), G2::zero(), G2::random(rng), G2::random(rng)],
+        &[G1::random(rng), G1::zero(), G1::random(rng), G1::random(rng)],
+        &[G1::zero(), G1::random(rng), G1::random(rng), G1::random(rng)],
+        &[G1::zero(), G1::random(rng), G1::random(rng), G1::random(rng)],
+        &[G1::random(rng), G1::random(rng)],
+        &[G1::random(rng), G1::random(rng), G1::random(rng)],
+        &G2::random(rng),
+        &G1::random(rng),
+        &G2::random(rng),
+        &G2::random(rng),
+        &G1::random(rng),
+        &G2::random(rng),
+        &G2::random(rng)
+    );
+
+    assert_eq!(kp.vk.ic_query.len(), 2);
+    assert_eq!(kp.pk.a_query.len(), 1);
+    assert_eq!(kp.pk.b_query.len(), 3);
+    assert_eq!(kp.pk.c_query.len(), 3);
+
+    let dir = env::temp_dir();
+    let pk_path = dir.join("mpc-test-pk");
+    let vk_path = dir.join("mpc-test-vk");
+    let (pk_path, vk_path) = (pk_path.to_str().unwrap(), vk_path.to_str().unwrap());
+
+    kp.write_to_disk(pk_path, vk_path).unwrap();
+    assert!(kp == Keypair::read_from_disk(pk_path, vk_path).unwrap());
+
+    // A proving key isn't mistaken for a verifying key.
+    let mut pk = BufReader::new(File::open(pk_path).unwrap());
+    assert!(match read_key::<VerifyingKey, _>(&mut pk, &VK_MAGIC) {
+        Err(KeyError::BadMagic) => true,
+        _ => false
+    });
+}
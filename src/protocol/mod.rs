@@ -28,25 +28,48 @@
 //!    by the coordinator, and the protocol proceeds as in the previous
 //!    two steps, except with `Stage2Contents` instead.
 //! 6. The coordinator writes the transcript to disk.
+//!
+//! These are the stages of the `Pghr13` scheme, which constructs
+//! libsnark's keys. The `Groth16` scheme shares stage 1 and replaces
+//! stages 2 and 3; see `groth16.rs`.
+//!
+//! Stage 1 can also be run on its own, for no constraint system in
+//! particular, and its result used to start stage 1 of any number of
+//! ceremonies; see `accumulator.rs`.
 
 use bn::*;
-
-#[cfg(feature = "snark")]
-use snark::*;
+use std::io::Read;
+use bincode::rustc_serialize::DecodingError;
 
 mod secrets;
 mod spair;
 mod nizk;
 mod multicore;
+mod stream;
+mod r1cs;
+mod qap;
+mod keypair;
+mod accumulator;
+mod beacon;
+mod scheme;
+mod signing;
+pub mod groth16;
 #[macro_use]
 mod digest;
 pub use self::secrets::*;
 pub use self::digest::*;
 use self::spair::*;
+pub use self::spair::{Batch, PairingCounts, pairing_counts};
 use self::multicore::*;
-
-#[cfg(feature = "snark")]
-mod qap;
+use self::stream::*;
+pub use self::multicore::set_threads;
+pub use self::r1cs::*;
+pub use self::keypair::*;
+pub use self::accumulator::*;
+pub use self::beacon::*;
+pub use self::scheme::*;
+pub use self::signing::*;
+pub use self::groth16::Groth16;
 
 /// The powers of tau.
 #[derive(Clone, RustcEncodable, RustcDecodable)]
@@ -56,26 +79,33 @@ pub struct Stage1Contents {
 }
 
 impl Stage1Contents {
-    #[cfg(feature = "snark")]
-    pub fn new(cs: &CS) -> Self {
+    pub fn new<S: Scheme>(qap: &QAP) -> Self {
+        Stage1Contents::with_powers(S::powers_of_tau(qap))
+    }
+
+    /// The first `n` powers of tau, before anyone has transformed them,
+    /// as for a universal ceremony (see `accumulator.rs`).
+    pub fn with_powers(n: usize) -> Self {
         Stage1Contents {
-            v1: (0..cs.d+1).map(|_| G1::one()).collect(),
-            v2: (0..cs.d+1).map(|_| G2::one()).collect()
+            v1: (0..n).map(|_| G1::one()).collect(),
+            v2: (0..n).map(|_| G2::one()).collect()
         }
     }
 
-    pub fn transform(&mut self, s: &PrivateKey) {
+    pub fn transform<K: SchemePrivateKey>(&mut self, s: &K) {
+        let tau = s.tau();
+
         parallel_two(&mut self.v1, &mut self.v2, |start, v1, v2| {
-            let mut c = s.tau.pow(Fr::from_str(&format!("{}", start)).unwrap());
+            let mut c = tau.pow(Fr::from_str(&format!("{}", start)).unwrap());
 
             for (g1, g2) in v1.iter_mut().zip(v2.iter_mut()) {
                 *g1 = *g1 * c;
                 g1.normalize();
                 *g2 = *g2 * c;
                 g2.normalize();
-                c = c * s.tau;
+                c = c * tau;
             }
-        }, ::THREADS);
+        }, threads());
     }
 
     pub fn is_well_formed(&self, prev: &Self) -> bool {
@@ -91,7 +121,7 @@ impl Stage1Contents {
         !prev.v2[1].is_zero()
     }
 
-    pub fn verify_transform(&self, prev: &Self, p: &PublicKey) -> bool {
+    pub fn verify_transform<K: SchemePublicKey>(&self, prev: &Self, p: &K) -> bool {
         self.is_well_formed(prev) &&
         same_power(
             &Spair::new(prev.v1[1], self.v1[1]).unwrap(),
@@ -100,6 +130,89 @@ impl Stage1Contents {
         checkseq(&self.v1, &Spair::new(self.v2[0], self.v2[1]).unwrap()) &&
         checkseq(&self.v2, &Spair::new(self.v1[0], self.v1[1]).unwrap())
     }
+
+    /// Like `verify_transform`, but reads the encoded previous and new
+    /// contents from `prev` and `next` in windows of `window` elements.
+    pub fn verify_transform_streaming<K: SchemePublicKey, P: Read, N: Read>(
+        prev: &mut P,
+        next: &mut N,
+        p: &K,
+        window: usize
+    ) -> Result<bool, DecodingError>
+    {
+        let mut batch = Batch::new();
+
+        Ok(try!(Self::verify_transform_batched(prev, next, p, window, &mut batch)) && batch.verify())
+    }
+
+    /// Like `verify_transform_streaming`, but leaves the pairing checks
+    /// to `batch` so that they can be verified along with others.
+    pub fn verify_transform_batched<K: SchemePublicKey, P: Read, N: Read>(
+        prev: &mut P,
+        next: &mut N,
+        p: &K,
+        window: usize,
+        batch: &mut Batch
+    ) -> Result<bool, DecodingError>
+    {
+        let window = ::std::cmp::max(window, 2);
+
+        // Besides the sequences themselves, only their first two
+        // elements are needed.
+        let mut prev_v1 = [G1::zero(); 2];
+        let mut next_v1 = [G1::zero(); 2];
+        let mut seq_v1 = SeqCombination::new();
+
+        if !try!(stream_pair(prev, next, window, |start, a: &[G1], b: &[G1]| {
+            if start == 0 && a.len() >= 2 {
+                prev_v1.copy_from_slice(&a[0..2]);
+                next_v1.copy_from_slice(&b[0..2]);
+            }
+
+            seq_v1.add(b);
+        })) {
+            return Ok(false);
+        }
+
+        let mut prev_v2 = [G2::zero(); 2];
+        let mut next_v2 = [G2::zero(); 2];
+        let mut seq_v2 = SeqCombination::new();
+
+        if !try!(stream_pair(prev, next, window, |start, a: &[G2], b: &[G2]| {
+            if start == 0 && a.len() >= 2 {
+                prev_v2.copy_from_slice(&a[0..2]);
+                next_v2.copy_from_slice(&b[0..2]);
+            }
+
+            seq_v2.add(b);
+        })) {
+            return Ok(false);
+        }
+
+        let well_formed =
+            next_v1[0] == G1::one() &&
+            next_v2[0] == G2::one() &&
+            prev_v1[0] == G1::one() &&
+            prev_v2[0] == G2::one() &&
+            !next_v1[1].is_zero() &&
+            !next_v2[1].is_zero() &&
+            !prev_v1[1].is_zero() &&
+            !prev_v2[1].is_zero();
+
+        if !well_formed {
+            return Ok(false);
+        }
+
+        batch.same_power(
+            &Spair::new(prev_v1[1], next_v1[1]).unwrap(),
+            &p.tau_g2()
+        );
+
+        Ok(
+            seq_v1.check(&Spair::new(next_v2[0], next_v2[1]).unwrap(), batch) &&
+            seq_v2.check(&Spair::new(next_v1[0], next_v1[1]).unwrap(), batch)
+        )
+    }
 }
 
 /// Random coefficients, part 1.
@@ -119,10 +232,9 @@ pub struct Stage2Contents {
 }
 
 impl Stage2Contents {
-    #[cfg(feature = "snark")]
-    pub fn new(cs: &CS, stage1: &Stage1Contents) -> Self {
+    pub fn new(qap: &QAP, stage1: &Stage1Contents) -> Self {
         // evaluate QAP for the next round
-        let (at, bt1, bt2, ct) = qap::evaluate(&stage1.v1, &stage1.v2, cs);
+        let (at, bt1, bt2, ct) = qap::evaluate(&stage1.v1, &stage1.v2, qap);
 
         Stage2Contents {
             vk_a: G2::one(),
@@ -139,20 +251,6 @@ impl Stage2Contents {
         }
     }
 
-    pub fn transform(&mut self, s: &PrivateKey) {
-        self.vk_a = self.vk_a * s.alpha_a;
-        self.vk_b = self.vk_b * s.alpha_b;
-        self.vk_c = self.vk_c * s.alpha_c;
-        self.vk_z = self.vk_z * (s.rho_a * s.rho_b);
-        mul_all_by(&mut self.pk_a, s.rho_a);
-        mul_all_by(&mut self.pk_a_prime, s.rho_a * s.alpha_a);
-        mul_all_by(&mut self.pk_b, s.rho_b);
-        mul_all_by(&mut self.pk_b_temp, s.rho_b);
-        mul_all_by(&mut self.pk_b_prime, s.rho_b * s.alpha_b);
-        mul_all_by(&mut self.pk_c, s.rho_a * s.rho_b);
-        mul_all_by(&mut self.pk_c_prime, s.rho_a * s.rho_b * s.alpha_c);
-    }
-
     pub fn is_well_formed(&self, prev: &Self) -> bool {
         !prev.vk_a.is_zero() &&
         !prev.vk_b.is_zero() &&
@@ -171,8 +269,27 @@ impl Stage2Contents {
         self.pk_c.len() == prev.pk_c.len() &&
         self.pk_c_prime.len() == prev.pk_c_prime.len()
     }
+}
+
+impl Stage for Stage2Contents {
+    type PrivateKey = PrivateKey;
+    type PublicKey = PublicKey;
+
+    fn transform(&mut self, s: &PrivateKey) {
+        self.vk_a = self.vk_a * s.alpha_a;
+        self.vk_b = self.vk_b * s.alpha_b;
+        self.vk_c = self.vk_c * s.alpha_c;
+        self.vk_z = self.vk_z * (s.rho_a * s.rho_b);
+        mul_all_by(&mut self.pk_a, s.rho_a);
+        mul_all_by(&mut self.pk_a_prime, s.rho_a * s.alpha_a);
+        mul_all_by(&mut self.pk_b, s.rho_b);
+        mul_all_by(&mut self.pk_b_temp, s.rho_b);
+        mul_all_by(&mut self.pk_b_prime, s.rho_b * s.alpha_b);
+        mul_all_by(&mut self.pk_c, s.rho_a * s.rho_b);
+        mul_all_by(&mut self.pk_c_prime, s.rho_a * s.rho_b * s.alpha_c);
+    }
 
-    pub fn verify_transform(&self, prev: &Self, p: &PublicKey) -> bool {
+    fn verify_transform(&self, prev: &Self, p: &PublicKey) -> bool {
         self.is_well_formed(prev) &&
         // Check parts of the verification key
         same_power(
@@ -228,6 +345,59 @@ impl Stage2Contents {
             &p.alpha_c_rho_a_rho_b_g2()
         )
     }
+
+    fn verify_transform_batched<P: Read, N: Read>(
+        prev: &mut P,
+        next: &mut N,
+        p: &PublicKey,
+        window: usize,
+        batch: &mut Batch
+    ) -> Result<bool, DecodingError>
+    {
+        let prev_vk_a: G2 = try!(read_element(prev));
+        let prev_vk_b: G1 = try!(read_element(prev));
+        let prev_vk_c: G2 = try!(read_element(prev));
+        let prev_vk_z: G2 = try!(read_element(prev));
+        let vk_a: G2 = try!(read_element(next));
+        let vk_b: G1 = try!(read_element(next));
+        let vk_c: G2 = try!(read_element(next));
+        let vk_z: G2 = try!(read_element(next));
+
+        if prev_vk_a.is_zero() || prev_vk_b.is_zero() || prev_vk_c.is_zero() || prev_vk_z.is_zero() ||
+           vk_a.is_zero() || vk_b.is_zero() || vk_c.is_zero() || vk_z.is_zero()
+        {
+            return Ok(false);
+        }
+
+        // Check parts of the verification key
+        batch.same_power(
+            &Spair::new(prev_vk_a, vk_a).unwrap(),
+            &p.alpha_a_g1()
+        );
+        batch.same_power(
+            &Spair::new(prev_vk_b, vk_b).unwrap(),
+            &p.alpha_b_g2()
+        );
+        batch.same_power(
+            &Spair::new(prev_vk_c, vk_c).unwrap(),
+            &p.alpha_c_g1()
+        );
+        batch.same_power(
+            &Spair::new(prev_vk_z, vk_z).unwrap(),
+            &p.rho_a_rho_b_g1()
+        );
+
+        Ok(
+            // Check parts of the proving key, in the order they're encoded
+            try!(checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.rho_a_g2(), batch)) &&
+            try!(checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.alpha_a_rho_a_g2(), batch)) &&
+            try!(checkvec_streaming::<G2, _, _, _>(prev, next, window, &p.rho_b_g1(), batch)) &&
+            try!(checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.rho_b_g2(), batch)) &&
+            try!(checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.alpha_b_rho_b_g2(), batch)) &&
+            try!(checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.rho_a_rho_b_g2(), batch)) &&
+            try!(checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.alpha_c_rho_a_rho_b_g2(), batch))
+        )
+    }
 }
 
 /// Random coefficients, part 2.
@@ -240,22 +410,21 @@ pub struct Stage3Contents {
 }
 
 impl Stage3Contents {
-    #[cfg(feature = "snark")]
-    pub fn new(cs: &CS, stage2: &Stage2Contents) -> Self {
-        assert_eq!(stage2.pk_a.len(), cs.num_vars + 1);
-        assert_eq!(stage2.pk_b_temp.len(), cs.num_vars + 1);
-        assert_eq!(stage2.pk_c.len(), cs.num_vars + 1);
+    pub fn new(qap: &QAP, stage2: &Stage2Contents) -> Self {
+        assert_eq!(stage2.pk_a.len(), qap.num_vars + 1);
+        assert_eq!(stage2.pk_b_temp.len(), qap.num_vars + 1);
+        assert_eq!(stage2.pk_c.len(), qap.num_vars + 1);
 
-        let mut pk_k = Vec::with_capacity(cs.num_vars + 3);
+        let mut pk_k = Vec::with_capacity(qap.num_vars + 3);
 
         // Perform Z extention as libsnark does.
         pk_k.extend_from_slice(&stage2.pk_a);
-        pk_k.push(stage2.pk_b_temp[cs.num_vars]);
-        pk_k.push(stage2.pk_c[cs.num_vars]);
+        pk_k.push(stage2.pk_b_temp[qap.num_vars]);
+        pk_k.push(stage2.pk_c[qap.num_vars]);
 
         // Add B and C
-        add_all_to(&mut pk_k[0..cs.num_vars], &stage2.pk_b_temp[0..cs.num_vars]);
-        add_all_to(&mut pk_k[0..cs.num_vars], &stage2.pk_c[0..cs.num_vars]);
+        add_all_to(&mut pk_k[0..qap.num_vars], &stage2.pk_b_temp[0..qap.num_vars]);
+        add_all_to(&mut pk_k[0..qap.num_vars], &stage2.pk_c[0..qap.num_vars]);
 
         Stage3Contents {
             vk_gamma: G2::one(),
@@ -265,14 +434,6 @@ impl Stage3Contents {
         }
     }
 
-    pub fn transform(&mut self, s: &PrivateKey) {
-        let betagamma = s.beta * s.gamma;
-        self.vk_gamma = self.vk_gamma * s.gamma;
-        self.vk_beta_gamma_one = self.vk_beta_gamma_one * betagamma;
-        self.vk_beta_gamma_two = self.vk_beta_gamma_two * betagamma;
-        mul_all_by(&mut self.pk_k, s.beta);
-    }
-
     pub fn is_well_formed(&self, prev: &Self) -> bool {
         !prev.vk_gamma.is_zero() &&
         !prev.vk_beta_gamma_one.is_zero() &&
@@ -282,8 +443,21 @@ impl Stage3Contents {
         !self.vk_beta_gamma_two.is_zero() &&
         self.pk_k.len() == prev.pk_k.len()
     }
+}
 
-    pub fn verify_transform(&self, prev: &Self, p: &PublicKey) -> bool {
+impl Stage for Stage3Contents {
+    type PrivateKey = PrivateKey;
+    type PublicKey = PublicKey;
+
+    fn transform(&mut self, s: &PrivateKey) {
+        let betagamma = s.beta * s.gamma;
+        self.vk_gamma = self.vk_gamma * s.gamma;
+        self.vk_beta_gamma_one = self.vk_beta_gamma_one * betagamma;
+        self.vk_beta_gamma_two = self.vk_beta_gamma_two * betagamma;
+        mul_all_by(&mut self.pk_k, s.beta);
+    }
+
+    fn verify_transform(&self, prev: &Self, p: &PublicKey) -> bool {
         self.is_well_formed(prev) &&
         same_power(
             &Spair::new(prev.vk_gamma, self.vk_gamma).unwrap(),
@@ -303,17 +477,82 @@ impl Stage3Contents {
             &p.beta_g2()
         )
     }
+
+    fn verify_transform_batched<P: Read, N: Read>(
+        prev: &mut P,
+        next: &mut N,
+        p: &PublicKey,
+        window: usize,
+        batch: &mut Batch
+    ) -> Result<bool, DecodingError>
+    {
+        let prev_vk_gamma: G2 = try!(read_element(prev));
+        let prev_vk_beta_gamma_one: G1 = try!(read_element(prev));
+        let prev_vk_beta_gamma_two: G2 = try!(read_element(prev));
+        let vk_gamma: G2 = try!(read_element(next));
+        let vk_beta_gamma_one: G1 = try!(read_element(next));
+        let vk_beta_gamma_two: G2 = try!(read_element(next));
+
+        if prev_vk_gamma.is_zero() || prev_vk_beta_gamma_one.is_zero() || prev_vk_beta_gamma_two.is_zero() ||
+           vk_gamma.is_zero() || vk_beta_gamma_one.is_zero() || vk_beta_gamma_two.is_zero()
+        {
+            return Ok(false);
+        }
+
+        batch.same_power(
+            &Spair::new(prev_vk_gamma, vk_gamma).unwrap(),
+            &p.gamma_g1()
+        );
+        batch.same_power(
+            &Spair::new(prev_vk_beta_gamma_one, vk_beta_gamma_one).unwrap(),
+            &p.beta_gamma_g2()
+        );
+        batch.same_power(
+            &Spair::new(prev_vk_beta_gamma_two, vk_beta_gamma_two).unwrap(),
+            &Spair::new(prev_vk_beta_gamma_one, vk_beta_gamma_one).unwrap()
+        );
+
+        checkvec_streaming::<G1, _, _, _>(prev, next, window, &p.beta_g2(), batch)
+    }
+}
+
+/// libsnark's `r1cs_ppzksnark`, which the stages above construct the
+/// keys of.
+#[derive(RustcEncodable, RustcDecodable)]
+pub struct Pghr13;
+
+impl Scheme for Pghr13 {
+    type PrivateKey = PrivateKey;
+    type PublicKey = PublicKey;
+    type PublicKeyNizks = PublicKeyNizks;
+    type Stage2 = Stage2Contents;
+    type Stage3 = Stage3Contents;
+
+    fn proof_system() -> ProofSystem {
+        ProofSystem::Pghr13
+    }
+
+    fn powers_of_tau(qap: &QAP) -> usize {
+        qap.d + 1
+    }
+
+    fn stage2(qap: &QAP, stage1: &Stage1Contents) -> Stage2Contents {
+        Stage2Contents::new(qap, stage1)
+    }
+
+    fn stage3(qap: &QAP, _: &Stage1Contents, stage2: &Stage2Contents) -> Stage3Contents {
+        Stage3Contents::new(qap, stage2)
+    }
 }
 
-#[cfg(feature = "snark")]
 pub fn keypair(
-    cs: &CS,
+    qap: &QAP,
     stage1: &Stage1Contents,
     stage2: &Stage2Contents,
     stage3: &Stage3Contents
 ) -> Keypair {
-    Keypair::from(
-        cs,
+    Keypair::new(
+        qap.num_inputs,
         &stage2.pk_a,
         &stage2.pk_a_prime,
         &stage2.pk_b,
@@ -334,39 +573,62 @@ pub fn keypair(
 
 #[test]
 fn compare_to_libsnark_generate() {
+    use snark::CS;
+    use bincode::SizeLimit::Infinite;
+    use bincode::rustc_serialize::encode;
+
     let rng = &mut ::rand::thread_rng();
 
     let privkeys: Vec<_> = (0..3).map(|_| PrivateKey::new(rng)).collect();
     let pubkeys: Vec<_> = privkeys.iter().map(|p| p.pubkey(rng)).collect();
 
     let cs = CS::dummy();
+    let qap = QAP::new(R1CS::from_libsnark(&cs)).unwrap();
 
     // Stage 1
-    let mut stage1 = Stage1Contents::new(&cs);
+    let mut stage1 = Stage1Contents::new::<Pghr13>(&qap);
 
     for (private, public) in privkeys.iter().zip(pubkeys.iter()) {
         let prev = stage1.clone();
         stage1.transform(private);
         assert!(stage1.verify_transform(&prev, public));
+        assert!(Stage1Contents::verify_transform_streaming(
+            &mut &encode(&prev, Infinite).unwrap()[..],
+            &mut &encode(&stage1, Infinite).unwrap()[..],
+            public,
+            3
+        ).unwrap());
     }
 
     // Stage 2
-    let mut stage2 = Stage2Contents::new(&cs, &stage1);
+    let mut stage2 = Stage2Contents::new(&qap, &stage1);
     for (private, public) in privkeys.iter().zip(pubkeys.iter()) {
         let prev = stage2.clone();
         stage2.transform(private);
         assert!(stage2.verify_transform(&prev, public));
+        assert!(Stage2Contents::verify_transform_streaming(
+            &mut &encode(&prev, Infinite).unwrap()[..],
+            &mut &encode(&stage2, Infinite).unwrap()[..],
+            public,
+            3
+        ).unwrap());
     }
 
     // Stage 3
-    let mut stage3 = Stage3Contents::new(&cs, &stage2);
+    let mut stage3 = Stage3Contents::new(&qap, &stage2);
     for (private, public) in privkeys.iter().zip(pubkeys.iter()) {
         let prev = stage3.clone();
         stage3.transform(private);
         assert!(stage3.verify_transform(&prev, public));
+        assert!(Stage3Contents::verify_transform_streaming(
+            &mut &encode(&prev, Infinite).unwrap()[..],
+            &mut &encode(&stage3, Infinite).unwrap()[..],
+            public,
+            3
+        ).unwrap());
     }
 
-    let kp = keypair(&cs, &stage1, &stage2, &stage3);
+    let kp = keypair(&qap, &stage1, &stage2, &stage3);
 
     // Compare to libsnark
 
@@ -375,5 +637,5 @@ fn compare_to_libsnark_generate() {
         acc.multiply(private);
     }
 
-    assert!(kp == acc.libsnark_keypair(&cs));
+    assert!(kp.to_libsnark(&cs) == acc.libsnark_keypair(&cs));
 }
@@ -1,5 +1,26 @@
 use bn::*;
 use crossbeam;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+/// Used until `set_threads` is called.
+const DEFAULT_THREADS: usize = 8;
+
+static THREADS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Sets the number of threads used by the protocol.
+pub fn set_threads(threads: usize) {
+    assert!(threads > 0);
+
+    THREADS.store(threads, Ordering::SeqCst);
+}
+
+/// The number of threads used by the protocol.
+pub fn threads() -> usize {
+    match THREADS.load(Ordering::SeqCst) {
+        0 => DEFAULT_THREADS,
+        n => n
+    }
+}
 
 fn calculate_window_size<T>(v: &[T], threads: usize) -> usize
 {
@@ -10,15 +31,22 @@ fn calculate_window_size<T>(v: &[T], threads: usize) -> usize
     }
 }
 
-pub fn parallel_all<
+/// Applies `f` to corresponding windows of `v1` and `v2` in parallel,
+/// returning the results in order.
+pub fn parallel_map<
     G: Group,
-    F: Fn(&[G], &[G]) -> bool + Sync
+    T: Send,
+    F: Fn(&[G], &[G]) -> T + Sync
 >
-(v1: &[G], v2: &[G], f: F, threads: usize) -> bool
+(v1: &[G], v2: &[G], f: F, threads: usize) -> Vec<T>
 {
     assert_eq!(v1.len(), v2.len());
     let f = &f;
 
+    if v1.is_empty() {
+        return vec![];
+    }
+
     crossbeam::scope(|scope| {
         let window_size = calculate_window_size(v1, threads);
         let mut tasks = vec![];
@@ -28,7 +56,7 @@ pub fn parallel_all<
             }));
         }
 
-        tasks.into_iter().map(|t| t.join()).all(|r| r)
+        tasks.into_iter().map(|t| t.join()).collect()
     })
 }
 
@@ -84,7 +112,7 @@ pub fn mul_all_by<G: Group>(v: &mut [G], c: Fr) {
             *i = *i * c;
             i.normalize();
         }
-    }, ::THREADS);
+    }, threads());
 }
 
 pub fn add_all_to<G: Group>(v: &mut [G], other: &[G]) {
@@ -95,5 +123,5 @@ pub fn add_all_to<G: Group>(v: &mut [G], other: &[G]) {
             *a = *a + other[i];
             i += 1;
         }
-    }, ::THREADS);
+    }, threads());
 }
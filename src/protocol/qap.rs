@@ -1,40 +1,68 @@
 use bn::*;
-use snark::*;
 use super::multicore::*;
+use super::r1cs::*;
 
 /// Evaluates the QAP A, B and C polynomials at tau given the powers of tau.
 /// Converts the powers of tau in G1 and G2 into the lagrange basis with an FFT
 /// Extends with Z(tau) as (effectively) done in libsnark.
-pub fn evaluate(g1_powers: &[G1], g2_powers: &[G2], cs: &CS) -> (Vec<G1>, Vec<G1>, Vec<G2>, Vec<G1>)
+pub fn evaluate(g1_powers: &[G1], g2_powers: &[G2], qap: &QAP) -> (Vec<G1>, Vec<G1>, Vec<G2>, Vec<G1>)
 {
-    assert_eq!(g1_powers.len(), cs.d+1);
-    assert_eq!(g2_powers.len(), cs.d+1);
+    assert_eq!(g1_powers.len(), qap.d+1);
+    assert_eq!(g2_powers.len(), qap.d+1);
 
-    let lc1 = lagrange_coeffs(&g1_powers[0..cs.d], cs.omega);
-    let lc2 = lagrange_coeffs(&g2_powers[0..cs.d], cs.omega);
+    let lc1 = lagrange_coeffs(&g1_powers[0..qap.d], qap.omega);
+    let lc2 = lagrange_coeffs(&g2_powers[0..qap.d], qap.omega);
 
-    let (mut at, mut bt1, mut bt2, mut ct) = evaluate_qap_polynomials(&lc1, &lc2, cs);
+    let (mut at, mut bt1, mut bt2, mut ct) = evaluate_qap_polynomials(&lc1, &lc2, qap);
 
     // Extention of Z(tau)
-    at.push(g1_powers[cs.d] - G1::one());
-    bt1.push(g1_powers[cs.d] - G1::one());
-    bt2.push(g2_powers[cs.d] - G2::one());
-    ct.push(g1_powers[cs.d] - G1::one());
+    at.push(g1_powers[qap.d] - G1::one());
+    bt1.push(g1_powers[qap.d] - G1::one());
+    bt2.push(g2_powers[qap.d] - G2::one());
+    ct.push(g1_powers[qap.d] - G1::one());
 
     (at, bt1, bt2, ct)
 }
 
-fn evaluate_qap_polynomials(lc1: &[G1], lc2: &[G2], cs: &CS) -> (Vec<G1>, Vec<G1>, Vec<G2>, Vec<G1>)
+/// The A, B and C polynomials of each variable are sums of the lagrange
+/// polynomials of the constraints the variable appears in, weighted by
+/// its coefficients there, so their evaluations are the same sums of
+/// the lagrange coefficients.
+fn evaluate_qap_polynomials(lc1: &[G1], lc2: &[G2], qap: &QAP) -> (Vec<G1>, Vec<G1>, Vec<G2>, Vec<G1>)
 {
-    assert_eq!(lc1.len(), cs.d);
-    assert_eq!(lc2.len(), cs.d);
+    assert_eq!(lc1.len(), qap.d);
+    assert_eq!(lc2.len(), qap.d);
 
-    let mut at = (0..cs.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
-    let mut bt1 = (0..cs.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
-    let mut bt2 = (0..cs.num_vars).map(|_| G2::zero()).collect::<Vec<_>>();
-    let mut ct = (0..cs.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
+    let mut at = (0..qap.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
+    let mut bt1 = (0..qap.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
+    let mut bt2 = (0..qap.num_vars).map(|_| G2::zero()).collect::<Vec<_>>();
+    let mut ct = (0..qap.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
 
-    cs.eval(&lc1, &lc2, &mut at, &mut bt1, &mut bt2, &mut ct);
+    let r1cs = qap.r1cs();
+
+    for (i, constraint) in r1cs.constraints.iter().enumerate() {
+        let (a, b) = qap.ab(constraint);
+
+        for &(var, coeff) in a {
+            at[var] = at[var] + lc1[i] * coeff;
+        }
+
+        for &(var, coeff) in b {
+            bt1[var] = bt1[var] + lc1[i] * coeff;
+            bt2[var] = bt2[var] + lc2[i] * coeff;
+        }
+
+        for &(var, coeff) in &constraint.c {
+            ct[var] = ct[var] + lc1[i] * coeff;
+        }
+    }
+
+    // The constraints `input * 0 = 0`, which follow the others.
+    for i in 0..(r1cs.num_inputs + 1) {
+        let j = r1cs.constraints.len() + i;
+
+        at[i] = at[i] + lc1[j];
+    }
 
     (at, bt1, bt2, ct)
 }
@@ -45,7 +73,7 @@ fn lagrange_coeffs<G: Group>(v: &[G], omega: Fr) -> Vec<G>
     assert_eq!((v.len() / 2) * 2, v.len());
 
     let overd = Fr::from_str(&format!("{}", v.len())).unwrap().inverse().unwrap();
-    let mut tmp = fft(v, omega, ::THREADS);
+    let mut tmp = fft(v, omega, threads());
     tmp.reverse(); // coefficients are in reverse
 
     mul_all_by(&mut tmp, overd);
@@ -121,7 +149,10 @@ fn fft<G: Group>(v: &[G], omega: Fr, threads: usize) -> Vec<G>
 }
 
 #[test]
+#[cfg(feature = "snark")]
 fn compare_to_libsnark() {
+    use snark::*;
+
     pub struct TauPowers {
         acc: Fr,
         tau: Fr
@@ -158,6 +189,13 @@ fn compare_to_libsnark() {
 
     // Get the QAP degree and omega (for FFT evaluation)
     let cs = CS::dummy();
+    let qap = QAP::new(R1CS::from_libsnark(&cs)).unwrap();
+
+    // The native reduction must agree with libsnark's
+    assert_eq!(qap.d, cs.d);
+    assert_eq!(qap.num_vars, cs.num_vars);
+    assert_eq!(qap.num_inputs, cs.num_inputs);
+    assert!(qap.omega == cs.omega);
 
     // Sample a random tau
     let tau = Fr::random(rng);
@@ -188,11 +226,26 @@ fn compare_to_libsnark() {
     // Wrong tau
     assert!(!cs.test_compare_tau(&lc1, &lc2, &Fr::random(rng)));
 
-    let (at, bt1, bt2, ct) = evaluate_qap_polynomials(&lc1, &lc2, &cs);
+    let (at, bt1, bt2, ct) = evaluate_qap_polynomials(&lc1, &lc2, &qap);
 
     // Compare evaluation with libsnark
     assert!(cs.test_eval(&tau, &at, &bt1, &bt2, &ct));
 
+    {
+        // Compare with libsnark's own evaluation from the lagrange coeffs
+        let mut at2 = (0..cs.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
+        let mut bt12 = (0..cs.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
+        let mut bt22 = (0..cs.num_vars).map(|_| G2::zero()).collect::<Vec<_>>();
+        let mut ct2 = (0..cs.num_vars).map(|_| G1::zero()).collect::<Vec<_>>();
+
+        cs.eval(&lc1, &lc2, &mut at2, &mut bt12, &mut bt22, &mut ct2);
+
+        assert!(at == at2);
+        assert!(bt1 == bt12);
+        assert!(bt2 == bt22);
+        assert!(ct == ct2);
+    }
+
     // Wrong tau
     assert!(!cs.test_eval(&Fr::random(rng), &at, &bt1, &bt2, &ct));
 
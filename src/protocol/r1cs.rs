@@ -0,0 +1,490 @@
+//! A native representation of rank-1 constraint systems and their
+//! reduction to a QAP, following libsnark's `r1cs_to_qap` so that the
+//! parameters are identical to what libsnark would generate.
+//!
+//! Constraint systems are stored in the following format, which doesn't
+//! depend on libsnark's serialization. All integers are little endian.
+//!
+//! ```text
+//! header:       magic [u8; 8] | version u32 | num_inputs u64 | num_aux u64
+//!               | num_constraints u64
+//! constraints:  num_constraints * (a | b | c)
+//! a, b and c:   num_terms u64 | num_terms * (index u64 | coeff)
+//! ```
+//!
+//! Each `coeff` is an `Fr`, bincode encoded. There is nothing after the
+//! last constraint, so every constraint system has exactly one encoding,
+//! and the hash of the file is the hash of the constraint system.
+
+use bn::*;
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::fs::File;
+use std::fmt;
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+use rand::SeedableRng;
+use rand::chacha::ChaChaRng;
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::{encode_into, decode_from, EncodingError, DecodingError};
+use super::digest::{Digest256, Digest256Writer};
+
+#[cfg(feature = "snark")]
+use snark::{self, CS};
+
+pub const R1CS_MAGIC: [u8; 8] = [0x6d, 0x70, 0x63, 0x72, 0x31, 0x63, 0x73, 0x00];
+pub const R1CS_VERSION: u32 = 1;
+
+/// libsnark's 2^28th root of unity in Fr, which generates every
+/// radix-2 evaluation domain.
+const ROOT_OF_UNITY: &'static str = "19103219067921713944291392827692070036145651957329286315305642004821462161904";
+
+/// Fr has 2^28th roots of unity, so no evaluation domain is larger.
+const TWO_ADICITY: usize = 28;
+
+/// Shape of the dummy constraint system, which is that of libsnark's
+/// example one.
+const DUMMY_CONSTRAINTS: usize = 250;
+const DUMMY_INPUTS: usize = 4;
+
+/// A linear combination of variables, as pairs of variable index and
+/// coefficient. Variable 0 is the constant one, followed by the inputs
+/// and then the auxiliary variables.
+pub type LinearCombination = Vec<(usize, Fr)>;
+
+/// A constraint `a * b = c`.
+#[derive(Clone, PartialEq)]
+pub struct Constraint {
+    pub a: LinearCombination,
+    pub b: LinearCombination,
+    pub c: LinearCombination
+}
+
+#[derive(Clone, PartialEq)]
+pub struct R1CS {
+    pub num_inputs: usize,
+    pub num_aux: usize,
+    pub constraints: Vec<Constraint>
+}
+
+impl R1CS {
+    #[cfg(feature = "snark")]
+    pub fn from_libsnark(cs: &CS) -> R1CS {
+        let num_inputs = cs.num_inputs;
+
+        R1CS {
+            num_inputs: num_inputs,
+            num_aux: cs.num_vars - 1 - num_inputs,
+            constraints: cs.constraints().into_iter().map(|c| {
+                Constraint {
+                    a: c.a,
+                    b: c.b,
+                    c: c.c
+                }
+            }).collect()
+        }
+    }
+
+    /// Converts the constraint system for libsnark, so that it can
+    /// construct the keypair.
+    #[cfg(feature = "snark")]
+    pub fn to_libsnark(&self) -> CS {
+        let constraints: Vec<_> = self.constraints.iter().map(|c| {
+            snark::Constraint {
+                a: c.a.clone(),
+                b: c.b.clone(),
+                c: c.c.clone()
+            }
+        }).collect();
+
+        CS::from_constraints(self.num_inputs, self.num_aux, &constraints)
+    }
+
+    /// A constraint system for rehearsals, with random coefficients.
+    /// They are sampled from a fixed seed, so that everyone gets the same
+    /// one, which is bound by its hash like any other. Each constraint
+    /// multiplies two earlier variables into a new one, so it's
+    /// satisfiable.
+    pub fn dummy() -> R1CS {
+        let seed: Vec<u32> = b"dummy".iter().map(|&b| b as u32).collect();
+        let rng = &mut ChaChaRng::from_seed(&seed);
+
+        let constraints = (0..DUMMY_CONSTRAINTS).map(|i| {
+            // The inputs and the variables of earlier constraints.
+            let known = DUMMY_INPUTS + i;
+
+            Constraint {
+                a: vec![(1 + i % known, Fr::random(rng))],
+                b: vec![(1 + (7 * i + 3) % known, Fr::random(rng))],
+                c: vec![(known + 1, Fr::one())]
+            }
+        }).collect();
+
+        R1CS {
+            num_inputs: DUMMY_INPUTS,
+            num_aux: DUMMY_CONSTRAINTS,
+            constraints: constraints
+        }
+    }
+
+    pub fn from_file(path: &str) -> Result<R1CS, R1CSError> {
+        R1CS::read(&mut BufReader::new(try!(File::open(path))))
+    }
+
+    pub fn to_file(&self, path: &str) -> Result<(), R1CSError> {
+        let mut f = BufWriter::new(try!(File::create(path)));
+
+        try!(self.write(&mut f));
+        try!(f.flush());
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> Result<R1CS, R1CSError> {
+        let mut magic = [0; 8];
+        try!(r.read_exact(&mut magic));
+
+        if magic != R1CS_MAGIC {
+            return Err(R1CSError::BadMagic);
+        }
+
+        let version = try!(r.read_u32::<LittleEndian>());
+        if version != R1CS_VERSION {
+            return Err(R1CSError::UnsupportedVersion(version));
+        }
+
+        let num_inputs = try!(r.read_u64::<LittleEndian>()) as usize;
+        let num_aux = try!(r.read_u64::<LittleEndian>()) as usize;
+        let num_constraints = try!(r.read_u64::<LittleEndian>());
+
+        let num_variables = try!(num_inputs.checked_add(num_aux).ok_or(R1CSError::Corrupt("too many variables")));
+
+        // Lengths aren't trusted to preallocate with, since the file
+        // may be corrupt.
+        let mut constraints = vec![];
+
+        for _ in 0..num_constraints {
+            let a = try!(read_lc(r, num_variables));
+            let b = try!(read_lc(r, num_variables));
+            let c = try!(read_lc(r, num_variables));
+
+            constraints.push(Constraint {
+                a: a,
+                b: b,
+                c: c
+            });
+        }
+
+        if try!(r.read(&mut [0])) != 0 {
+            return Err(R1CSError::Corrupt("trailing data"));
+        }
+
+        Ok(R1CS {
+            num_inputs: num_inputs,
+            num_aux: num_aux,
+            constraints: constraints
+        })
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> Result<(), R1CSError> {
+        try!(w.write_all(&R1CS_MAGIC));
+        try!(w.write_u32::<LittleEndian>(R1CS_VERSION));
+        try!(w.write_u64::<LittleEndian>(self.num_inputs as u64));
+        try!(w.write_u64::<LittleEndian>(self.num_aux as u64));
+        try!(w.write_u64::<LittleEndian>(self.constraints.len() as u64));
+
+        for constraint in &self.constraints {
+            try!(write_lc(w, &constraint.a));
+            try!(write_lc(w, &constraint.b));
+            try!(write_lc(w, &constraint.c));
+        }
+
+        Ok(())
+    }
+
+    /// The hash of the encoding of the constraint system, which is the
+    /// hash of the file it is stored in.
+    pub fn hash(&self) -> Digest256 {
+        let mut h = Digest256Writer::new();
+        self.write(&mut h).unwrap();
+
+        h.finish()
+    }
+
+    /// Number of variables, not counting the constant one.
+    pub fn num_variables(&self) -> usize {
+        self.num_inputs + self.num_aux
+    }
+
+    /// Whether A and B should be swapped, which is when fewer variables
+    /// appear in A, since B is evaluated in G2 as well. libsnark swaps
+    /// them in exactly this case before the reduction.
+    pub fn should_swap_ab(&self) -> bool {
+        let mut touched_by_a = vec![false; self.num_variables() + 1];
+        let mut touched_by_b = vec![false; self.num_variables() + 1];
+
+        for constraint in &self.constraints {
+            for &(i, _) in &constraint.a {
+                touched_by_a[i] = true;
+            }
+
+            for &(i, _) in &constraint.b {
+                touched_by_b[i] = true;
+            }
+        }
+
+        let in_a = touched_by_a.iter().filter(|t| **t).count();
+        let in_b = touched_by_b.iter().filter(|t| **t).count();
+
+        in_b > in_a
+    }
+}
+
+fn read_lc<R: Read>(r: &mut R, num_variables: usize) -> Result<LinearCombination, R1CSError> {
+    let num_terms = try!(r.read_u64::<LittleEndian>());
+    let mut lc = vec![];
+
+    for _ in 0..num_terms {
+        let index = try!(r.read_u64::<LittleEndian>());
+
+        if index > num_variables as u64 {
+            return Err(R1CSError::Corrupt("variable index out of range"));
+        }
+
+        let coeff: Fr = try!(decode_from(r, Infinite));
+
+        lc.push((index as usize, coeff));
+    }
+
+    Ok(lc)
+}
+
+fn write_lc<W: Write>(w: &mut W, lc: &LinearCombination) -> Result<(), R1CSError> {
+    try!(w.write_u64::<LittleEndian>(lc.len() as u64));
+
+    for &(index, ref coeff) in lc {
+        try!(w.write_u64::<LittleEndian>(index as u64));
+        try!(encode_into(coeff, w, Infinite));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum R1CSError {
+    Io(io::Error),
+    Encoding(EncodingError),
+    Decoding(DecodingError),
+    BadMagic,
+    UnsupportedVersion(u32),
+    Corrupt(&'static str)
+}
+
+impl fmt::Display for R1CSError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            R1CSError::Io(ref e) => write!(f, "i/o error: {}", e),
+            R1CSError::Encoding(ref e) => write!(f, "encoding error: {}", e),
+            R1CSError::Decoding(ref e) => write!(f, "decoding error: {}", e),
+            R1CSError::BadMagic => write!(f, "not a constraint system"),
+            R1CSError::UnsupportedVersion(v) => write!(f, "unsupported constraint system version {}", v),
+            R1CSError::Corrupt(e) => write!(f, "corrupt constraint system: {}", e)
+        }
+    }
+}
+
+impl From<io::Error> for R1CSError {
+    fn from(e: io::Error) -> R1CSError {
+        R1CSError::Io(e)
+    }
+}
+
+impl From<EncodingError> for R1CSError {
+    fn from(e: EncodingError) -> R1CSError {
+        R1CSError::Encoding(e)
+    }
+}
+
+impl From<DecodingError> for R1CSError {
+    fn from(e: DecodingError) -> R1CSError {
+        R1CSError::Decoding(e)
+    }
+}
+
+/// The QAP a constraint system reduces to. Its polynomials are never
+/// constructed; they are evaluated straight from the constraints.
+pub struct QAP {
+    /// Degree of the QAP, which is the size of the evaluation domain.
+    pub d: usize,
+    /// Number of variables, including the constant one.
+    pub num_vars: usize,
+    pub num_inputs: usize,
+    /// Generator of the evaluation domain.
+    pub omega: Fr,
+    /// The constraint system as it was given, so that it still has the
+    /// hash of its file.
+    r1cs: R1CS,
+    /// Whether the reduction swaps A and B.
+    swap_ab: bool
+}
+
+impl QAP {
+    /// Reduces `r1cs` to a QAP. Returns `None` if a constraint refers
+    /// to a variable which doesn't exist, or if libsnark wouldn't choose
+    /// a radix-2 domain for it, which the protocol relies on.
+    pub fn new(r1cs: R1CS) -> Option<QAP> {
+        for constraint in &r1cs.constraints {
+            for lc in &[&constraint.a, &constraint.b, &constraint.c] {
+                if lc.iter().any(|&(i, _)| i > r1cs.num_variables()) {
+                    return None;
+                }
+            }
+        }
+
+        let swap_ab = r1cs.should_swap_ab();
+
+        // The extra constraints `input * 0 = 0` for every input and
+        // the constant one, which libsnark adds for soundness.
+        let d = match domain_size(r1cs.constraints.len() + r1cs.num_inputs + 1) {
+            Some(d) => d,
+            None => return None
+        };
+
+        Some(QAP {
+            d: d,
+            num_vars: r1cs.num_variables() + 1,
+            num_inputs: r1cs.num_inputs,
+            omega: root_of_unity(d).unwrap(),
+            r1cs: r1cs,
+            swap_ab: swap_ab
+        })
+    }
+
+    pub fn r1cs(&self) -> &R1CS {
+        &self.r1cs
+    }
+
+    /// The A and B of a constraint, as the QAP has them.
+    pub fn ab<'a>(&self, constraint: &'a Constraint) -> (&'a LinearCombination, &'a LinearCombination) {
+        if self.swap_ab {
+            (&constraint.b, &constraint.a)
+        } else {
+            (&constraint.a, &constraint.b)
+        }
+    }
+}
+
+/// The size of the domain libsnark evaluates a QAP over, given the
+/// number of constraints, if that domain is a radix-2 domain.
+fn domain_size(min_size: usize) -> Option<usize> {
+    if min_size < 2 {
+        return None;
+    }
+
+    let size = min_size.next_power_of_two();
+
+    // libsnark only rounds up to the next power of two if the domain
+    // would be more than half full; otherwise it uses a smaller "step"
+    // domain, whose size isn't a power of two.
+    if size != min_size && min_size - size / 2 <= size / 4 {
+        return None;
+    }
+
+    if size.trailing_zeros() as usize > TWO_ADICITY {
+        return None;
+    }
+
+    Some(size)
+}
+
+/// A primitive `n`th root of unity, for `n` a power of two.
+pub fn root_of_unity(n: usize) -> Option<Fr> {
+    if n < 2 || !n.is_power_of_two() || n.trailing_zeros() as usize > TWO_ADICITY {
+        return None;
+    }
+
+    let mut omega = Fr::from_str(ROOT_OF_UNITY).unwrap();
+
+    for _ in (n.trailing_zeros() as usize)..TWO_ADICITY {
+        omega = omega * omega;
+    }
+
+    Some(omega)
+}
+
+#[test]
+fn roots_of_unity() {
+    let pow = |x: Fr, n: usize| x.pow(Fr::from_str(&format!("{}", n)).unwrap());
+
+    for log_n in 1..(TWO_ADICITY + 1) {
+        let n = 1 << log_n;
+        let omega = root_of_unity(n).unwrap();
+
+        assert!(pow(omega, n) == Fr::one());
+        assert!(pow(omega, n / 2) != Fr::one());
+    }
+
+    assert!(root_of_unity(1 << (TWO_ADICITY + 1)).is_none());
+    assert!(root_of_unity(3).is_none());
+
+    assert_eq!(domain_size(2), Some(2));
+    assert_eq!(domain_size(255), Some(256));
+    assert_eq!(domain_size(256), Some(256));
+    assert_eq!(domain_size(300), None);
+}
+
+#[test]
+fn r1cs_file_roundtrip() {
+    let rng = &mut ::rand::thread_rng();
+
+    let r1cs = R1CS {
+        num_inputs: 2,
+        num_aux: 3,
+        constraints: (0..10).map(|i| {
+            Constraint {
+                a: vec![(i % 6, Fr::random(rng)), (5, Fr::one())],
+                b: vec![(0, Fr::random(rng))],
+                c: vec![]
+            }
+        }).collect()
+    };
+
+    let mut encoded = vec![];
+    r1cs.write(&mut encoded).unwrap();
+
+    assert!(R1CS::read(&mut &encoded[..]).unwrap() == r1cs);
+    assert!(r1cs.hash() == Digest256::from_reader(&mut &encoded[..]));
+
+    // Trailing data
+    let mut longer = encoded.clone();
+    longer.push(0);
+    assert!(R1CS::read(&mut &longer[..]).is_err());
+
+    // Truncated
+    assert!(R1CS::read(&mut &encoded[..encoded.len() - 1]).is_err());
+
+    // Not a constraint system
+    let mut bad_magic = encoded.clone();
+    bad_magic[0] ^= 1;
+    assert!(R1CS::read(&mut &bad_magic[..]).is_err());
+
+    // Refers to a variable which doesn't exist
+    let mut bad_index = r1cs.clone();
+    bad_index.constraints[3].c.push((6, Fr::one()));
+    let mut encoded = vec![];
+    bad_index.write(&mut encoded).unwrap();
+    assert!(R1CS::read(&mut &encoded[..]).is_err());
+    assert!(QAP::new(bad_index).is_none());
+
+    // B has more variables than A, so the reduction swaps them, but the
+    // constraint system it keeps is the one that was read.
+    let mut swapped = r1cs.clone();
+    for constraint in &mut swapped.constraints {
+        ::std::mem::swap(&mut constraint.a, &mut constraint.b);
+    }
+    assert!(swapped.should_swap_ab());
+    let mut encoded = vec![];
+    swapped.write(&mut encoded).unwrap();
+    let qap = QAP::new(R1CS::read(&mut &encoded[..]).unwrap()).unwrap();
+    assert!(qap.r1cs() == &swapped);
+    assert!(qap.r1cs().hash() == Digest256::from_reader(&mut &encoded[..]));
+    assert!(qap.ab(&swapped.constraints[0]) == (&r1cs.constraints[0].a, &r1cs.constraints[0].b));
+}
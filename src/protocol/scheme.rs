@@ -0,0 +1,147 @@
+//! The zk-SNARKs the ceremony can construct parameters for.
+//!
+//! Every scheme shares stage 1, the powers of tau, and differs in the
+//! secrets its players sample, in what stages 2 and 3 compute from the
+//! powers of tau, and in the keypair that comes out at the end. The
+//! coordinator, the players and the verifier are written once, generic
+//! over `Scheme`, and pick the scheme of the ceremony at runtime.
+
+use bn::*;
+use rand::Rng;
+use std::io::Read;
+use rustc_serialize::{Encodable, Decodable};
+use bincode::rustc_serialize::DecodingError;
+use super::spair::{Spair, Batch};
+use super::digest::{Digest256, Digest512};
+use super::r1cs::QAP;
+use super::{Stage1Contents, Pghr13};
+use super::groth16::Groth16;
+
+/// Names a scheme in the configuration and the transcript.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, RustcEncodable, RustcDecodable)]
+pub enum ProofSystem {
+    /// libsnark's `r1cs_ppzksnark`.
+    Pghr13,
+    Groth16
+}
+
+impl ProofSystem {
+    pub fn from_name(name: &str) -> Option<ProofSystem> {
+        match name {
+            "pghr13" => Some(ProofSystem::Pghr13),
+            "groth16" => Some(ProofSystem::Groth16),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ProofSystem::Pghr13 => "pghr13",
+            ProofSystem::Groth16 => "groth16"
+        }
+    }
+
+    /// As `Scheme::powers_of_tau`, for the named scheme.
+    pub fn powers_of_tau(&self, qap: &QAP) -> usize {
+        match *self {
+            ProofSystem::Pghr13 => Pghr13::powers_of_tau(qap),
+            ProofSystem::Groth16 => Groth16::powers_of_tau(qap)
+        }
+    }
+}
+
+/// The records generic over a scheme, like `Exclusion<S>`, derive
+/// their serialization, which requires it of the scheme itself.
+pub trait Scheme: Sized + Encodable + Decodable {
+    type PrivateKey: SchemePrivateKey<PublicKey = Self::PublicKey>;
+    type PublicKey: SchemePublicKey<PrivateKey = Self::PrivateKey, Nizks = Self::PublicKeyNizks>;
+    type PublicKeyNizks: SchemeNizks<PublicKey = Self::PublicKey>;
+    type Stage2: Stage<PrivateKey = Self::PrivateKey, PublicKey = Self::PublicKey>;
+    type Stage3: Stage<PrivateKey = Self::PrivateKey, PublicKey = Self::PublicKey>;
+
+    fn proof_system() -> ProofSystem;
+
+    /// Number of powers of tau, starting from tau^0, that stage 1
+    /// computes for `qap`.
+    fn powers_of_tau(qap: &QAP) -> usize;
+
+    /// The initial contents of stage 2, which the coordinator computes
+    /// from the final contents of stage 1.
+    fn stage2(qap: &QAP, stage1: &Stage1Contents) -> Self::Stage2;
+
+    /// The initial contents of stage 3, likewise.
+    fn stage3(qap: &QAP, stage1: &Stage1Contents, stage2: &Self::Stage2) -> Self::Stage3;
+}
+
+/// The secrets sampled by a player.
+pub trait SchemePrivateKey {
+    type PublicKey;
+
+    fn new<R: Rng>(rng: &mut R) -> Self;
+
+    /// Construct the "public key" used to verify that the player is
+    /// performing their transformations correctly.
+    fn pubkey<R: Rng>(&self, rng: &mut R) -> Self::PublicKey;
+
+    /// The secret stage 1 is transformed with.
+    fn tau(&self) -> Fr;
+}
+
+pub trait SchemePublicKey: Clone + Encodable + Decodable {
+    type PrivateKey;
+    type Nizks;
+
+    /// The player's commitment to the public key.
+    fn hash(&self) -> Digest256;
+
+    /// Proves knowledge of each of the secrets of the public key.
+    fn nizks<R: Rng>(&self, rng: &mut R, privkey: &Self::PrivateKey, extra: &Digest512) -> Self::Nizks;
+
+    fn tau_g2(&self) -> Spair<G2>;
+}
+
+pub trait SchemeNizks: Encodable + Decodable {
+    type PublicKey;
+
+    /// Verifies each of the nizks, named after the secret they prove
+    /// knowledge of.
+    fn verify_each(&self, pubkey: &Self::PublicKey, extra: &Digest512) -> Vec<(&'static str, bool)>;
+
+    fn is_valid(&self, pubkey: &Self::PublicKey, extra: &Digest512) -> bool {
+        self.verify_each(pubkey, extra).iter().all(|&(_, valid)| valid)
+    }
+}
+
+/// The contents of stage 2 or 3, which each player transforms in turn.
+pub trait Stage: Clone + Encodable + Decodable {
+    type PrivateKey;
+    type PublicKey;
+
+    fn transform(&mut self, s: &Self::PrivateKey);
+
+    fn verify_transform(&self, prev: &Self, p: &Self::PublicKey) -> bool;
+
+    /// Like `verify_transform`, but reads the encoded previous and new
+    /// contents from `prev` and `next` in windows of `window` elements.
+    fn verify_transform_streaming<P: Read, N: Read>(
+        prev: &mut P,
+        next: &mut N,
+        p: &Self::PublicKey,
+        window: usize
+    ) -> Result<bool, DecodingError>
+    {
+        let mut batch = Batch::new();
+
+        Ok(try!(Self::verify_transform_batched(prev, next, p, window, &mut batch)) && batch.verify())
+    }
+
+    /// Like `verify_transform_streaming`, but leaves the pairing checks
+    /// to `batch` so that they can be verified along with others.
+    fn verify_transform_batched<P: Read, N: Read>(
+        prev: &mut P,
+        next: &mut N,
+        p: &Self::PublicKey,
+        window: usize,
+        batch: &mut Batch
+    ) -> Result<bool, DecodingError>;
+}
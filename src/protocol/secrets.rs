@@ -3,6 +3,7 @@ use rand::Rng;
 use super::spair::{Spair, same_power};
 use super::nizk::Nizk;
 use super::digest::{Digest512,Digest256};
+use super::scheme::*;
 #[cfg(feature = "snark")]
 use snark::*;
 use rustc_serialize::{Encodable, Encoder, Decodable, Decoder};
@@ -42,16 +43,20 @@ pub struct PublicKeyNizks {
     gamma: Nizk<G1>
 }
 
-impl PublicKeyNizks {
-    pub fn is_valid(&self, pubkey: &PublicKey, extra: &Digest512) -> bool {
-        pubkey.tau_g2().verify_nizk(&self.tau, extra) &&
-        pubkey.alpha_a_g1().verify_nizk(&self.alpha_a, extra) &&
-        pubkey.alpha_b_g2().verify_nizk(&self.alpha_b, extra) &&
-        pubkey.alpha_c_g1().verify_nizk(&self.alpha_c, extra) &&
-        pubkey.rho_a_g2().verify_nizk(&self.rho_a, extra) &&
-        pubkey.rho_b_g1().verify_nizk(&self.rho_b, extra) &&
-        pubkey.beta_g2().verify_nizk(&self.beta, extra) &&
-        pubkey.gamma_g1().verify_nizk(&self.gamma, extra)
+impl SchemeNizks for PublicKeyNizks {
+    type PublicKey = PublicKey;
+
+    fn verify_each(&self, pubkey: &PublicKey, extra: &Digest512) -> Vec<(&'static str, bool)> {
+        vec![
+            ("tau", pubkey.tau_g2().verify_nizk(&self.tau, extra)),
+            ("alpha_a", pubkey.alpha_a_g1().verify_nizk(&self.alpha_a, extra)),
+            ("alpha_b", pubkey.alpha_b_g2().verify_nizk(&self.alpha_b, extra)),
+            ("alpha_c", pubkey.alpha_c_g1().verify_nizk(&self.alpha_c, extra)),
+            ("rho_a", pubkey.rho_a_g2().verify_nizk(&self.rho_a, extra)),
+            ("rho_b", pubkey.rho_b_g1().verify_nizk(&self.rho_b, extra)),
+            ("beta", pubkey.beta_g2().verify_nizk(&self.beta, extra)),
+            ("gamma", pubkey.gamma_g1().verify_nizk(&self.gamma, extra))
+        ]
     }
 }
 
@@ -73,27 +78,6 @@ impl PublicKey {
         same_power(&self.0.f8_gamma, &Spair::new(self.0.f2_beta, self.0.f2_beta_gamma).unwrap())
     }
 
-    pub fn hash(&self) -> Digest256 {
-        Digest256::from(self).expect("PublicKey should never fail to encode")
-    }
-
-    pub fn nizks<R: Rng>(&self, rng: &mut R, privkey: &PrivateKey, extra: &Digest512) -> PublicKeyNizks {
-        PublicKeyNizks {
-            tau: self.tau_g2().nizk(rng, privkey.tau, extra),
-            alpha_a: self.alpha_a_g1().nizk(rng, privkey.alpha_a, extra),
-            alpha_b: self.alpha_b_g2().nizk(rng, privkey.alpha_b, extra),
-            alpha_c: self.alpha_c_g1().nizk(rng, privkey.alpha_c, extra),
-            rho_a: self.rho_a_g2().nizk(rng, privkey.rho_a, extra),
-            rho_b: self.rho_b_g1().nizk(rng, privkey.rho_b, extra),
-            beta: self.beta_g2().nizk(rng, privkey.beta, extra),
-            gamma: self.gamma_g1().nizk(rng, privkey.gamma, extra)
-        }
-    }
-
-    pub fn tau_g2(&self) -> Spair<G2> {
-        self.0.f3_tau.clone()
-    }
-
     pub fn alpha_a_g1(&self) -> Spair<G1> {
         self.0.f4_alpha_a.clone()
     }
@@ -151,6 +135,32 @@ impl PublicKey {
     }
 }
 
+impl SchemePublicKey for PublicKey {
+    type PrivateKey = PrivateKey;
+    type Nizks = PublicKeyNizks;
+
+    fn hash(&self) -> Digest256 {
+        Digest256::from(self).expect("PublicKey should never fail to encode")
+    }
+
+    fn nizks<R: Rng>(&self, rng: &mut R, privkey: &PrivateKey, extra: &Digest512) -> PublicKeyNizks {
+        PublicKeyNizks {
+            tau: self.tau_g2().nizk(rng, privkey.tau, extra),
+            alpha_a: self.alpha_a_g1().nizk(rng, privkey.alpha_a, extra),
+            alpha_b: self.alpha_b_g2().nizk(rng, privkey.alpha_b, extra),
+            alpha_c: self.alpha_c_g1().nizk(rng, privkey.alpha_c, extra),
+            rho_a: self.rho_a_g2().nizk(rng, privkey.rho_a, extra),
+            rho_b: self.rho_b_g1().nizk(rng, privkey.rho_b, extra),
+            beta: self.beta_g2().nizk(rng, privkey.beta, extra),
+            gamma: self.gamma_g1().nizk(rng, privkey.gamma, extra)
+        }
+    }
+
+    fn tau_g2(&self) -> Spair<G2> {
+        self.0.f3_tau.clone()
+    }
+}
+
 impl Encodable for PublicKey {
     fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
         self.0.encode(s)
@@ -183,10 +193,12 @@ pub struct PrivateKey {
     pub gamma: Fr
 }
 
-impl PrivateKey {
+impl SchemePrivateKey for PrivateKey {
+    type PublicKey = PublicKey;
+
     /// Construct the player's secrets given a random number
     /// generator.
-    pub fn new<R: Rng>(rng: &mut R) -> PrivateKey {
+    fn new<R: Rng>(rng: &mut R) -> PrivateKey {
         PrivateKey {
             tau: Fr::random(rng),
             rho_a: Fr::random(rng),
@@ -199,6 +211,54 @@ impl PrivateKey {
         }
     }
 
+    fn pubkey<R: Rng>(&self, rng: &mut R) -> PublicKey {
+        let f1 = G2::random(rng);
+        let f1_rho_a = f1 * self.rho_a;
+        let f1_rho_a_alpha_a = f1_rho_a * self.alpha_a;
+        let f1_rho_a_rho_b = f1_rho_a * self.rho_b;
+        let f1_rho_a_rho_b_alpha_c = f1_rho_a_rho_b * self.alpha_c;
+        let f1_rho_a_rho_b_alpha_b = f1_rho_a_rho_b * self.alpha_b;
+        let f2 = G2::random(rng);
+        let f2_beta = f2 * self.beta;
+        let f2_beta_gamma = f2_beta * self.gamma;
+
+        let f3_tau = Spair::random(rng, self.tau).unwrap();
+        let f4_alpha_a = Spair::random(rng, self.alpha_a).unwrap();
+        let f5_alpha_c = Spair::random(rng, self.alpha_c).unwrap();
+        let f6_rho_b = Spair::random(rng, self.rho_b).unwrap();
+        let f7_rho_a_rho_b = Spair::random(rng, self.rho_a * self.rho_b).unwrap();
+        let f8_gamma = Spair::random(rng, self.gamma).unwrap();
+
+        let tmp = PublicKey(PublicKeyInner {
+            f1: f1,
+            f1_rho_a: f1_rho_a,
+            f1_rho_a_alpha_a: f1_rho_a_alpha_a,
+            f1_rho_a_rho_b: f1_rho_a_rho_b,
+            f1_rho_a_rho_b_alpha_c: f1_rho_a_rho_b_alpha_c,
+            f1_rho_a_rho_b_alpha_b: f1_rho_a_rho_b_alpha_b,
+            f2: f2,
+            f2_beta: f2_beta,
+            f2_beta_gamma: f2_beta_gamma,
+
+            f3_tau: f3_tau,
+            f4_alpha_a: f4_alpha_a,
+            f5_alpha_c: f5_alpha_c,
+            f6_rho_b: f6_rho_b,
+            f7_rho_a_rho_b: f7_rho_a_rho_b,
+            f8_gamma: f8_gamma
+        });
+
+        assert!(tmp.is_valid());
+
+        tmp
+    }
+
+    fn tau(&self) -> Fr {
+        self.tau
+    }
+}
+
+impl PrivateKey {
     /// Construct a "blank" private key for accumulating
     /// in tests.
     #[cfg(feature = "snark")]
@@ -241,50 +301,6 @@ impl PrivateKey {
             &self.gamma
         )
     }
-
-    /// Construct the "public key" used to verify that the player
-    /// is performing their transformations correctly.
-    pub fn pubkey<R: Rng>(&self, rng: &mut R) -> PublicKey {
-        let f1 = G2::random(rng);
-        let f1_rho_a = f1 * self.rho_a;
-        let f1_rho_a_alpha_a = f1_rho_a * self.alpha_a;
-        let f1_rho_a_rho_b = f1_rho_a * self.rho_b;
-        let f1_rho_a_rho_b_alpha_c = f1_rho_a_rho_b * self.alpha_c;
-        let f1_rho_a_rho_b_alpha_b = f1_rho_a_rho_b * self.alpha_b;
-        let f2 = G2::random(rng);
-        let f2_beta = f2 * self.beta;
-        let f2_beta_gamma = f2_beta * self.gamma;
-
-        let f3_tau = Spair::random(rng, self.tau).unwrap();
-        let f4_alpha_a = Spair::random(rng, self.alpha_a).unwrap();
-        let f5_alpha_c = Spair::random(rng, self.alpha_c).unwrap();
-        let f6_rho_b = Spair::random(rng, self.rho_b).unwrap();
-        let f7_rho_a_rho_b = Spair::random(rng, self.rho_a * self.rho_b).unwrap();
-        let f8_gamma = Spair::random(rng, self.gamma).unwrap();
-
-        let tmp = PublicKey(PublicKeyInner {
-            f1: f1,
-            f1_rho_a: f1_rho_a,
-            f1_rho_a_alpha_a: f1_rho_a_alpha_a,
-            f1_rho_a_rho_b: f1_rho_a_rho_b,
-            f1_rho_a_rho_b_alpha_c: f1_rho_a_rho_b_alpha_c,
-            f1_rho_a_rho_b_alpha_b: f1_rho_a_rho_b_alpha_b,
-            f2: f2,
-            f2_beta: f2_beta,
-            f2_beta_gamma: f2_beta_gamma,
-
-            f3_tau: f3_tau,
-            f4_alpha_a: f4_alpha_a,
-            f5_alpha_c: f5_alpha_c,
-            f6_rho_b: f6_rho_b,
-            f7_rho_a_rho_b: f7_rho_a_rho_b,
-            f8_gamma: f8_gamma
-        });
-
-        assert!(tmp.is_valid());
-
-        tmp
-    }
 }
 
 #[test]
@@ -0,0 +1,128 @@
+//! The coordinator's long-term signing key, with which it vouches for
+//! the key it runs the channel to the network machines with (see
+//! `channel.rs`), and signs the transcript as it writes it (see
+//! `transcript.rs`). Anyone holding the published verifying key can tell
+//! they're talking to the coordinator, and that a copy of the transcript
+//! is the one it wrote.
+//!
+//! Signatures are Schnorr signatures in G1, i.e. nizks of the secret key
+//! whose challenge is bound to the signed hash.
+
+use bn::*;
+use rand::Rng;
+use std::io::{self, Read, Write};
+use rustc_serialize::hex::{FromHex, ToHex};
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::{encode, decode};
+use super::digest::{Digest256, Digest512};
+use super::nizk::Nizk;
+
+#[derive(Clone)]
+pub struct SigningKey {
+    secret: Fr
+}
+
+/// The verifying key of a `SigningKey`, which the coordinator publishes.
+/// It isn't called a verifying key so as not to be confused with the
+/// SNARK's.
+#[derive(Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct CoordinatorKey(G1);
+
+#[derive(Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Signature(Nizk<G1>);
+
+/// What the nizk's challenge is bound to, which sets signatures apart
+/// from the players' nizks.
+fn signed(hash: &Digest256) -> Digest512 {
+    Digest512::from(&("coordinator signature", hash)).unwrap()
+}
+
+impl SigningKey {
+    pub fn new<R: Rng>(rng: &mut R) -> SigningKey {
+        SigningKey {
+            secret: Fr::random(rng)
+        }
+    }
+
+    pub fn verifying_key(&self) -> CoordinatorKey {
+        CoordinatorKey(G1::one() * self.secret)
+    }
+
+    pub fn sign<R: Rng>(&self, rng: &mut R, hash: &Digest256) -> Signature {
+        Signature(Nizk::new(rng, G1::one(), self.secret, &signed(hash)))
+    }
+
+    /// Reads a key written by `save`.
+    pub fn load<R: Read>(r: &mut R) -> Option<SigningKey> {
+        let mut contents = String::new();
+
+        if r.read_to_string(&mut contents).is_err() {
+            return None;
+        }
+
+        let bytes = match contents.trim().from_hex() {
+            Ok(bytes) => bytes,
+            Err(_) => return None
+        };
+
+        decode(&bytes).ok().map(|secret| SigningKey {
+            secret: secret
+        })
+    }
+
+    /// Writes the secret key, in hex.
+    pub fn save<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", encode(&self.secret, Infinite).unwrap().to_hex())
+    }
+}
+
+impl CoordinatorKey {
+    pub fn verify(&self, hash: &Digest256, signature: &Signature) -> bool {
+        signature.0.verify(G1::one(), self.0, &signed(hash))
+    }
+
+    /// Parses a key published with `to_hex`.
+    pub fn from_hex(s: &str) -> Option<CoordinatorKey> {
+        let bytes = match s.from_hex() {
+            Ok(bytes) => bytes,
+            Err(_) => return None
+        };
+
+        decode(&bytes).ok().and_then(|public: G1| {
+            if public.is_zero() {
+                None
+            } else {
+                Some(CoordinatorKey(public))
+            }
+        })
+    }
+
+    pub fn to_hex(&self) -> String {
+        encode(&self.0, Infinite).unwrap().to_hex()
+    }
+}
+
+#[test]
+fn coordinator_signatures() {
+    let rng = &mut ::rand::thread_rng();
+
+    let key = SigningKey::new(rng);
+    let other = SigningKey::new(rng);
+    let hash = Digest256::from(&"message").unwrap();
+    let signature = key.sign(rng, &hash);
+
+    assert!(key.verifying_key().verify(&hash, &signature));
+    assert!(!key.verifying_key().verify(&Digest256::from(&"forgery").unwrap(), &signature));
+    assert!(!other.verifying_key().verify(&hash, &signature));
+
+    let published = key.verifying_key().to_hex();
+    assert!(CoordinatorKey::from_hex(&published) == Some(key.verifying_key()));
+    assert!(CoordinatorKey::from_hex("c0ffee").is_none());
+    assert!(CoordinatorKey::from_hex("not hex").is_none());
+
+    let mut saved = vec![];
+    key.save(&mut saved).unwrap();
+    let loaded = SigningKey::load(&mut &saved[..]).unwrap();
+    assert!(loaded.verifying_key() == key.verifying_key());
+    assert!(SigningKey::load(&mut &b"nonsense"[..]).is_none());
+}
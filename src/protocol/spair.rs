@@ -1,9 +1,13 @@
 use rand::Rng;
 use bn::*;
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
 use super::multicore::*;
 use super::digest::Digest512;
 use super::nizk::Nizk;
 use rustc_serialize::{Encodable, Encoder, Decodable, Decoder};
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::encode;
+use std::collections::HashMap;
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct Spair<G: Group> {
@@ -59,31 +63,141 @@ impl<G: Group> Spair<G> {
 
 pub trait Pairing<G: Group>: Group {
     fn pairing(self, other: G) -> Gt;
+
+    /// The arguments to `pairing`, in order.
+    fn arguments(self, other: G) -> (G1, G2);
 }
 
 impl Pairing<G2> for G1 {
     fn pairing(self, other: G2) -> Gt {
         pairing(self, other)
     }
+
+    fn arguments(self, other: G2) -> (G1, G2) {
+        (self, other)
+    }
 }
 
 impl Pairing<G1> for G2 {
     fn pairing(self, other: G1) -> Gt {
         pairing(other, self)
     }
+
+    fn arguments(self, other: G1) -> (G1, G2) {
+        (other, self)
+    }
+}
+
+// Pairings computed in this process, by the check that computed them.
+static SAME_POWER_PAIRINGS: AtomicUsize = ATOMIC_USIZE_INIT;
+static CHECKVEC_PAIRINGS: AtomicUsize = ATOMIC_USIZE_INIT;
+static BATCH_PAIRINGS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// How many pairings each kind of check has computed so far, for the
+/// metrics. `checkvec` and `checkseq` count as `checkvec`, rather than
+/// as the `same_power` they end in.
+pub struct PairingCounts {
+    pub same_power: usize,
+    pub checkvec: usize,
+    pub batch: usize
+}
+
+pub fn pairing_counts() -> PairingCounts {
+    PairingCounts {
+        same_power: SAME_POWER_PAIRINGS.load(Ordering::SeqCst),
+        checkvec: CHECKVEC_PAIRINGS.load(Ordering::SeqCst),
+        batch: BATCH_PAIRINGS.load(Ordering::SeqCst)
+    }
 }
 
 pub fn same_power<Group1: Group, Group2: Group>(a: &Spair<Group1>, b: &Spair<Group2>) -> bool
+where Group1: Pairing<Group2> {
+    SAME_POWER_PAIRINGS.fetch_add(2, Ordering::SeqCst);
+
+    pairings_match(a, b)
+}
+
+fn pairings_match<Group1: Group, Group2: Group>(a: &Spair<Group1>, b: &Spair<Group2>) -> bool
 where Group1: Pairing<Group2> {
     a.f.pairing(b.fs) == a.fs.pairing(b.f)
 }
 
+/// `same_power` checks which are deferred so that many of them can be
+/// verified at once. Each check of `a` against `b` becomes the equation
+/// `e(r * a.f, b.fs) * e(-r * a.fs, b.f) = 1` for a fresh random `r`, and
+/// all of the equations are multiplied together. Terms sharing a point
+/// in G2 are folded into one, and the product is computed with a single
+/// Miller loop and final exponentiation, by `pairing_batch`.
+pub struct Batch {
+    terms: Vec<(G1, G2)>,
+    /// Where the term of each point in G2 is, keyed by its encoding.
+    positions: HashMap<Vec<u8>, usize>
+}
+
+impl Batch {
+    pub fn new() -> Self {
+        Batch {
+            terms: vec![],
+            positions: HashMap::new()
+        }
+    }
+
+    pub fn same_power<Group1: Group, Group2: Group>(&mut self, a: &Spair<Group1>, b: &Spair<Group2>)
+    where Group1: Pairing<Group2>
+    {
+        let r = Fr::random(&mut ::rand::thread_rng());
+
+        let (p, q) = a.f.arguments(b.fs);
+        self.add(p * r, q);
+
+        let (p, q) = a.fs.arguments(b.f);
+        self.add(-(p * r), q);
+    }
+
+    /// Takes on all of the checks of `other`.
+    pub fn merge(&mut self, other: &Batch) {
+        for &(p, q) in &other.terms {
+            self.add(p, q);
+        }
+    }
+
+    fn add(&mut self, p: G1, q: G2) {
+        let key = encode(&q, Infinite).unwrap();
+
+        if let Some(&i) = self.positions.get(&key) {
+            self.terms[i].0 = self.terms[i].0 + p;
+            return;
+        }
+
+        self.positions.insert(key, self.terms.len());
+        self.terms.push((p, q));
+    }
+
+    /// Whether all of the checks hold, except with negligible
+    /// probability.
+    pub fn verify(&self) -> bool {
+        let terms: Vec<_> = self.terms.iter().filter(|t| !t.0.is_zero()).collect();
+
+        if terms.is_empty() {
+            return true;
+        }
+
+        BATCH_PAIRINGS.fetch_add(terms.len(), Ordering::SeqCst);
+
+        let terms: Vec<(G1, G2)> = terms.into_iter().cloned().collect();
+
+        pairing_batch(&terms) == Gt::one()
+    }
+}
+
 pub fn checkvec<Group1: Group, Group2: Group>(
     v1: &[Group1], v2: &[Group1], a: &Spair<Group2>
 ) -> bool
 where Group1: Pairing<Group2>
 {
-    parallel_all(v1, v2, |v1, v2| {
+    // Every chunk is combined with its own randomness, and then the
+    // chunks are summed, so that only one pairing check is needed.
+    let chunks = parallel_map(v1, v2, |v1, v2| {
         assert_eq!(v1.len(), v2.len());
 
         let rng = &mut ::rand::thread_rng();
@@ -96,14 +210,20 @@ where Group1: Pairing<Group2>
             q = q + (*b * alpha);
         }
 
-        if p.is_zero() && q.is_zero() {
-            true
-        } else if p.is_zero() || q.is_zero() {
-            false
-        } else {
-            same_power(&Spair::new(p, q).unwrap(), a)
-        }
-    }, ::THREADS)
+        (p, q)
+    }, threads());
+
+    let (p, q) = chunks.into_iter().fold((Group1::zero(), Group1::zero()), |acc, c| (acc.0 + c.0, acc.1 + c.1));
+
+    if p.is_zero() && q.is_zero() {
+        true
+    } else if p.is_zero() || q.is_zero() {
+        false
+    } else {
+        CHECKVEC_PAIRINGS.fetch_add(2, Ordering::SeqCst);
+
+        pairings_match(&Spair::new(p, q).unwrap(), a)
+    }
 }
 
 pub fn checkseq<Group1: Group, Group2: Group>(
@@ -129,6 +249,87 @@ fn trivial_samepower() {
     assert!(!same_power(&b, &c));
 }
 
+#[test]
+fn samepower_counts_pairings() {
+    let rng = &mut ::rand::thread_rng();
+
+    let f = Fr::random(rng);
+    let a = Spair::<G1>::random(rng, f).unwrap();
+    let b = Spair::<G2>::random(rng, f).unwrap();
+
+    let before = pairing_counts();
+
+    assert!(same_power(&a, &b));
+
+    let mut batch = Batch::new();
+    batch.same_power(&a, &b);
+    assert!(batch.verify());
+
+    // Other tests may be checking at the same time.
+    let after = pairing_counts();
+    assert!(after.same_power >= before.same_power + 2);
+    assert!(after.batch >= before.batch + 2);
+}
+
+#[test]
+fn batched_samepower() {
+    let rng = &mut ::rand::thread_rng();
+
+    let f = Fr::random(rng);
+    let e = Fr::random(rng);
+    let a = Spair::<G1>::random(rng, f).unwrap();
+    let b = Spair::<G2>::random(rng, f).unwrap();
+    let c = Spair::<G1>::random(rng, e).unwrap();
+    let d = Spair::<G2>::random(rng, e).unwrap();
+
+    let mut good = Batch::new();
+    good.same_power(&a, &b);
+    good.same_power(&b, &a);
+    good.same_power(&c, &d);
+    good.same_power(&c, &d);
+    assert!(good.verify());
+
+    let mut bad = Batch::new();
+    bad.same_power(&a, &b);
+    bad.same_power(&c, &b);
+    assert!(!bad.verify());
+
+    good.merge(&bad);
+    assert!(!good.verify());
+    assert!(Batch::new().verify());
+}
+
+#[test]
+fn batch_agrees_with_same_power() {
+    let rng = &mut ::rand::thread_rng();
+
+    // Checks in both directions, sharing points in G2 and not, of which
+    // one at a time is broken.
+    let f = Fr::random(rng);
+    let e = Fr::random(rng);
+    let g1: Vec<_> = (0..4).map(|_| Spair::<G1>::random(rng, f).unwrap()).collect();
+    let g2: Vec<_> = (0..3).map(|_| Spair::<G2>::random(rng, f).unwrap()).collect();
+    let wrong = Spair::<G1>::random(rng, e).unwrap();
+
+    for broken in 0..(g1.len() + 1) {
+        let mut batch = Batch::new();
+        let mut all_hold = true;
+
+        for (i, a) in g1.iter().enumerate() {
+            let a = if i == broken { &wrong } else { a };
+
+            for b in &g2 {
+                batch.same_power(a, b);
+                batch.same_power(b, a);
+                all_hold &= same_power(a, b);
+            }
+        }
+
+        assert_eq!(batch.verify(), all_hold);
+        assert_eq!(all_hold, broken == g1.len());
+    }
+}
+
 #[test]
 fn samepower_seq() {
     fn general_seq_test<Group1: Group, Group2: Group>()
@@ -0,0 +1,238 @@
+//! Checks for stage transformations that read the previous and new
+//! contents of a stage in windows, instead of decoding both of them in
+//! full. The random linear combinations used by `checkvec` and
+//! `checkseq` are accumulated as the windows go by, so only a window of
+//! each vector is ever held in memory.
+
+use bn::*;
+use std::io::Read;
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::{decode_from, DecodingError};
+use super::spair::*;
+use super::multicore::*;
+
+pub fn read_element<G: Group, R: Read>(r: &mut R) -> Result<G, DecodingError> {
+    decode_from(r, Infinite)
+}
+
+fn read_window<G: Group, R: Read>(r: &mut R, n: usize, window: &mut Vec<G>) -> Result<(), DecodingError> {
+    window.clear();
+
+    for _ in 0..n {
+        window.push(try!(read_element(r)));
+    }
+
+    Ok(())
+}
+
+/// Reads an encoded vector from each of `prev` and `next` in windows of
+/// at most `window` elements, calling `f` with the offset of each pair of
+/// windows. Returns `false` if the vectors have different lengths, in
+/// which case neither is read any further.
+pub fn stream_pair<G, P, N, F>(
+    prev: &mut P,
+    next: &mut N,
+    window: usize,
+    mut f: F
+) -> Result<bool, DecodingError>
+    where G: Group, P: Read, N: Read, F: FnMut(usize, &[G], &[G])
+{
+    assert!(window > 0);
+
+    let len: u64 = try!(decode_from(prev, Infinite));
+    let next_len: u64 = try!(decode_from(next, Infinite));
+
+    if len != next_len {
+        return Ok(false);
+    }
+
+    let len = len as usize;
+    let mut a = Vec::with_capacity(::std::cmp::min(window, len));
+    let mut b = Vec::with_capacity(::std::cmp::min(window, len));
+    let mut start = 0;
+
+    while start < len {
+        let n = ::std::cmp::min(window, len - start);
+
+        try!(read_window(prev, n, &mut a));
+        try!(read_window(next, n, &mut b));
+
+        f(start, &a, &b);
+
+        start += n;
+    }
+
+    Ok(true)
+}
+
+/// Random linear combinations of two vectors, which are related by
+/// the same power if the combinations are.
+pub struct Combination<G: Group> {
+    p: G,
+    q: G
+}
+
+impl<G: Group> Combination<G> {
+    pub fn new() -> Self {
+        Combination {
+            p: G::zero(),
+            q: G::zero()
+        }
+    }
+
+    /// Adds corresponding windows of both vectors.
+    pub fn add(&mut self, a: &[G], b: &[G]) {
+        let parts = parallel_map(a, b, |a, b| {
+            let rng = &mut ::rand::thread_rng();
+            let mut p = G::zero();
+            let mut q = G::zero();
+
+            for (a, b) in a.iter().zip(b.iter()) {
+                let alpha = Fr::random(rng);
+                p = p + (*a * alpha);
+                q = q + (*b * alpha);
+            }
+
+            (p, q)
+        }, threads());
+
+        for (p, q) in parts {
+            self.p = self.p + p;
+            self.q = self.q + q;
+        }
+    }
+
+    /// Whether everything added so far is related by the same power
+    /// as `s`, as `checkvec` would decide. The pairing check is left to
+    /// `batch`, so this is only `false` if the combinations are
+    /// degenerate.
+    pub fn check<Group2: Group>(&self, s: &Spair<Group2>, batch: &mut Batch) -> bool
+        where G: Pairing<Group2>
+    {
+        if self.p.is_zero() && self.q.is_zero() {
+            true
+        } else if self.p.is_zero() || self.q.is_zero() {
+            false
+        } else {
+            batch.same_power(&Spair::new(self.p, self.q).unwrap(), s);
+
+            true
+        }
+    }
+}
+
+/// Random linear combination of a vector with itself shifted by one
+/// element, as checked by `checkseq`. The windows of the vector are
+/// added in order.
+pub struct SeqCombination<G: Group> {
+    combination: Combination<G>,
+    last: Option<G>
+}
+
+impl<G: Group> SeqCombination<G> {
+    pub fn new() -> Self {
+        SeqCombination {
+            combination: Combination::new(),
+            last: None
+        }
+    }
+
+    pub fn add(&mut self, window: &[G]) {
+        if window.is_empty() {
+            return;
+        }
+
+        // The pair spanning the previous window and this one.
+        if let Some(last) = self.last {
+            self.combination.add(&[last], &window[0..1]);
+        }
+
+        self.combination.add(&window[0..window.len()-1], &window[1..]);
+        self.last = Some(window[window.len()-1]);
+    }
+
+    pub fn check<Group2: Group>(&self, s: &Spair<Group2>, batch: &mut Batch) -> bool
+        where G: Pairing<Group2>
+    {
+        self.combination.check(s, batch)
+    }
+}
+
+/// Streaming equivalent of `checkvec` for the next vector in `prev`
+/// and `next`, deferring the pairing check to `batch`.
+pub fn checkvec_streaming<Group1, Group2, P, N>(
+    prev: &mut P,
+    next: &mut N,
+    window: usize,
+    a: &Spair<Group2>,
+    batch: &mut Batch
+) -> Result<bool, DecodingError>
+    where Group1: Group + Pairing<Group2>, Group2: Group, P: Read, N: Read
+{
+    let mut combination = Combination::<Group1>::new();
+
+    let same_len = try!(stream_pair(prev, next, window, |_, a, b| {
+        combination.add(a, b);
+    }));
+
+    Ok(same_len && combination.check(a, batch))
+}
+
+#[test]
+fn streaming_checks_agree() {
+    use bincode::rustc_serialize::encode_into;
+
+    fn general_test<Group1: Group, Group2: Group>()
+    where Group1: Pairing<Group2>
+    {
+        let rng = &mut ::rand::thread_rng();
+
+        let s = Fr::random(rng);
+        let p = Spair::<Group2>::random(rng, s).unwrap();
+
+        let mut a = vec![Group1::random(rng)];
+        for _ in 0..50 {
+            let n = *a.last().unwrap() * s;
+            a.push(n);
+        }
+
+        let b: Vec<Group1> = a.iter().map(|e| *e * s).collect();
+        let mut c = b.clone();
+        c[37] = Group1::random(rng);
+
+        let encoded = |v: &[Group1]| {
+            let mut bytes = vec![];
+            encode_into(&v.to_vec(), &mut bytes, Infinite).unwrap();
+            bytes
+        };
+
+        let checkvec = |a: &[Group1], b: &[Group1], window: usize| {
+            let mut batch = Batch::new();
+
+            checkvec_streaming::<Group1, _, _, _>(&mut &encoded(a)[..], &mut &encoded(b)[..], window, &p, &mut batch).unwrap() &&
+            batch.verify()
+        };
+
+        for window in &[1, 2, 7, 64] {
+            assert!(checkvec(&a, &b, *window));
+            assert!(!checkvec(&a, &c, *window));
+            assert!(!checkvec(&a, &b[1..], *window));
+
+            let mut good = SeqCombination::<Group1>::new();
+            let mut bad = SeqCombination::<Group1>::new();
+
+            assert!(stream_pair(&mut &encoded(&a)[..], &mut &encoded(&c)[..], *window, |_, a, c| {
+                good.add(a);
+                bad.add(c);
+            }).unwrap());
+
+            let mut good_batch = Batch::new();
+            let mut bad_batch = Batch::new();
+            assert!(good.check(&p, &mut good_batch) && good_batch.verify());
+            assert!(!(bad.check(&p, &mut bad_batch) && bad_batch.verify()));
+        }
+    }
+
+    general_test::<G1, G2>();
+    general_test::<G2, G1>();
+}
@@ -0,0 +1,228 @@
+//! The verifier's machine-readable account of everything it checked,
+//! so that ceremony audits can be archived and compared.
+//!
+//! The verifier exits with 0 if the transcript is valid, and otherwise
+//! with the exit code of the class of the first failure:
+//!
+//! * 3: the transcript can't be read, or isn't for this ceremony
+//! * 4: a revealed public key doesn't match its commitment
+//! * 5: a nizk is invalid
+//! * 6: a stage transformation is invalid
+//! * 7: a disc hash doesn't follow from the previous message
+//! * 8: an exclusion isn't supported by its evidence
+//! * 9: an exclusion can't be confirmed, like that of an unresponsive
+//!   player, and `--accept-unconfirmed` wasn't given
+//! * 10: a contribution's elements can't be decoded, like points that
+//!   aren't on the curve
+//! * 11: a player's attestation doesn't hold (see `attestation.rs`)
+//! * 12: the transcript isn't signed by the coordinator's key
+//!
+//! Chain failures mean a player transformed something other than what
+//! they were given, while the others mean their proofs are bad. With
+//! `--collect-all`, the verifier carries on after a failure, checking
+//! every contribution against the one recorded before it, and reports
+//! all of the failures; it still exits with the code of the first.
+//!
+//! Usage errors exit with 2, as with every binary.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::time::Instant;
+use rustc_serialize::json;
+use rustc_serialize::{Encodable, Encoder};
+use protocol::Digest256;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Failure {
+    Transcript,
+    Commitment,
+    Nizks,
+    Transform,
+    Chain,
+    Exclusion,
+    Unconfirmed,
+    Malformed,
+    Attestation,
+    Signature
+}
+
+impl Failure {
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            Failure::Transcript => 3,
+            Failure::Commitment => 4,
+            Failure::Nizks => 5,
+            Failure::Transform => 6,
+            Failure::Chain => 7,
+            Failure::Exclusion => 8,
+            Failure::Unconfirmed => 9,
+            Failure::Malformed => 10,
+            Failure::Attestation => 11,
+            Failure::Signature => 12
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Failure::Transcript => "transcript",
+            Failure::Commitment => "commitment",
+            Failure::Nizks => "nizks",
+            Failure::Transform => "transformation",
+            Failure::Chain => "chain",
+            Failure::Exclusion => "exclusion",
+            Failure::Unconfirmed => "unconfirmed",
+            Failure::Malformed => "malformed",
+            Failure::Attestation => "attestation",
+            Failure::Signature => "signature"
+        }
+    }
+}
+
+impl Encodable for Failure {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        s.emit_str(self.name())
+    }
+}
+
+#[derive(RustcEncodable)]
+pub struct Check {
+    pub stage: u8,
+    pub name: String,
+    pub passed: bool
+}
+
+#[derive(RustcEncodable)]
+pub struct DiscHash {
+    pub disc: String,
+    pub hash: String
+}
+
+#[derive(RustcEncodable)]
+pub struct PlayerReport {
+    /// Numbered from 1, as players are everywhere else.
+    pub player: usize,
+    pub commitment: String,
+    pub discs: Vec<DiscHash>,
+    pub excluded: Option<String>,
+    pub checks: Vec<Check>
+}
+
+#[derive(RustcEncodable)]
+pub struct FailureReport {
+    pub class: Failure,
+    pub exit_code: i32,
+    pub message: String
+}
+
+#[derive(RustcEncodable)]
+pub struct Timing {
+    pub step: String,
+    pub seconds: f64
+}
+
+#[derive(RustcEncodable)]
+pub struct Report {
+    pub transcript: String,
+    pub constraint_system: String,
+    pub players: Vec<PlayerReport>,
+    pub failures: Vec<FailureReport>,
+    pub timings: Vec<Timing>,
+    pub pk_hash: Option<String>,
+    pub vk_hash: Option<String>,
+    /// Hash of the accumulator, for a universal ceremony.
+    pub accumulator_hash: Option<String>,
+    /// The coordinator key the transcript's signatures were checked
+    /// against, if they were.
+    pub coordinator_key: Option<String>,
+    pub collect_all: bool,
+    pub valid: bool
+}
+
+impl Report {
+    pub fn new(transcript: &str, constraint_system: &str, collect_all: bool) -> Report {
+        Report {
+            transcript: transcript.to_string(),
+            constraint_system: constraint_system.to_string(),
+            players: vec![],
+            failures: vec![],
+            timings: vec![],
+            pk_hash: None,
+            vk_hash: None,
+            accumulator_hash: None,
+            coordinator_key: None,
+            collect_all: collect_all,
+            valid: false
+        }
+    }
+
+    pub fn add_player(&mut self, commitment: &Digest256) {
+        let player = self.players.len() + 1;
+
+        self.players.push(PlayerReport {
+            player: player,
+            commitment: commitment.to_string(),
+            discs: vec![],
+            excluded: None,
+            checks: vec![]
+        });
+    }
+
+    pub fn disc(&mut self, player: usize, disc: &str, hash: &Digest256) {
+        self.players[player].discs.push(DiscHash {
+            disc: disc.to_string(),
+            hash: hash.to_string()
+        });
+    }
+
+    pub fn excluded(&mut self, player: usize, stage: u8, reason: &str) {
+        self.players[player].excluded = Some(format!("stage{}: {}", stage, reason));
+    }
+
+    /// Records the outcome of a check of a player's contribution.
+    pub fn check(&mut self, player: usize, stage: u8, name: &str, passed: bool) -> bool {
+        self.players[player].checks.push(Check {
+            stage: stage,
+            name: name.to_string(),
+            passed: passed
+        });
+
+        passed
+    }
+
+    /// Records a failure, returning its class.
+    pub fn fail(&mut self, class: Failure, message: String) -> Failure {
+        println!("{}", message);
+
+        self.failures.push(FailureReport {
+            class: class,
+            exit_code: class.exit_code(),
+            message: message
+        });
+
+        class
+    }
+
+    /// The class of the first failure, which decides the exit code.
+    pub fn first_failure(&self) -> Option<Failure> {
+        self.failures.first().map(|f| f.class)
+    }
+
+    pub fn time(&mut self, step: &str, since: Instant) {
+        let elapsed = since.elapsed();
+
+        self.timings.push(Timing {
+            step: step.to_string(),
+            seconds: elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9
+        });
+    }
+
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        let encoded = json::as_pretty_json(self).to_string();
+        let mut f = try!(File::create(path));
+
+        try!(f.write_all(encoded.as_bytes()));
+        try!(f.write_all(b"\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+//! A small HTTP endpoint the coordinator serves its status on, for
+//! monitoring a running ceremony (`--status <addr>`). It should be bound
+//! to localhost, or otherwise kept away from the players.
+//!
+//! `GET /status` (or just `GET /`) responds with JSON like:
+//!
+//! ```text
+//! {
+//!   "stage": 1,                      0 while players are registering
+//!   "current_player": "0a1b2c3d4e5f6071",
+//!   "queue": ["..."],                peerids waiting for their turn, in order
+//!   "players": [{
+//!     "peerid": "...",
+//!     "commitment": "...",
+//!     "last_stage": 1,               null until a contribution is accepted
+//!     "last_hash": "..."             the message hash after that contribution
+//!   }],
+//!   "bytes_sent": 1234,
+//!   "bytes_received": 5678,
+//!   "elapsed_secs": 60
+//! }
+//! ```
+//!
+//! `GET /metrics` responds with the coordinator's metrics, in the
+//! Prometheus text format (see `metrics.rs`). Anything else gets a 404.
+//! Every response closes the connection.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use rustc_serialize::json;
+use rustc_serialize::hex::ToHex;
+use protocol::Digest256;
+use transport::Traffic;
+use lobby::Lobby;
+use metrics::Metrics;
+
+/// Longest request we read, headers and all.
+const MAX_REQUEST: u64 = 8192;
+
+#[derive(Clone, RustcEncodable)]
+struct PlayerStatus {
+    peerid: String,
+    commitment: String,
+    last_stage: Option<u8>,
+    last_hash: Option<String>
+}
+
+#[derive(RustcEncodable)]
+struct StatusReport {
+    stage: u8,
+    current_player: Option<String>,
+    queue: Vec<String>,
+    players: Vec<PlayerStatus>,
+    bytes_sent: usize,
+    bytes_received: usize,
+    elapsed_secs: u64
+}
+
+/// What the status endpoint reports, which the coordinator keeps up to
+/// date as the ceremony goes on.
+#[derive(Clone)]
+pub struct Monitor {
+    lobby: Lobby,
+    metrics: Metrics,
+    traffic: Arc<Traffic>,
+    players: Arc<Mutex<Vec<PlayerStatus>>>,
+    started: Instant
+}
+
+impl Monitor {
+    pub fn new(lobby: Lobby, metrics: Metrics) -> Monitor {
+        Monitor {
+            lobby: lobby,
+            metrics: metrics,
+            traffic: Arc::new(Traffic::new()),
+            players: Arc::new(Mutex::new(vec![])),
+            started: Instant::now()
+        }
+    }
+
+    /// The totals every connection with a network machine should be
+    /// `Metered` into.
+    pub fn traffic(&self) -> Arc<Traffic> {
+        self.traffic.clone()
+    }
+
+    /// Records a player's commitment, in commitment order.
+    pub fn registered(&self, peerid: &[u8; 8], commitment: &Digest256) {
+        self.players.lock().unwrap().push(PlayerStatus {
+            peerid: peerid.to_hex(),
+            commitment: commitment.to_string(),
+            last_stage: None,
+            last_hash: None
+        });
+    }
+
+    /// Records that a player's contribution to `stage` was accepted,
+    /// leaving `hash` as the hash of the last message.
+    pub fn accepted(&self, player: usize, stage: u8, hash: &Digest256) {
+        let mut players = self.players.lock().unwrap();
+
+        players[player].last_stage = Some(stage);
+        players[player].last_hash = Some(hash.to_string());
+    }
+
+    fn report(&self) -> StatusReport {
+        let (stage, current) = self.lobby.turn();
+
+        StatusReport {
+            stage: stage,
+            current_player: current.map(|peerid| peerid.to_hex()),
+            queue: self.lobby.waiting().iter().map(|peerid| peerid.to_hex()).collect(),
+            players: self.players.lock().unwrap().clone(),
+            bytes_sent: self.traffic.sent(),
+            bytes_received: self.traffic.received(),
+            elapsed_secs: self.started.elapsed().as_secs()
+        }
+    }
+
+    /// Serves the status on `addr` from a thread of its own, returning
+    /// the address it is bound to.
+    pub fn listen(&self, addr: &str) -> io::Result<SocketAddr> {
+        let listener = try!(TcpListener::bind(addr));
+        let bound = try!(listener.local_addr());
+        let monitor = self.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Ok(stream) = stream {
+                    // A client which goes away is no concern of ours.
+                    let _ = monitor.respond(stream);
+                }
+            }
+        });
+
+        Ok(bound)
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> io::Result<()> {
+        try!(stream.set_read_timeout(Some(Duration::from_secs(5))));
+        try!(stream.set_write_timeout(Some(Duration::from_secs(5))));
+
+        let mut reader = BufReader::new(try!(stream.try_clone()).take(MAX_REQUEST));
+        let mut request = String::new();
+        try!(reader.read_line(&mut request));
+
+        // The headers don't matter, but the client may not expect a
+        // response before it has sent them.
+        loop {
+            let mut header = String::new();
+
+            if try!(reader.read_line(&mut header)) == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+
+        let mut words = request.split_whitespace();
+
+        let (status, content_type, body) = match (words.next(), words.next()) {
+            (Some("GET"), Some("/")) | (Some("GET"), Some("/status")) => {
+                ("200 OK", "application/json", json::encode(&self.report()).unwrap())
+            },
+            (Some("GET"), Some("/metrics")) => {
+                ("200 OK", "text/plain; version=0.0.4", self.metrics.render())
+            },
+            _ => ("404 Not Found", "application/json", "{\"error\":\"not found\"}".to_string())
+        };
+
+        try!(write!(stream, "HTTP/1.0 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, content_type, body.len(), body));
+
+        stream.flush()
+    }
+}
+
+#[test]
+fn status_endpoint() {
+    use lobby::{Availability, Turn};
+    use config::TimeoutAction;
+
+    fn get(addr: &SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        response
+    }
+
+    let lobby = Lobby::new(TimeoutAction::Skip);
+    let monitor = Monitor::new(lobby.clone(), Metrics::new());
+    let addr = monitor.listen("127.0.0.1:0").unwrap();
+
+    let commitment = Digest256::from(&1u8).unwrap();
+    let hash = Digest256::from(&2u8).unwrap();
+
+    for i in 0..2 {
+        lobby.register([i; 8], Availability::always());
+        monitor.registered(&[i; 8], &commitment);
+    }
+
+    let response = get(&addr, "/status");
+    assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+    assert!(response.contains("\"stage\":0"));
+    assert!(response.contains("\"current_player\":null"));
+    assert!(response.contains(&format!("\"commitment\":\"{}\"", commitment.to_string())));
+    assert!(response.contains("\"last_hash\":null"));
+
+    lobby.begin_stage(1, vec![0, 1], Duration::from_secs(3600));
+    assert_eq!(lobby.next(|_| true, Duration::from_secs(0)), Some(Turn::Player(0)));
+    monitor.accepted(0, 1, &hash);
+
+    let response = get(&addr, "/");
+    assert!(response.contains("\"stage\":1"));
+    assert!(response.contains("\"current_player\":\"0000000000000000\""));
+    assert!(response.contains("\"queue\":[\"0101010101010101\"]"));
+    assert!(response.contains(&format!("\"last_stage\":1,\"last_hash\":\"{}\"", hash.to_string())));
+
+    let response = get(&addr, "/metrics");
+    assert!(response.starts_with("HTTP/1.0 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n"));
+    assert!(response.contains("# TYPE mpc_pairings_total counter\n"));
+
+    assert!(get(&addr, "/players").starts_with("HTTP/1.0 404 Not Found\r\n"));
+}
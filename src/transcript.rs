@@ -0,0 +1,924 @@
+//! The transcript is a versioned container of records, laid out as follows.
+//! All integers are little endian.
+//!
+//! ```text
+//! header:   magic [u8; 8] | version u32 | length u64 | TranscriptHeader (bincode)
+//! records:  kind u8 | stage u8 | player u32 | length u64 | payload
+//! index:    (a record of kind INDEX) count u64 | count * entry
+//!           entry: kind u8 | stage u8 | player u32 | offset u64 | length u64
+//! trailer:  index offset u64 | magic [u8; 8]
+//! ```
+//!
+//! Record payloads are bincode encoded:
+//!
+//! * `Commitments`: the `Vec<Digest256>` of commitments, in player order,
+//!   followed by the `Vec<[u8; 8]>` of the players' peerids and the
+//!   `Vec<Digest256>` of the hashes of their identity keys (see
+//!   `channel.rs`), which the coordinator uses to recognise reconnecting
+//!   players when resuming, and the `Vec<Availability>` they registered
+//!   with (see `lobby.rs`).
+//! * `Contribution(1, i)`: `PublicKey`, `PublicKeyNizks`, `Stage1Contents`
+//!   and the ihash of player `i`.
+//! * `Contribution(2 or 3, i)`: the stage contents and the ihash.
+//! * `Exclusion(stage, i)`: the `Exclusion` of player `i`.
+//! * `Beacon`: the `Beacon`, recorded before its first contribution.
+//!   The beacon contributes after everyone else in each stage, as
+//!   player `players`, one past the last.
+//! * `Signature(n)`: the `Digest256` running hash of the transcript so
+//!   far and the coordinator's `Signature` of it (see `signing.rs`); the
+//!   `n`th signature in the transcript.
+//!
+//! The coordinator signs the header, and then every record right after
+//! writing it, with its long-term key. The running hash starts out as
+//! the hash of everything before the first record, and is chained with
+//! each record other than a signature as
+//! `H(previous hash, kind, stage, player, length, H(payload))`. So each
+//! signature covers the whole transcript up to it, and a transcript with
+//! every record signed can't be altered, or cut short anywhere but right
+//! after a signature, without the coordinator's key. The index and
+//! trailer aren't signed, since they are only there to find the records.
+//!
+//! The players don't necessarily take their turns in commitment order, so
+//! the turns of each stage are in the order their records were written.
+//!
+//! The `CeremonyParams` in the header are hashed along with the
+//! commitments into the nizks' `extra` and the first ihash, so every
+//! contribution is bound to the constraint system, the scheme and the
+//! accumulator stage 1 starts from, if any. The `PublicKey`,
+//! `PublicKeyNizks` and stage contents are those of the scheme the
+//! params name. A universal ceremony's transcript ends with stage 1.
+//!
+//! The index lets tools seek straight to any record. Since it's only
+//! written once the ceremony is complete, a transcript without a trailer
+//! is read by scanning the records instead. The coordinator resumes such
+//! a transcript by appending to its last complete record.
+//!
+//! A record's length is written as `INCOMPLETE` until its payload has
+//! been written, so that a record the coordinator died in the middle of
+//! is never mistaken for a complete one.
+
+use std::io::{self, Read, Write, Seek, SeekFrom};
+use std::fs::File;
+use std::fmt;
+use bn::Fr;
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::{encode_into, decode_from, EncodingError, DecodingError};
+use protocol::{Digest256, Digest256Writer, Digest512, QAP, ProofSystem, Accumulator};
+use protocol::{SigningKey, CoordinatorKey, Signature};
+use config::Config;
+
+pub const TRANSCRIPT_MAGIC: [u8; 8] = [0x6d, 0x70, 0x63, 0x74, 0x72, 0x61, 0x6e, 0x73];
+pub const TRANSCRIPT_VERSION: u32 = 2;
+
+const KIND_COMMITMENTS: u8 = 1;
+const KIND_CONTRIBUTION: u8 = 2;
+const KIND_EXCLUSION: u8 = 3;
+const KIND_BEACON: u8 = 4;
+const KIND_SIGNATURE: u8 = 5;
+const KIND_INDEX: u8 = 0xff;
+
+const RECORD_HEADER_LEN: u64 = 1 + 1 + 4 + 8;
+
+/// Length of a record whose payload is still being written.
+const INCOMPLETE: u64 = !0;
+const TRAILER_LEN: u64 = 8 + 8;
+
+/// Identifies a record in the transcript.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Section {
+    Commitments,
+    /// A player's accepted contribution to a stage.
+    Contribution(u8, usize),
+    /// A player's exclusion during a stage.
+    Exclusion(u8, usize),
+    /// The public random beacon, which contributes after the players.
+    Beacon,
+    /// The coordinator's signature of the transcript up to it.
+    Signature(usize)
+}
+
+impl Section {
+    pub fn is_signature(&self) -> bool {
+        match *self {
+            Section::Signature(_) => true,
+            _ => false
+        }
+    }
+
+    fn to_parts(&self) -> (u8, u8, u32) {
+        match *self {
+            Section::Commitments => (KIND_COMMITMENTS, 0, 0),
+            Section::Contribution(stage, player) => (KIND_CONTRIBUTION, stage, player as u32),
+            Section::Exclusion(stage, player) => (KIND_EXCLUSION, stage, player as u32),
+            Section::Beacon => (KIND_BEACON, 0, 0),
+            Section::Signature(n) => (KIND_SIGNATURE, 0, n as u32)
+        }
+    }
+
+    fn from_parts(kind: u8, stage: u8, player: u32) -> Option<Section> {
+        match kind {
+            KIND_COMMITMENTS => Some(Section::Commitments),
+            KIND_CONTRIBUTION => Some(Section::Contribution(stage, player as usize)),
+            KIND_EXCLUSION => Some(Section::Exclusion(stage, player as usize)),
+            KIND_BEACON => Some(Section::Beacon),
+            KIND_SIGNATURE => Some(Section::Signature(player as usize)),
+            _ => None
+        }
+    }
+}
+
+/// Parameters of the constraint system the ceremony is for.
+#[derive(Clone, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct CeremonyParams {
+    pub scheme: ProofSystem,
+    pub d: usize,
+    pub num_vars: usize,
+    pub num_inputs: usize,
+    pub omega: Fr,
+    pub cs_hash: Digest256,
+    /// Number of powers of tau in stage 1.
+    pub powers: usize,
+    /// Hash of the accumulator stage 1 is taken from, if any, in which
+    /// case nobody takes a turn in stage 1.
+    pub accumulator: Option<Digest256>
+}
+
+impl CeremonyParams {
+    /// The constraint system is identified by its hash, which is the
+    /// hash of its file, or what its file would be for the dummy one.
+    pub fn new(qap: &QAP, accumulator: Option<&Accumulator>, config: &Config) -> CeremonyParams {
+        CeremonyParams {
+            scheme: config.scheme,
+            d: qap.d,
+            num_vars: qap.num_vars,
+            num_inputs: qap.num_inputs,
+            omega: qap.omega,
+            cs_hash: qap.r1cs().hash(),
+            powers: config.scheme.powers_of_tau(qap),
+            accumulator: accumulator.map(|a| a.hash())
+        }
+    }
+
+    /// A universal ceremony, which computes 2^k powers of tau, has no
+    /// constraint system; it is identified by the hash of its name.
+    pub fn universal(k: u32, config: &Config) -> CeremonyParams {
+        CeremonyParams {
+            scheme: config.scheme,
+            d: 0,
+            num_vars: 0,
+            num_inputs: 0,
+            omega: Fr::zero(),
+            cs_hash: Digest256::from_reader(&mut &b"powers of tau"[..]),
+            powers: 1 << k,
+            accumulator: None
+        }
+    }
+
+    /// Hash of all the commitments and the parameters, which the
+    /// players' nizks are bound to.
+    pub fn hash_of_commitments(&self, commitments: &[Digest256]) -> Digest512 {
+        Digest512::from(&(commitments, self)).unwrap()
+    }
+
+    /// The `last_message_hash` before the first player's contribution,
+    /// which every later one is chained to. Like the nizks, it commits
+    /// to the constraint system, so that a transcript can't be replayed
+    /// against a different one.
+    pub fn initial_message_hash(&self, commitments: &[Digest256]) -> Digest256 {
+        Digest256::from(&(commitments, self)).unwrap()
+    }
+}
+
+#[derive(Clone, RustcEncodable, RustcDecodable)]
+pub struct TranscriptHeader {
+    /// The coordinator's configuration for the ceremony.
+    pub config: Config,
+    pub params: CeremonyParams
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IndexEntry {
+    pub section: Section,
+    /// Offset of the record's payload from the start of the transcript.
+    pub offset: u64,
+    pub len: u64
+}
+
+#[derive(Debug)]
+pub enum TranscriptError {
+    Io(io::Error),
+    Encoding(EncodingError),
+    Decoding(DecodingError),
+    BadMagic,
+    UnsupportedVersion(u32),
+    Corrupt(&'static str),
+    MissingSection(Section),
+    /// The coordinator's signatures don't cover the transcript.
+    BadSignature(String)
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TranscriptError::Io(ref e) => write!(f, "i/o error: {}", e),
+            TranscriptError::Encoding(ref e) => write!(f, "encoding error: {}", e),
+            TranscriptError::Decoding(ref e) => write!(f, "decoding error: {}", e),
+            TranscriptError::BadMagic => write!(f, "not a transcript"),
+            TranscriptError::UnsupportedVersion(v) => write!(f, "unsupported transcript version {}", v),
+            TranscriptError::Corrupt(e) => write!(f, "corrupt transcript: {}", e),
+            TranscriptError::MissingSection(s) => write!(f, "transcript has no {:?} record", s),
+            TranscriptError::BadSignature(ref e) => write!(f, "bad signature: {}", e)
+        }
+    }
+}
+
+impl From<io::Error> for TranscriptError {
+    fn from(e: io::Error) -> TranscriptError {
+        TranscriptError::Io(e)
+    }
+}
+
+impl From<EncodingError> for TranscriptError {
+    fn from(e: EncodingError) -> TranscriptError {
+        TranscriptError::Encoding(e)
+    }
+}
+
+impl From<DecodingError> for TranscriptError {
+    fn from(e: DecodingError) -> TranscriptError {
+        TranscriptError::Decoding(e)
+    }
+}
+
+fn write_record_header<W: Write>(w: &mut W, kind: u8, stage: u8, player: u32, len: u64) -> io::Result<()> {
+    try!(w.write_u8(kind));
+    try!(w.write_u8(stage));
+    try!(w.write_u32::<LittleEndian>(player));
+    try!(w.write_u64::<LittleEndian>(len));
+
+    Ok(())
+}
+
+fn read_record_header<R: Read>(r: &mut R) -> io::Result<(u8, u8, u32, u64)> {
+    let kind = try!(r.read_u8());
+    let stage = try!(r.read_u8());
+    let player = try!(r.read_u32::<LittleEndian>());
+    let len = try!(r.read_u64::<LittleEndian>());
+
+    Ok((kind, stage, player, len))
+}
+
+/// The running hash after a record, which is signed.
+fn chain(prev: &Digest256, kind: u8, stage: u8, player: u32, len: u64, payload: &Digest256) -> Digest256 {
+    Digest256::from(&(prev, kind, stage, player, len, payload)).unwrap()
+}
+
+/// Writes the payload of a record, hashing it on the way.
+pub struct PayloadWriter<'a, W: 'a> {
+    w: &'a mut W,
+    digest: Digest256Writer
+}
+
+impl<'a, W: Write> Write for PayloadWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = try!(self.w.write(buf));
+        try!(self.digest.write_all(&buf[..n]));
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.w.flush()
+    }
+}
+
+pub struct TranscriptWriter<W: Write + Seek> {
+    w: W,
+    index: Vec<IndexEntry>,
+    key: SigningKey,
+    /// The running hash of everything written so far.
+    hash: Digest256,
+    /// Number of signatures written so far.
+    signatures: usize
+}
+
+impl<W: Write + Seek> TranscriptWriter<W> {
+    /// Writes the header, signed with `key` like every record after it.
+    pub fn create(mut w: W, header: &TranscriptHeader, key: SigningKey) -> Result<Self, TranscriptError> {
+        let mut encoded = vec![];
+        try!(encode_into(header, &mut encoded, Infinite));
+
+        let mut bytes = vec![];
+        try!(bytes.write_all(&TRANSCRIPT_MAGIC));
+        try!(bytes.write_u32::<LittleEndian>(TRANSCRIPT_VERSION));
+        try!(bytes.write_u64::<LittleEndian>(encoded.len() as u64));
+        try!(bytes.write_all(&encoded));
+
+        try!(w.write_all(&bytes));
+
+        let mut writer = TranscriptWriter {
+            w: w,
+            index: vec![],
+            key: key,
+            hash: Digest256::from_reader(&mut &bytes[..]),
+            signatures: 0
+        };
+
+        try!(writer.sign());
+
+        Ok(writer)
+    }
+
+    /// Appends a record, whose payload is written by `f`, and signs the
+    /// transcript up to it.
+    pub fn record<F>(&mut self, section: Section, f: F) -> Result<(), TranscriptError>
+        where F: FnOnce(&mut PayloadWriter<W>) -> Result<(), EncodingError>
+    {
+        let (kind, stage, player) = section.to_parts();
+        let (len, payload) = try!(self.append(section, f));
+
+        self.hash = chain(&self.hash, kind, stage, player, len, &payload);
+
+        self.sign()
+    }
+
+    /// Appends a signature of the running hash.
+    fn sign(&mut self) -> Result<(), TranscriptError> {
+        let hash = self.hash.clone();
+        let signature = self.key.sign(&mut ::rand::thread_rng(), &hash);
+        let section = Section::Signature(self.signatures);
+
+        try!(self.append(section, |w| {
+            try!(encode_into(&hash, w, Infinite));
+
+            encode_into(&signature, w, Infinite)
+        }));
+
+        self.signatures += 1;
+
+        Ok(())
+    }
+
+    /// Appends a record, returning the length and hash of its payload.
+    fn append<F>(&mut self, section: Section, f: F) -> Result<(u64, Digest256), TranscriptError>
+        where F: FnOnce(&mut PayloadWriter<W>) -> Result<(), EncodingError>
+    {
+        let (kind, stage, player) = section.to_parts();
+
+        // The length isn't known until the payload is written, so
+        // the record header is filled in afterwards.
+        let start = try!(self.w.seek(SeekFrom::Current(0)));
+        try!(write_record_header(&mut self.w, kind, stage, player, INCOMPLETE));
+
+        let payload = {
+            let mut payload = PayloadWriter {
+                w: &mut self.w,
+                digest: Digest256Writer::new()
+            };
+            try!(f(&mut payload));
+
+            payload.digest.finish()
+        };
+
+        let end = try!(self.w.seek(SeekFrom::Current(0)));
+
+        let len = end - start - RECORD_HEADER_LEN;
+        try!(self.w.seek(SeekFrom::Start(start)));
+        try!(write_record_header(&mut self.w, kind, stage, player, len));
+        try!(self.w.seek(SeekFrom::Start(end)));
+
+        self.index.push(IndexEntry {
+            section: section,
+            offset: start + RECORD_HEADER_LEN,
+            len: len
+        });
+
+        Ok((len, payload))
+    }
+
+    /// Pushes everything written so far to the underlying writer.
+    pub fn flush(&mut self) -> Result<(), TranscriptError> {
+        try!(self.w.flush());
+
+        Ok(())
+    }
+
+    /// Writes the index and trailer, completing the transcript.
+    pub fn finish(mut self) -> Result<W, TranscriptError> {
+        let index_offset = try!(self.w.seek(SeekFrom::Current(0)));
+        let len = 8 + (self.index.len() as u64) * (RECORD_HEADER_LEN + 8);
+
+        try!(write_record_header(&mut self.w, KIND_INDEX, 0, 0, len));
+        try!(self.w.write_u64::<LittleEndian>(self.index.len() as u64));
+
+        for entry in &self.index {
+            let (kind, stage, player) = entry.section.to_parts();
+
+            try!(self.w.write_u8(kind));
+            try!(self.w.write_u8(stage));
+            try!(self.w.write_u32::<LittleEndian>(player));
+            try!(self.w.write_u64::<LittleEndian>(entry.offset));
+            try!(self.w.write_u64::<LittleEndian>(entry.len));
+        }
+
+        try!(self.w.write_u64::<LittleEndian>(index_offset));
+        try!(self.w.write_all(&TRANSCRIPT_MAGIC));
+        try!(self.w.flush());
+
+        Ok(self.w)
+    }
+}
+
+impl TranscriptWriter<File> {
+    /// Continues a transcript that was never finished, discarding
+    /// anything after its last complete record. It must have been signed
+    /// with `key` so far, and its last record is signed now if the
+    /// coordinator stopped before it could be.
+    pub fn resume<R: Read + Seek>(mut f: File, existing: &mut TranscriptReader<R>, key: SigningKey) -> Result<Self, TranscriptError> {
+        if existing.is_complete() {
+            return Err(TranscriptError::Corrupt("transcript is already complete"));
+        }
+
+        let (signed, hash) = try!(existing.signatures(&key.verifying_key()));
+        let end = existing.records_end();
+
+        try!(f.set_len(end));
+        try!(f.seek(SeekFrom::Start(end)));
+
+        let mut writer = TranscriptWriter {
+            w: f,
+            index: existing.index().to_vec(),
+            key: key,
+            hash: hash,
+            signatures: existing.index().iter().filter(|e| e.section.is_signature()).count()
+        };
+
+        if !signed {
+            try!(writer.sign());
+        }
+
+        Ok(writer)
+    }
+
+    /// Makes sure everything written so far survives a crash.
+    pub fn checkpoint(&mut self) -> Result<(), TranscriptError> {
+        try!(self.w.flush());
+        try!(self.w.sync_all());
+
+        Ok(())
+    }
+}
+
+pub struct TranscriptReader<R: Read + Seek> {
+    r: R,
+    header: TranscriptHeader,
+    index: Vec<IndexEntry>,
+    records_start: u64,
+    complete: bool
+}
+
+impl<R: Read + Seek> TranscriptReader<R> {
+    pub fn open(mut r: R) -> Result<Self, TranscriptError> {
+        let mut magic = [0; 8];
+        try!(r.read_exact(&mut magic));
+
+        if magic != TRANSCRIPT_MAGIC {
+            return Err(TranscriptError::BadMagic);
+        }
+
+        let version = try!(r.read_u32::<LittleEndian>());
+
+        if version != TRANSCRIPT_VERSION {
+            return Err(TranscriptError::UnsupportedVersion(version));
+        }
+
+        let header_len = try!(r.read_u64::<LittleEndian>());
+        let header: TranscriptHeader = try!(decode_from(&mut (&mut r).take(header_len), Infinite));
+        let records_start = 8 + 4 + 8 + header_len;
+
+        let (index, complete) = match try!(Self::read_index(&mut r, records_start)) {
+            Some(index) => (index, true),
+            None => (try!(Self::scan(&mut r, records_start)), false)
+        };
+
+        Ok(TranscriptReader {
+            r: r,
+            header: header,
+            index: index,
+            records_start: records_start,
+            complete: complete
+        })
+    }
+
+    pub fn header(&self) -> &TranscriptHeader {
+        &self.header
+    }
+
+    /// The records of the transcript, in the order they were written.
+    pub fn index(&self) -> &[IndexEntry] {
+        &self.index
+    }
+
+    /// Number of records, leaving out the coordinator's signatures.
+    pub fn records(&self) -> usize {
+        self.index.iter().filter(|e| !e.section.is_signature()).count()
+    }
+
+    /// Checks the coordinator's signatures against `key`: every one has
+    /// to be of the running hash up to it, and the header and every
+    /// record but the last have to be followed by one. Returns whether
+    /// the last record is too, which it isn't if the coordinator stopped
+    /// right before signing it.
+    pub fn check_signatures(&mut self, key: &CoordinatorKey) -> Result<bool, TranscriptError> {
+        self.signatures(key).map(|(signed, _)| signed)
+    }
+
+    /// As `check_signatures`, also returning the running hash after the
+    /// last record.
+    fn signatures(&mut self, key: &CoordinatorKey) -> Result<(bool, Digest256), TranscriptError> {
+        try!(self.r.seek(SeekFrom::Start(0)));
+        let mut hash = Digest256::from_reader(&mut (&mut self.r).take(self.records_start));
+
+        // Whether the header or record before is covered by a signature,
+        // and what it was, in case it isn't.
+        let mut signed = false;
+        let mut before = "the header".to_string();
+
+        for entry in self.index.clone() {
+            try!(self.r.seek(SeekFrom::Start(entry.offset)));
+            let mut payload = (&mut self.r).take(entry.len);
+
+            if entry.section.is_signature() {
+                let (recorded, signature): (Digest256, Signature) = try!(decode_from(&mut payload, Infinite));
+
+                if recorded != hash || !key.verify(&hash, &signature) {
+                    return Err(TranscriptError::BadSignature(format!("the signature after {} is invalid", before)));
+                }
+
+                signed = true;
+            } else {
+                if !signed {
+                    return Err(TranscriptError::BadSignature(format!("{} is not signed", before)));
+                }
+
+                let (kind, stage, player) = entry.section.to_parts();
+                hash = chain(&hash, kind, stage, player, entry.len, &Digest256::from_reader(&mut payload));
+
+                signed = false;
+                before = format!("the {:?} record", entry.section);
+            }
+        }
+
+        Ok((signed, hash))
+    }
+
+    /// Whether the transcript was finished with an index.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Offset just past the last complete record.
+    pub fn records_end(&self) -> u64 {
+        match self.index.last() {
+            Some(entry) => entry.offset + entry.len,
+            None => self.records_start
+        }
+    }
+
+    pub fn contains(&self, section: Section) -> bool {
+        self.index.iter().any(|e| e.section == section)
+    }
+
+    /// The players with a contribution or exclusion record in `stage`,
+    /// in the order they took their turns.
+    pub fn order(&self, stage: u8) -> Vec<usize> {
+        self.index.iter().filter_map(|e| {
+            match e.section {
+                Section::Contribution(s, player) | Section::Exclusion(s, player) if s == stage => Some(player),
+                _ => None
+            }
+        }).collect()
+    }
+
+    pub fn entry(&self, section: Section) -> Option<&IndexEntry> {
+        self.index.iter().find(|e| e.section == section)
+    }
+
+    /// Positions the transcript at the start of the payload of `section`.
+    pub fn seek(&mut self, section: Section) -> Result<&mut R, TranscriptError> {
+        let offset = match self.index.iter().find(|e| e.section == section) {
+            Some(entry) => entry.offset,
+            None => return Err(TranscriptError::MissingSection(section))
+        };
+
+        try!(self.r.seek(SeekFrom::Start(offset)));
+
+        Ok(&mut self.r)
+    }
+
+    fn read_index(r: &mut R, records_start: u64) -> Result<Option<Vec<IndexEntry>>, TranscriptError> {
+        let end = try!(r.seek(SeekFrom::End(0)));
+
+        if end < records_start + RECORD_HEADER_LEN + TRAILER_LEN {
+            return Ok(None);
+        }
+
+        try!(r.seek(SeekFrom::Start(end - TRAILER_LEN)));
+        let index_offset = try!(r.read_u64::<LittleEndian>());
+        let mut magic = [0; 8];
+        try!(r.read_exact(&mut magic));
+
+        if magic != TRANSCRIPT_MAGIC || index_offset < records_start || index_offset > end - TRAILER_LEN {
+            return Ok(None);
+        }
+
+        try!(r.seek(SeekFrom::Start(index_offset)));
+        let (kind, _, _, _) = try!(read_record_header(r));
+
+        if kind != KIND_INDEX {
+            return Err(TranscriptError::Corrupt("trailer does not point to the index"));
+        }
+
+        let count = try!(r.read_u64::<LittleEndian>());
+        let mut index = vec![];
+
+        for _ in 0..count {
+            let kind = try!(r.read_u8());
+            let stage = try!(r.read_u8());
+            let player = try!(r.read_u32::<LittleEndian>());
+            let offset = try!(r.read_u64::<LittleEndian>());
+            let len = try!(r.read_u64::<LittleEndian>());
+
+            let section = match Section::from_parts(kind, stage, player) {
+                Some(s) => s,
+                None => return Err(TranscriptError::Corrupt("unknown record kind in index"))
+            };
+
+            if offset < records_start || offset + len > index_offset {
+                return Err(TranscriptError::Corrupt("index entry out of bounds"));
+            }
+
+            index.push(IndexEntry {
+                section: section,
+                offset: offset,
+                len: len
+            });
+        }
+
+        Ok(Some(index))
+    }
+
+    /// Builds the index by walking the records, stopping at the first
+    /// incomplete one.
+    fn scan(r: &mut R, records_start: u64) -> Result<Vec<IndexEntry>, TranscriptError> {
+        let end = try!(r.seek(SeekFrom::End(0)));
+        let mut pos = records_start;
+        let mut index = vec![];
+
+        while pos + RECORD_HEADER_LEN <= end {
+            try!(r.seek(SeekFrom::Start(pos)));
+            let (kind, stage, player, len) = try!(read_record_header(r));
+
+            let next = match (pos + RECORD_HEADER_LEN).checked_add(len) {
+                Some(next) if len != INCOMPLETE && next <= end => next,
+                _ => break
+            };
+
+            let section = match Section::from_parts(kind, stage, player) {
+                Some(s) => s,
+                None => break
+            };
+
+            index.push(IndexEntry {
+                section: section,
+                offset: pos + RECORD_HEADER_LEN,
+                len: len
+            });
+
+            pos = next;
+        }
+
+        Ok(index)
+    }
+}
+
+#[test]
+fn transcript_roundtrip() {
+    use std::io::Cursor;
+
+    let header = TranscriptHeader {
+        config: Config::default(),
+        params: CeremonyParams {
+            scheme: ProofSystem::Pghr13,
+            d: 4,
+            num_vars: 3,
+            num_inputs: 1,
+            omega: Fr::one(),
+            cs_hash: Digest256::from(&"test").unwrap(),
+            powers: 5,
+            accumulator: None
+        }
+    };
+
+    let key = SigningKey::new(&mut ::rand::thread_rng());
+    let verifying_key = key.verifying_key();
+
+    let mut writer = TranscriptWriter::create(Cursor::new(vec![]), &header, key).unwrap();
+    writer.record(Section::Commitments, |w| encode_into(&vec![1u8, 2, 3], w, Infinite)).unwrap();
+    writer.record(Section::Contribution(1, 0), |w| encode_into(&42u64, w, Infinite)).unwrap();
+    writer.record(Section::Exclusion(1, 1), |w| encode_into(&"gone", w, Infinite)).unwrap();
+
+    let partial = writer.w.get_ref().clone();
+    let complete = writer.finish().unwrap().into_inner();
+
+    for (bytes, is_complete) in vec![(complete, true), (partial, false)] {
+        let mut reader = TranscriptReader::open(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(reader.is_complete(), is_complete);
+        assert!(reader.header().params == header.params);
+        assert_eq!(reader.records(), 3);
+        assert_eq!(reader.index().len(), 7);
+        assert!(reader.contains(Section::Signature(3)));
+        assert_eq!(reader.check_signatures(&verifying_key).ok(), Some(true));
+        assert!(reader.contains(Section::Exclusion(1, 1)));
+        assert!(!reader.contains(Section::Contribution(1, 1)));
+        assert_eq!(reader.order(1), vec![0, 1]);
+        assert!(reader.order(2).is_empty());
+
+        // Read out of order
+        let v: u64 = decode_from(reader.seek(Section::Contribution(1, 0)).unwrap(), Infinite).unwrap();
+        assert_eq!(v, 42);
+        let v: Vec<u8> = decode_from(reader.seek(Section::Commitments).unwrap(), Infinite).unwrap();
+        assert_eq!(v, vec![1, 2, 3]);
+
+        assert!(reader.seek(Section::Contribution(2, 0)).is_err());
+    }
+
+    // A truncated record is ignored, which here is the last signature.
+    let mut truncated = TranscriptWriter::create(Cursor::new(vec![]), &header, SigningKey::new(&mut ::rand::thread_rng())).unwrap();
+    truncated.record(Section::Commitments, |w| encode_into(&vec![1u8, 2, 3], w, Infinite)).unwrap();
+    truncated.record(Section::Contribution(1, 0), |w| encode_into(&42u64, w, Infinite)).unwrap();
+    let signature = truncated.index.last().unwrap().offset - RECORD_HEADER_LEN;
+    let verifying_key = truncated.key.verifying_key();
+    let mut bytes = truncated.w.into_inner();
+    let len = bytes.len();
+    bytes.truncate(len - 1);
+
+    let mut reader = TranscriptReader::open(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.records(), 2);
+    assert_eq!(reader.index().len(), 4);
+    assert_eq!(reader.records_end(), signature);
+    assert_eq!(reader.check_signatures(&verifying_key).ok(), Some(false));
+
+    assert!(TranscriptReader::open(Cursor::new(vec![0u8; 64])).is_err());
+}
+
+#[test]
+fn transcript_signatures_cover_everything() {
+    use std::io::Cursor;
+
+    let header = TranscriptHeader {
+        config: Config::default(),
+        params: CeremonyParams::universal(4, &Config::default())
+    };
+
+    let rng = &mut ::rand::thread_rng();
+    let key = SigningKey::new(rng);
+    let verifying_key = key.verifying_key();
+
+    let mut writer = TranscriptWriter::create(Cursor::new(vec![]), &header, key).unwrap();
+    writer.record(Section::Commitments, |w| encode_into(&vec![1u8, 2, 3], w, Infinite)).unwrap();
+    writer.record(Section::Contribution(1, 0), |w| encode_into(&42u64, w, Infinite)).unwrap();
+    let commitments = writer.index[1].offset as usize;
+    let contribution = writer.index[3].offset as usize;
+    let bytes = writer.finish().unwrap().into_inner();
+
+    let check = |bytes: Vec<u8>, key: &CoordinatorKey| {
+        TranscriptReader::open(Cursor::new(bytes)).unwrap().check_signatures(key)
+    };
+
+    assert_eq!(check(bytes.clone(), &verifying_key).ok(), Some(true));
+    assert!(check(bytes.clone(), &SigningKey::new(rng).verifying_key()).is_err());
+
+    // Changing any record breaks the signatures after it.
+    for &offset in &[commitments, contribution] {
+        let mut tampered = bytes.clone();
+        tampered[offset] ^= 1;
+        assert!(check(tampered, &verifying_key).is_err());
+    }
+}
+
+#[test]
+fn transcript_resumes_after_torn_record() {
+    use std::env;
+    use std::fs::OpenOptions;
+
+    let header = TranscriptHeader {
+        config: Config::default(),
+        params: CeremonyParams::universal(4, &Config::default())
+    };
+
+    let path = env::temp_dir().join("mpc-test-transcript");
+    let key = SigningKey::new(&mut ::rand::thread_rng());
+    let verifying_key = key.verifying_key();
+    let mut saved = vec![];
+    key.save(&mut saved).unwrap();
+
+    {
+        let mut writer = TranscriptWriter::create(File::create(&path).unwrap(), &header, key).unwrap();
+        writer.record(Section::Commitments, |w| encode_into(&vec![1u8, 2, 3], w, Infinite)).unwrap();
+
+        // The coordinator dies halfway through a contribution's payload.
+        assert!(writer.record(Section::Contribution(1, 0), |w| {
+            try!(encode_into(&vec![7u8; 1000], w, Infinite));
+
+            Err(EncodingError::SizeLimit)
+        }).is_err());
+    }
+
+    let mut reader = TranscriptReader::open(File::open(&path).unwrap()).unwrap();
+    assert!(!reader.is_complete());
+    assert_eq!(reader.records(), 1);
+    assert!(!reader.contains(Section::Contribution(1, 0)));
+    assert_eq!(reader.check_signatures(&verifying_key).ok(), Some(true));
+
+    let f = OpenOptions::new().read(true).write(true).open(&path).unwrap();
+    let key = SigningKey::load(&mut &saved[..]).unwrap();
+    let mut writer = TranscriptWriter::resume(f, &mut reader, key).unwrap();
+    writer.record(Section::Contribution(1, 0), |w| encode_into(&42u64, w, Infinite)).unwrap();
+    writer.finish().unwrap();
+
+    let mut reader = TranscriptReader::open(File::open(&path).unwrap()).unwrap();
+    assert!(reader.is_complete());
+    assert_eq!(reader.records(), 2);
+    assert_eq!(reader.check_signatures(&verifying_key).ok(), Some(true));
+    let contribution: u64 = decode_from(reader.seek(Section::Contribution(1, 0)).unwrap(), Infinite).unwrap();
+    assert_eq!(contribution, 42);
+}
+
+#[test]
+fn params_bind_initial_hashes() {
+    let params = CeremonyParams {
+        scheme: ProofSystem::Pghr13,
+        d: 4,
+        num_vars: 3,
+        num_inputs: 1,
+        omega: Fr::one(),
+        cs_hash: Digest256::from(&"test").unwrap(),
+        powers: 5,
+        accumulator: None
+    };
+
+    let mut other = params.clone();
+    other.cs_hash = Digest256::from(&"other").unwrap();
+
+    let mut groth16 = params.clone();
+    groth16.scheme = ProofSystem::Groth16;
+
+    let mut accumulated = params.clone();
+    accumulated.accumulator = Some(Digest256::from(&"accumulator").unwrap());
+
+    let commitments = vec![Digest256::from(&"player").unwrap()];
+
+    assert!(params.hash_of_commitments(&commitments) == params.hash_of_commitments(&commitments));
+    assert!(params.hash_of_commitments(&commitments) != other.hash_of_commitments(&commitments));
+    assert!(params.hash_of_commitments(&commitments) != groth16.hash_of_commitments(&commitments));
+    assert!(params.hash_of_commitments(&commitments) != accumulated.hash_of_commitments(&commitments));
+    assert!(params.initial_message_hash(&commitments) != other.initial_message_hash(&commitments));
+    assert!(params.initial_message_hash(&commitments) != Digest256::from(&commitments).unwrap());
+}
+
+#[test]
+fn params_bind_cs_file() {
+    use std::env;
+    use protocol::{R1CS, Constraint};
+
+    // B has more variables than A, so the reduction swaps them.
+    let r1cs = R1CS {
+        num_inputs: 1,
+        num_aux: 2,
+        constraints: (0..6).map(|i| {
+            Constraint {
+                a: vec![(1, Fr::one())],
+                b: vec![(i % 4, Fr::one()), (3, Fr::one())],
+                c: vec![(2, Fr::one())]
+            }
+        }).collect()
+    };
+
+    let path = env::temp_dir().join("mpc-test-r1cs");
+    let path = path.to_str().unwrap();
+    r1cs.to_file(path).unwrap();
+
+    let params = CeremonyParams::new(&QAP::new(R1CS::from_file(path).unwrap()).unwrap(), None, &Config::default());
+
+    let mut f = File::open(path).unwrap();
+    assert!(params.cs_hash == R1CS::read(&mut f).unwrap().hash());
+    assert!(params.cs_hash == Digest256::from_reader(&mut File::open(path).unwrap()));
+
+    // The dummy constraint system is the same every time.
+    let dummy = CeremonyParams::new(&QAP::new(R1CS::dummy()).unwrap(), None, &Config::default());
+    assert!(dummy.cs_hash == R1CS::dummy().hash());
+    assert!(dummy == CeremonyParams::new(&QAP::new(R1CS::dummy()).unwrap(), None, &Config::default()));
+}
@@ -0,0 +1,489 @@
+//! The connections between the network machines and the coordinator.
+//!
+//! The coordinator listens on a `Transport` and the network machines
+//! connect to it, after which both ends only see a `Connection` they
+//! read from and write to. TCP is what a real ceremony uses, Unix
+//! sockets suit setups where everything runs on one host, and the
+//! in-memory transport runs the protocol within a single process.
+//!
+//! Connections are never used as they are, but to carry the secure
+//! channel in `channel.rs`.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, TcpListener, Shutdown};
+#[cfg(unix)]
+use std::os::unix::net::{UnixStream, UnixListener};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender, Receiver, RecvTimeoutError};
+use std::cell::Cell;
+use std::time::Duration;
+
+pub trait Transport: Clone + Send + 'static {
+    type Connection: Connection;
+    type Listener: Listener<Connection = Self::Connection>;
+
+    fn connect(&self, addr: &str) -> io::Result<Self::Connection>;
+
+    fn listen(&self, addr: &str) -> io::Result<Self::Listener>;
+}
+
+pub trait Connection: Read + Write + Send + 'static {
+    /// Sets the read and write timeout. Reads and writes which time out
+    /// fail, rather than blocking forever.
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+
+    /// Hangs up, in both directions.
+    fn shutdown(&self) -> io::Result<()>;
+
+    /// Describes the remote end, for logging.
+    fn remote(&self) -> String;
+}
+
+pub trait Listener {
+    type Connection: Connection;
+
+    fn accept(&self) -> io::Result<Self::Connection>;
+}
+
+#[derive(Clone)]
+pub struct Tcp;
+
+impl Transport for Tcp {
+    type Connection = TcpStream;
+    type Listener = TcpListener;
+
+    fn connect(&self, addr: &str) -> io::Result<TcpStream> {
+        TcpStream::connect(addr)
+    }
+
+    fn listen(&self, addr: &str) -> io::Result<TcpListener> {
+        TcpListener::bind(addr)
+    }
+}
+
+impl Connection for TcpStream {
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        try!(self.set_read_timeout(timeout));
+
+        self.set_write_timeout(timeout)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        TcpStream::shutdown(self, Shutdown::Both)
+    }
+
+    fn remote(&self) -> String {
+        match self.peer_addr() {
+            Ok(addr) => addr.to_string(),
+            Err(_) => "unknown host".to_string()
+        }
+    }
+}
+
+impl Listener for TcpListener {
+    type Connection = TcpStream;
+
+    fn accept(&self) -> io::Result<TcpStream> {
+        TcpListener::accept(self).map(|(stream, _)| stream)
+    }
+}
+
+#[cfg(unix)]
+#[derive(Clone)]
+pub struct Unix;
+
+#[cfg(unix)]
+impl Transport for Unix {
+    type Connection = UnixStream;
+    type Listener = UnixListener;
+
+    fn connect(&self, addr: &str) -> io::Result<UnixStream> {
+        UnixStream::connect(addr)
+    }
+
+    /// Binds the socket at `addr`, replacing any left behind by a
+    /// previous run of the coordinator. Anything else at `addr` is left
+    /// alone, in case the path was mistyped.
+    fn listen(&self, addr: &str) -> io::Result<UnixListener> {
+        use std::fs;
+        use std::os::unix::fs::FileTypeExt;
+
+        match fs::symlink_metadata(addr) {
+            Ok(ref metadata) if metadata.file_type().is_socket() => try!(fs::remove_file(addr)),
+            Ok(_) => return Err(io::Error::new(io::ErrorKind::AddrInUse, format!("{} exists and is not a socket", addr))),
+            Err(_) => {}
+        }
+
+        UnixListener::bind(addr)
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        try!(self.set_read_timeout(timeout));
+
+        self.set_write_timeout(timeout)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        UnixStream::shutdown(self, Shutdown::Both)
+    }
+
+    fn remote(&self) -> String {
+        "local socket".to_string()
+    }
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Connection = UnixStream;
+
+    fn accept(&self) -> io::Result<UnixStream> {
+        UnixListener::accept(self).map(|(stream, _)| stream)
+    }
+}
+
+/// Connections within the process, between listeners and connectors
+/// sharing (clones of) the same `Memory`. Addresses are just names.
+#[derive(Clone)]
+pub struct Memory {
+    listeners: Arc<Mutex<HashMap<String, Sender<MemoryConnection>>>>
+}
+
+impl Memory {
+    pub fn new() -> Memory {
+        Memory {
+            listeners: Arc::new(Mutex::new(HashMap::new()))
+        }
+    }
+}
+
+impl Transport for Memory {
+    type Connection = MemoryConnection;
+    type Listener = MemoryListener;
+
+    fn connect(&self, addr: &str) -> io::Result<MemoryConnection> {
+        let listeners = self.listeners.lock().unwrap();
+
+        let refused = || io::Error::new(io::ErrorKind::ConnectionRefused, format!("nothing is listening on {}", addr));
+
+        let listener = try!(listeners.get(addr).ok_or_else(&refused));
+        let (ours, theirs) = MemoryConnection::pair();
+        try!(listener.send(theirs).map_err(|_| refused()));
+
+        Ok(ours)
+    }
+
+    fn listen(&self, addr: &str) -> io::Result<MemoryListener> {
+        let mut listeners = self.listeners.lock().unwrap();
+
+        if listeners.contains_key(addr) {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, format!("something is already listening on {}", addr)));
+        }
+
+        let (tx, rx) = channel();
+        listeners.insert(addr.to_string(), tx);
+
+        Ok(MemoryListener {
+            incoming: rx
+        })
+    }
+}
+
+pub struct MemoryListener {
+    incoming: Receiver<MemoryConnection>
+}
+
+impl Listener for MemoryListener {
+    type Connection = MemoryConnection;
+
+    fn accept(&self) -> io::Result<MemoryConnection> {
+        self.incoming.recv().map_err(|_| io::Error::new(io::ErrorKind::NotConnected, "transport is gone"))
+    }
+}
+
+/// One end of an in-memory connection. Every write is sent to the other
+/// end as a chunk, which its reads consume.
+pub struct MemoryConnection {
+    tx: Sender<Vec<u8>>,
+    rx: Receiver<Vec<u8>>,
+    /// The rest of the chunk being read.
+    pending: Vec<u8>,
+    timeout: Cell<Option<Duration>>,
+    /// Shared by both ends, set when either hangs up.
+    closed: Arc<AtomicBool>
+}
+
+impl MemoryConnection {
+    fn pair() -> (MemoryConnection, MemoryConnection) {
+        let (tx1, rx1) = channel();
+        let (tx2, rx2) = channel();
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let a = MemoryConnection {
+            tx: tx1,
+            rx: rx2,
+            pending: vec![],
+            timeout: Cell::new(None),
+            closed: closed.clone()
+        };
+
+        let b = MemoryConnection {
+            tx: tx2,
+            rx: rx1,
+            pending: vec![],
+            timeout: Cell::new(None),
+            closed: closed
+        };
+
+        (a, b)
+    }
+}
+
+impl Read for MemoryConnection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            if self.closed.load(Ordering::SeqCst) {
+                return Ok(0);
+            }
+
+            let chunk = match self.timeout.get() {
+                Some(timeout) => match self.rx.recv_timeout(timeout) {
+                    Ok(chunk) => chunk,
+                    Err(RecvTimeoutError::Timeout) => {
+                        return Err(io::Error::new(io::ErrorKind::TimedOut, "read timed out"));
+                    },
+                    Err(RecvTimeoutError::Disconnected) => vec![]
+                },
+                None => self.rx.recv().unwrap_or(vec![])
+            };
+
+            // An empty chunk is the other end hanging up.
+            if chunk.is_empty() {
+                return Ok(0);
+            }
+
+            self.pending = chunk;
+        }
+
+        let n = ::std::cmp::min(buf.len(), self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+
+        Ok(n)
+    }
+}
+
+impl Write for MemoryConnection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let broken = || io::Error::new(io::ErrorKind::BrokenPipe, "connection closed");
+
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(broken());
+        }
+
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        try!(self.tx.send(buf.to_vec()).map_err(|_| broken()));
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Connection for MemoryConnection {
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.timeout.set(timeout);
+
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.closed.store(true, Ordering::SeqCst);
+
+        // Wakes up the other end if it's waiting to read.
+        let _ = self.tx.send(vec![]);
+
+        Ok(())
+    }
+
+    fn remote(&self) -> String {
+        "in-memory peer".to_string()
+    }
+}
+
+/// Totals of the bytes sent and received over any number of `Metered`
+/// connections.
+pub struct Traffic {
+    sent: AtomicUsize,
+    received: AtomicUsize
+}
+
+impl Traffic {
+    pub fn new() -> Traffic {
+        Traffic {
+            sent: AtomicUsize::new(0),
+            received: AtomicUsize::new(0)
+        }
+    }
+
+    pub fn sent(&self) -> usize {
+        self.sent.load(Ordering::SeqCst)
+    }
+
+    pub fn received(&self) -> usize {
+        self.received.load(Ordering::SeqCst)
+    }
+}
+
+/// A connection which adds what goes through it to a shared `Traffic`.
+pub struct Metered<C: Connection> {
+    inner: C,
+    traffic: Arc<Traffic>
+}
+
+impl<C: Connection> Metered<C> {
+    pub fn new(inner: C, traffic: Arc<Traffic>) -> Metered<C> {
+        Metered {
+            inner: inner,
+            traffic: traffic
+        }
+    }
+}
+
+impl<C: Connection> Read for Metered<C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = try!(self.inner.read(buf));
+        self.traffic.received.fetch_add(n, Ordering::SeqCst);
+
+        Ok(n)
+    }
+}
+
+impl<C: Connection> Write for Metered<C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = try!(self.inner.write(buf));
+        self.traffic.sent.fetch_add(n, Ordering::SeqCst);
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<C: Connection> Connection for Metered<C> {
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.inner.set_timeout(timeout)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.inner.shutdown()
+    }
+
+    fn remote(&self) -> String {
+        self.inner.remote()
+    }
+}
+
+#[test]
+fn memory_transport() {
+    use std::thread;
+
+    let transport = Memory::new();
+    assert!(transport.connect("coordinator").is_err());
+
+    let listener = transport.listen("coordinator").unwrap();
+    assert!(transport.listen("coordinator").is_err());
+
+    let remote = transport.clone();
+    let player = thread::spawn(move || {
+        let mut c = remote.connect("coordinator").unwrap();
+        c.write_all(b"ping").unwrap();
+
+        let mut buf = [0; 5];
+        c.read_exact(&mut buf).unwrap();
+
+        buf
+    });
+
+    let mut c = listener.accept().unwrap();
+    let mut buf = [0; 4];
+    c.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+    c.write_all(b"hello").unwrap();
+
+    assert_eq!(&player.join().unwrap(), b"hello");
+
+    // The player's end is gone now.
+    assert_eq!(c.read(&mut [0]).unwrap(), 0);
+    assert!(c.write_all(b"anyone?").is_err());
+
+    let mut a = transport.connect("coordinator").unwrap();
+    let mut b = listener.accept().unwrap();
+
+    a.set_timeout(Some(Duration::from_millis(10))).unwrap();
+    assert!(a.read(&mut [0]).is_err());
+
+    b.write_all(&[1, 2, 3]).unwrap();
+    let mut buf = [0; 2];
+    a.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [1, 2]);
+    a.read_exact(&mut buf[..1]).unwrap();
+    assert_eq!(buf[0], 3);
+
+    b.shutdown().unwrap();
+    assert_eq!(a.read(&mut [0]).unwrap(), 0);
+    assert!(a.write_all(&[0]).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_listen_replaces_only_sockets() {
+    use std::env;
+    use std::fs::{self, File};
+
+    let path = env::temp_dir().join("mpc-test-socket");
+    let addr = path.to_str().unwrap();
+    let _ = fs::remove_file(addr);
+
+    File::create(addr).unwrap();
+    assert_eq!(Unix.listen(addr).err().map(|e| e.kind()), Some(io::ErrorKind::AddrInUse));
+    assert!(fs::symlink_metadata(addr).unwrap().file_type().is_file());
+    fs::remove_file(addr).unwrap();
+
+    // One left behind by a previous run is replaced.
+    drop(Unix.listen(addr).unwrap());
+    let listener = Unix.listen(addr).unwrap();
+    assert!(Unix.connect(addr).is_ok());
+
+    drop(listener);
+    fs::remove_file(addr).unwrap();
+}
+
+#[test]
+fn metered_connection() {
+    let transport = Memory::new();
+    let listener = transport.listen("coordinator").unwrap();
+    let traffic = Arc::new(Traffic::new());
+
+    let mut a = Metered::new(transport.connect("coordinator").unwrap(), traffic.clone());
+    let mut b = Metered::new(listener.accept().unwrap(), traffic.clone());
+
+    a.write_all(b"ping").unwrap();
+    let mut buf = [0; 4];
+    b.read_exact(&mut buf).unwrap();
+    b.write_all(b"pong!").unwrap();
+
+    assert_eq!(traffic.sent(), 9);
+    assert_eq!(traffic.received(), 4);
+}
@@ -12,158 +12,1169 @@ extern crate byteorder;
 #[macro_use]
 mod protocol;
 
-mod consts;
-use self::consts::*;
+mod exclusion;
+use self::exclusion::*;
 
-use std::fs::File;
+mod transport;
+mod wire;
+use self::wire::MessageType;
+
+mod config;
+use self::config::*;
+
+mod transcript;
+use self::transcript::*;
+
+mod report;
+use self::report::*;
+
+mod attestation;
+use self::attestation::*;
+
+mod metrics;
+use self::metrics::*;
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write, Seek, SeekFrom, BufReader, BufWriter};
+use std::env;
+use std::process;
+use std::time::Instant;
 use protocol::*;
-use snark::*;
+use rustc_serialize::{Encodable, Decodable};
 
 use bincode::SizeLimit::Infinite;
 use bincode::rustc_serialize::{encode_into, decode_from};
 
-pub const THREADS: usize = 128;
+const THREADS: usize = 128;
 
-fn main() {
-    let mut f = File::open("transcript").unwrap();
+/// Length of the encoded ihash which ends every contribution.
+const IHASH_LEN: u64 = 32;
 
-    let cs = {
-        if USE_DUMMY_CS {
-            CS::dummy()
-        } else {
-            CS::from_file()
+/// Unwraps the result of reading the transcript, or fails verification
+/// because it couldn't be read.
+macro_rules! read {
+    ($report:expr, $result:expr) => (
+        match $result {
+            Ok(v) => v,
+            Err(e) => return Err($report.fail(Failure::Transcript, format!("Could not read the transcript: {}", e)))
         }
-    };
+    )
+}
+
+/// Records a failure, and stops verification there unless every failure
+/// is being collected.
+macro_rules! fail {
+    ($report:expr, $class:expr, $($arg:tt)*) => ({
+        let failure = $report.fail($class, format!($($arg)*));
+
+        if !$report.collect_all {
+            return Err(failure);
+        }
+    })
+}
+
+/// A byte range of a file holding the encoding of something which may
+/// be too large to keep in memory, like the contents of a stage.
+#[derive(Clone)]
+struct Location {
+    path: String,
+    offset: u64,
+    len: u64
+}
+
+impl Location {
+    fn record(path: &str, entry: &IndexEntry) -> Location {
+        Location {
+            path: path.to_string(),
+            offset: entry.offset,
+            len: entry.len
+        }
+    }
+
+    fn open(&self) -> Result<io::Take<BufReader<File>>, TranscriptError> {
+        let mut f = try!(File::open(&self.path));
+        try!(f.seek(SeekFrom::Start(self.offset)));
+
+        Ok(BufReader::new(f).take(self.len))
+    }
+
+    fn decode<T: Decodable>(&self) -> Result<T, TranscriptError> {
+        Ok(try!(decode_from(&mut try!(self.open()), Infinite)))
+    }
+
+    fn digest(&self) -> Result<Digest256, TranscriptError> {
+        Ok(Digest256::from_reader(&mut try!(self.open())))
+    }
+
+    /// The stage contents of a contribution `record`, which `f` has
+    /// been read up to.
+    fn stage_in(&self, f: &io::Take<BufReader<File>>) -> Result<Location, TranscriptError> {
+        if f.limit() < IHASH_LEN {
+            return Err(TranscriptError::Corrupt("contribution is truncated"));
+        }
+
+        Ok(Location {
+            path: self.path.clone(),
+            offset: self.offset + (self.len - f.limit()),
+            len: f.limit() - IHASH_LEN
+        })
+    }
+
+    /// The ihash which ends a contribution `record`, for when the
+    /// contribution can't be read up to it.
+    fn ihash(&self) -> Result<Digest256, TranscriptError> {
+        if self.len < IHASH_LEN {
+            return Err(TranscriptError::Corrupt("contribution is truncated"));
+        }
+
+        Location {
+            path: self.path.clone(),
+            offset: self.offset + self.len - IHASH_LEN,
+            len: IHASH_LEN
+        }.decode()
+    }
+}
+
+/// Writes the initial contents of a stage, which the verifier computes
+/// itself, to disk so that the first contribution can be streamed
+/// against it like any other.
+fn spill<T: Encodable>(path: String, contents: &T) -> Location {
+    {
+        let mut f = BufWriter::new(File::create(&path).unwrap());
+        encode_into(contents, &mut f, Infinite).unwrap();
+        f.flush().unwrap();
+    }
+
+    let len = fs::metadata(&path).unwrap().len();
+
+    Location {
+        path: path,
+        offset: 0,
+        len: len
+    }
+}
+
+fn encoded<T: Encodable>(obj: &T) -> Vec<u8> {
+    let mut contents = vec![];
+    encode_into(obj, &mut contents, Infinite).unwrap();
+
+    contents
+}
+
+/// Hashes a stage along with whatever is encoded around it, exactly as
+/// `digest256_from_parts!` would if the stage were in memory.
+fn digest_around(before: &[u8], stage: &Location, after: &[u8]) -> Result<Digest256, TranscriptError> {
+    Ok(Digest256::from_reader(&mut before.chain(try!(stage.open())).chain(after)))
+}
+
+/// Reads the ihash which ends a contribution, making sure nothing
+/// follows it.
+fn read_ihash(f: &mut io::Take<BufReader<File>>) -> Result<Digest256, TranscriptError> {
+    let ihash: Digest256 = try!(decode_from(f, Infinite));
+
+    if f.limit() != 0 {
+        return Err(TranscriptError::Corrupt("contribution has trailing data"));
+    }
+
+    Ok(ihash)
+}
+
+fn find_contribution(
+    report: &mut Report,
+    transcript: &TranscriptReader<File>,
+    path: &str,
+    stage: u8,
+    player: usize
+) -> Result<Location, Failure>
+{
+    match transcript.entry(Section::Contribution(stage, player)) {
+        Some(entry) => Ok(Location::record(path, entry)),
+        None => Err(report.fail(Failure::Transcript, format!("Player {} has no record in stage{}", player+1, stage)))
+    }
+}
+
+/// The turns taken in a stage: its players, in the order the coordinator
+/// scheduled them, followed by the beacon if there is one. The players
+/// must be exactly `players`.
+fn stage_turns(
+    report: &mut Report,
+    transcript: &TranscriptReader<File>,
+    stage: u8,
+    players: Vec<usize>,
+    num_players: usize,
+    total: usize
+) -> Result<Vec<usize>, Failure>
+{
+    let mut turns: Vec<usize> = transcript.order(stage).into_iter().filter(|&i| i < num_players).collect();
+
+    let mut sorted = turns.clone();
+    sorted.sort();
+
+    if sorted != players {
+        return Err(report.fail(Failure::Transcript, format!("The turns taken in stage{} are not those of its players", stage)));
+    }
+
+    if total > num_players {
+        turns.push(num_players);
+    }
+
+    Ok(turns)
+}
+
+/// Verifies the pairing checks of every contribution to a stage at
+/// once. Only if that fails are they verified one by one, to find out
+/// whose contribution is invalid.
+fn check_batches(report: &mut Report, stage: u8, batches: &[(usize, Batch)]) -> Result<(), Failure> {
+    let mut all = Batch::new();
+
+    for &(_, ref batch) in batches {
+        all.merge(batch);
+    }
+
+    if all.verify() {
+        for &(player, _) in batches {
+            report.check(player, stage, "pairing checks", true);
+        }
+
+        return Ok(());
+    }
+
+    let mut found = false;
 
-    let num_players: usize = decode_from(&mut f, Infinite).unwrap();
+    for &(player, ref batch) in batches {
+        if !report.check(player, stage, "pairing checks", batch.verify()) {
+            found = true;
+            fail!(report, Failure::Transform, "Invalid stage{} transformation from player {}", stage, player+1);
+        }
+    }
+
+    if !found {
+        fail!(report, Failure::Transform, "Invalid stage{} transformation", stage);
+    }
+
+    Ok(())
+}
+
+/// What the verifier can tell of whether a player really misbehaved
+/// the way an exclusion says.
+#[derive(Clone, Copy, PartialEq)]
+enum Evidence {
+    Supported,
+    Unsupported,
+    /// There is nothing to check, like when the player was unresponsive.
+    Unconfirmed
+}
+
+impl From<bool> for Evidence {
+    fn from(supported: bool) -> Evidence {
+        if supported { Evidence::Supported } else { Evidence::Unsupported }
+    }
+}
+
+/// Checks an exclusion record for the given player's turn. `evidence`
+/// decides whether the recorded misbehavior really happened. Unconfirmed
+/// exclusions are only accepted with `--accept-unconfirmed`.
+fn check_exclusion<S: Scheme, F: FnOnce(&Misbehavior<S>) -> Evidence>(
+    config: &Config,
+    report: &mut Report,
+    roster: &mut Roster,
+    exclusion: &Exclusion<S>,
+    player: usize,
+    stage: u8,
+    evidence: F
+) -> Result<(), Failure>
+{
+    if exclusion.player != player || exclusion.stage != stage {
+        return Err(report.fail(Failure::Exclusion, format!("Exclusion record for player {} in stage{} is out of place", player+1, stage)));
+    }
+
+    println!("Player {} was excluded during stage{}: {}", player+1, stage, exclusion.reason.describe());
+    report.excluded(player, stage, exclusion.reason.describe());
+
+    match evidence(&exclusion.reason) {
+        Evidence::Supported => {
+            report.check(player, stage, "exclusion evidence", true);
+        },
+        Evidence::Unsupported => {
+            report.check(player, stage, "exclusion evidence", false);
+            fail!(report, Failure::Exclusion, "Exclusion of player {} during stage{} is not supported by its evidence", player+1, stage);
+        },
+        Evidence::Unconfirmed => {
+            if report.check(player, stage, "exclusion unconfirmed", config.accept_unconfirmed) {
+                println!("Exclusion of player {} during stage{} is unconfirmed, but accepted", player+1, stage);
+            } else {
+                fail!(report, Failure::Unconfirmed,
+                      "Exclusion of player {} during stage{} cannot be confirmed; pass --accept-unconfirmed to accept it", player+1, stage);
+            }
+        }
+    }
+
+    // Without a coherent roster there is nothing sensible to check
+    // the rest of the transcript against, even when collecting failures.
+    if let Err(e) = roster.exclude(exclusion) {
+        return Err(report.fail(Failure::Exclusion, format!("Invalid exclusion: {}", e)));
+    }
+
+    Ok(())
+}
+
+/// Checks a rejected contribution that the coordinator kept in quarantine
+/// against the hash recorded in the transcript. If the quarantined file
+/// isn't available, the exclusion is unconfirmed.
+fn check_quarantine<F>(
+    stage: u8,
+    player: usize,
+    expected: &Digest256,
+    is_invalid: F
+) -> Evidence
+    where F: FnOnce(&mut BufReader<File>) -> Result<bool, TranscriptError>
+{
+    let path = quarantine_path(stage, player);
+
+    match File::open(&path) {
+        Ok(mut q) => {
+            if Digest256::from_reader(&mut q) != *expected {
+                println!("Quarantined contribution {} does not match the transcript", path);
+                return Evidence::Unsupported;
+            }
+
+            let mut q = match File::open(&path) {
+                Ok(q) => BufReader::new(q),
+                Err(_) => return Evidence::Unconfirmed
+            };
+
+            match is_invalid(&mut q) {
+                Ok(is_invalid) => is_invalid.into(),
+                Err(e) => {
+                    println!("Could not read quarantined contribution {}: {}", path, e);
+                    Evidence::Unsupported
+                }
+            }
+        },
+        Err(_) => {
+            println!("Quarantined contribution {} is not available", path);
+            Evidence::Unconfirmed
+        }
+    }
+}
+
+/// Checks the public key and nizks a player's first contribution starts
+/// with: that the key is the one they committed to, or the one derived
+/// from the beacon's value, and that they know its secrets.
+fn check_pubkey<S: Scheme>(
+    report: &mut Report,
+    stage: u8,
+    player: usize,
+    num_players: usize,
+    expected: &Digest256,
+    hash_of_commitments: &Digest512,
+    pubkey: &S::PublicKey,
+    nizks: &S::PublicKeyNizks
+) -> Result<(), Failure>
+{
+    if player == num_players {
+        if !report.check(player, stage, "beacon secrets", pubkey.hash() == *expected) {
+            fail!(report, Failure::Commitment, "The beacon's public key is not the one derived from its value");
+        }
+    } else if !report.check(player, stage, "commitment", pubkey.hash() == *expected) {
+        fail!(report, Failure::Commitment, "Invalid commitment from player {}", player+1);
+    }
+
+    let mut nizks_valid = true;
+    for (name, valid) in nizks.verify_each(pubkey, hash_of_commitments) {
+        nizks_valid &= report.check(player, stage, &format!("nizk {}", name), valid);
+    }
+
+    if !nizks_valid {
+        fail!(report, Failure::Nizks, "Invalid nizks from player {}", player+1);
+    }
+
+    Ok(())
+}
+
+/// Whether a quarantined message fails to decode as a `T`, which is
+/// what the coordinator excludes a player for.
+fn undecodable<T: Decodable>(q: &mut BufReader<File>) -> Result<bool, TranscriptError> {
+    Ok(decode_from::<_, T>(q, Infinite).is_err())
+}
+
+/// The beacon of a ceremony which has one. Whether there is a beacon,
+/// and how often its value is hashed, were fixed in the header before
+/// anyone took a turn, so the coordinator can't choose either once it
+/// has seen the contributions.
+fn read_beacon<R: Read + Seek>(
+    report: &mut Report,
+    transcript: &mut TranscriptReader<R>,
+    ceremony: &Config
+) -> Result<Option<Beacon>, Failure>
+{
+    if transcript.contains(Section::Beacon) != ceremony.beacon {
+        return Err(report.fail(Failure::Transcript, format!("The ceremony was started {} a beacon, but the transcript has {}",
+                                                            if ceremony.beacon { "with" } else { "without" },
+                                                            if ceremony.beacon { "none" } else { "one" })));
+    }
+
+    if !ceremony.beacon {
+        return Ok(None);
+    }
+
+    let beacon: Beacon = read!(report, decode_from(read!(report, transcript.seek(Section::Beacon)), Infinite));
+    println!("Beacon: {} ({} iterations)", beacon.to_hex(), beacon.iterations);
+
+    if beacon.iterations != ceremony.beacon_iterations {
+        return Err(report.fail(Failure::Transcript, format!("The beacon is hashed {} times, but the ceremony was started with {}",
+                                                            beacon.iterations, ceremony.beacon_iterations)));
+    }
+
+    Ok(Some(beacon))
+}
+
+/// Verifies the commitments and every player's turn in each stage,
+/// returning the final contents of the stages. Without a QAP, the
+/// ceremony is a universal one, which ends with stage 1.
+fn verify_stages<S: Scheme>(
+    config: &Config,
+    report: &mut Report,
+    metrics: &Metrics,
+    transcript: &mut TranscriptReader<File>,
+    qap: Option<&QAP>,
+    initial_stage1: &Stage1Contents,
+    ceremony: &Config,
+    params: &CeremonyParams
+) -> Result<(Location, Option<(Location, Location)>), Failure>
+{
+    // Contributions are never decoded in full; their stage contents
+    // are streamed from the transcript in windows of this many elements.
+    let window = config.verify_window;
+
+    let num_players = ceremony.players;
     println!("Number of players: {}", num_players);
 
-    let mut commitments = vec![];
-    let mut pubkeys = vec![];
-    for i in 0..num_players {
-        let comm: Digest256 = decode_from(&mut f, Infinite).unwrap();
-        commitments.push(comm);
+    let commitments: Vec<Digest256> = read!(report, decode_from(read!(report, transcript.seek(Section::Commitments)), Infinite));
+
+    if commitments.len() != num_players {
+        return Err(report.fail(Failure::Transcript, format!("Transcript has {} commitments for {} players", commitments.len(), num_players)));
+    }
+
+    for (i, comm) in commitments.iter().enumerate() {
         println!("Player {} commitment: {}", i+1, comm.to_string());
+        report.add_player(comm);
     }
 
+    let mut records = 1;
+
     // Hash of all the commitments.
-    let hash_of_commitments = Digest512::from(&commitments).unwrap();
+    let hash_of_commitments = params.hash_of_commitments(&commitments);
+
+    // The beacon contributes after the players, with secrets we can
+    // recompute from its value, so its public key must be exactly the
+    // one they give.
+    let mut expected = commitments.clone();
+
+    if let Some(beacon) = try!(read_beacon(report, transcript, ceremony)) {
+        records += 1;
+
+        let (_, pubkey, _) = beacon.contribution::<S>(&hash_of_commitments);
+        println!("Beacon public key hash: {}", pubkey.hash().to_string());
+        report.add_player(&pubkey.hash());
+        expected.push(pubkey.hash());
+    }
+
+    let total = expected.len();
+    let mut pubkeys: Vec<Option<S::PublicKey>> = vec![None; total];
 
     // Hash of the last message
-    let mut last_message_hash = Digest256::from(&commitments).unwrap();
+    let mut last_message_hash = params.initial_message_hash(&commitments);
+
+    let mut roster = Roster::new(num_players, ceremony.min_players);
 
-    let mut stage1 = Stage1Contents::new(&cs);
+    let start = Instant::now();
+    let initial = spill(format!("{}.stage1", config.transcript), initial_stage1);
+    let mut stage1 = initial.clone();
+    let mut contributions = 0;
+    let mut batches = vec![];
 
-    for i in 0..num_players {
+    // A ceremony started from an accumulator takes its stage1 from it,
+    // which the header's accumulator hash pins, so nobody has a turn in
+    // stage1, and the first contributions reveal the public keys in
+    // stage2 instead.
+    let accumulated = params.accumulator.is_some();
+
+    let turns = if accumulated {
+        try!(stage_turns(report, transcript, 1, vec![], num_players, num_players))
+    } else {
+        try!(stage_turns(report, transcript, 1, (0..num_players).collect(), num_players, total))
+    };
+
+    for i in turns {
         let expected_ihash = {
-            let h = digest256_from_parts!(
-                hash_of_commitments,
-                stage1,
-                last_message_hash
-            );
+            let h = read!(report, digest_around(
+                &encoded(&hash_of_commitments),
+                &stage1,
+                &encoded(&last_message_hash)
+            ));
             println!("Player {} hash of disk A: {}", i+1, h.to_string());
+            report.disc(i, "A", &h);
             h
         };
-        let pubkey: PublicKey = decode_from(&mut f, Infinite).unwrap();
 
-        if pubkey.hash() != commitments[i] {
-            panic!("Invalid commitment from player {}", i);
-        }
+        records += 1;
 
-        let nizks: PublicKeyNizks = decode_from(&mut f, Infinite).unwrap();
+        if i < num_players && transcript.contains(Section::Exclusion(1, i)) {
+            let exclusion: Exclusion<S> = read!(report, decode_from(read!(report, transcript.seek(Section::Exclusion(1, i))), Infinite));
 
-        if !nizks.is_valid(&pubkey, &hash_of_commitments) {
-            panic!("Invalid nizks from player {}", i);
-        }
+            try!(check_exclusion(config, report, &mut roster, &exclusion, i, 1, |reason| {
+                match *reason {
+                    Misbehavior::BadCommitment(ref pubkey) => {
+                        (pubkey.hash() != commitments[i]).into()
+                    },
+                    Misbehavior::InvalidNizks(ref pubkey, ref nizks) => {
+                        (pubkey.hash() == commitments[i] &&
+                         !nizks.is_valid(pubkey, &hash_of_commitments)).into()
+                    },
+                    Misbehavior::InvalidTransform(ref h) => {
+                        check_quarantine(1, i, h, |q| {
+                            let pubkey: S::PublicKey = try!(decode_from(q, Infinite));
+                            let nizks: S::PublicKeyNizks = try!(decode_from(q, Infinite));
+
+                            Ok(pubkey.hash() == commitments[i] &&
+                               nizks.is_valid(&pubkey, &hash_of_commitments) &&
+                               !try!(Stage1Contents::verify_transform_streaming(&mut try!(stage1.open()), q, &pubkey, window)))
+                        })
+                    },
+                    Misbehavior::Malformed(kind, ref h) => {
+                        check_quarantine(1, i, h, |q| {
+                            match MessageType::from_kind(kind) {
+                                Some(MessageType::PublicKey) => undecodable::<S::PublicKey>(q),
+                                Some(MessageType::PublicKeyNizks) => undecodable::<S::PublicKeyNizks>(q),
+                                Some(MessageType::Stage1) => undecodable::<Stage1Contents>(q),
+                                Some(MessageType::Ihash) => undecodable::<Digest256>(q),
+                                _ => Ok(false)
+                            }
+                        })
+                    },
+                    // The coordinator never read the message, so there is
+                    // nothing to check but that it went over the limit.
+                    Misbehavior::Oversized(length, limit) => {
+                        if length > limit { Evidence::Unconfirmed } else { Evidence::Unsupported }
+                    },
+                    // The verifier can't know how long a turn took, or why
+                    // the operator excluded a player, only the coordinator
+                    // can.
+                    Misbehavior::Unresponsive | Misbehavior::OutOfTime(_) | Misbehavior::Operator => Evidence::Unconfirmed
+                }
+            }));
 
-        let new_stage: Stage1Contents = decode_from(&mut f, Infinite).unwrap();
-        if !new_stage.verify_transform(&stage1, &pubkey) {
-            panic!("Invalid stage1 transformation from player {}", i);
+            continue;
         }
 
-        let ihash: Digest256 = decode_from(&mut f, Infinite).unwrap();
-        assert!(ihash == expected_ihash);
+        let record = try!(find_contribution(report, transcript, &config.transcript, 1, i));
+        let mut f = read!(report, record.open());
+
+        let pubkey: S::PublicKey = read!(report, decode_from(&mut f, Infinite));
+        let nizks: S::PublicKeyNizks = read!(report, decode_from(&mut f, Infinite));
+        try!(check_pubkey::<S>(report, 1, i, num_players, &expected[i], &hash_of_commitments, &pubkey, &nizks));
+
+        let new_stage = read!(report, record.stage_in(&f));
+        let mut prev = read!(report, stage1.open());
+        let mut batch = Batch::new();
+        let began = Instant::now();
+        let well_formed = match Stage1Contents::verify_transform_batched(&mut prev, &mut f, &pubkey, window, &mut batch) {
+            Ok(well_formed) => Some(well_formed),
+            Err(e) => {
+                fail!(report, Failure::Malformed, "Could not decode the stage1 contribution of player {}: {}", i+1, e);
+                None
+            }
+        };
+        metrics.observe(&VERIFY_TRANSFORM_SECONDS, &[("stage", "1")], seconds(began.elapsed()));
+
+        let ihash = if report.check(i, 1, "transformation", well_formed == Some(true)) {
+            batches.push((i, batch));
+            read!(report, read_ihash(&mut f))
+        } else {
+            // A record that can't be decoded has already failed as such.
+            if well_formed.is_some() {
+                fail!(report, Failure::Transform, "Invalid stage1 transformation from player {}", i+1);
+            }
+            read!(report, record.ihash())
+        };
+
+        // The contribution is checked against the previous one in the
+        // transcript either way, so a broken link in the chain doesn't
+        // stop the rest of it from being checked.
+        if !report.check(i, 1, "ihash", ihash == expected_ihash) {
+            fail!(report, Failure::Chain, "Player {} did not transform the stage1 they were given", i+1);
+        }
 
         {
-            last_message_hash = digest256_from_parts!(
-                pubkey,
-                nizks,
-                new_stage,
-                ihash
-            );
+            last_message_hash = read!(report, record.digest());
             println!("Player {} hash of disk B: {}", i+1, last_message_hash.to_string());
+            report.disc(i, "B", &last_message_hash);
         }
 
         stage1 = new_stage;
-        pubkeys.push(pubkey);
+        pubkeys[i] = Some(pubkey);
+
+        // The beacon adds no secrecy, so it doesn't count.
+        if i < num_players {
+            contributions += 1;
+        }
+    }
+
+    if contributions == 0 && !accumulated {
+        return Err(report.fail(Failure::Transcript, "Nobody contributed to stage1".to_string()));
     }
 
-    let mut stage2 = Stage2Contents::new(&cs, &stage1);
+    try!(check_batches(report, 1, &batches));
+    let _ = fs::remove_file(&initial.path);
+    report.time("stage1", start);
+
+    let qap = match qap {
+        Some(qap) => qap,
+        None => {
+            if records != transcript.records() {
+                fail!(report, Failure::Transcript, "Transcript contains unexpected records");
+            }
 
-    for i in 0..num_players {
+            return Ok((stage1, None));
+        }
+    };
+
+    let start = Instant::now();
+    let initial = {
+        let stage1: Stage1Contents = read!(report, stage1.decode());
+
+        spill(format!("{}.stage2", config.transcript), &S::stage2(qap, &stage1))
+    };
+    let mut stage2 = initial.clone();
+    let mut contributions = 0;
+    let mut batches = vec![];
+
+    let active = (0..num_players).filter(|&i| roster.is_active(i)).collect();
+    let turns = try!(stage_turns(report, transcript, 2, active, num_players, total));
+
+    for i in turns {
         let expected_ihash = {
-            let h = digest256_from_parts!(
-                stage2,
-                last_message_hash
-            );
+            // Disc C starts with the hash of commitments for a player
+            // who reveals their public key with it.
+            let before = if accumulated { encoded(&hash_of_commitments) } else { vec![] };
+            let h = read!(report, digest_around(
+                &before,
+                &stage2,
+                &encoded(&last_message_hash)
+            ));
             println!("Player {} hash of disk C: {}", i+1, h.to_string());
+            report.disc(i, "C", &h);
 
             h
         };
 
-        let new_stage: Stage2Contents = decode_from(&mut f, Infinite).unwrap();
-        if !new_stage.verify_transform(&stage2, &pubkeys[i]) {
-            panic!("Invalid stage2 transformation from player {}", i);
+        records += 1;
+
+        if i < num_players && transcript.contains(Section::Exclusion(2, i)) {
+            let exclusion: Exclusion<S> = read!(report, decode_from(read!(report, transcript.seek(Section::Exclusion(2, i))), Infinite));
+
+            try!(check_exclusion(config, report, &mut roster, &exclusion, i, 2, |reason| {
+                match *reason {
+                    Misbehavior::BadCommitment(ref pubkey) if accumulated => {
+                        (pubkey.hash() != commitments[i]).into()
+                    },
+                    Misbehavior::InvalidNizks(ref pubkey, ref nizks) if accumulated => {
+                        (pubkey.hash() == commitments[i] &&
+                         !nizks.is_valid(pubkey, &hash_of_commitments)).into()
+                    },
+                    Misbehavior::InvalidTransform(ref h) => {
+                        check_quarantine(2, i, h, |q| {
+                            let revealed: S::PublicKey;
+                            let pubkey = match pubkeys[i] {
+                                Some(ref pubkey) => pubkey,
+                                None => {
+                                    revealed = try!(decode_from(q, Infinite));
+                                    let nizks: S::PublicKeyNizks = try!(decode_from(q, Infinite));
+
+                                    if revealed.hash() != commitments[i] || !nizks.is_valid(&revealed, &hash_of_commitments) {
+                                        return Ok(false);
+                                    }
+
+                                    &revealed
+                                }
+                            };
+
+                            Ok(!try!(<S::Stage2 as Stage>::verify_transform_streaming(&mut try!(stage2.open()), q, pubkey, window)))
+                        })
+                    },
+                    Misbehavior::Malformed(kind, ref h) => {
+                        check_quarantine(2, i, h, |q| {
+                            match MessageType::from_kind(kind) {
+                                Some(MessageType::PublicKey) if accumulated => undecodable::<S::PublicKey>(q),
+                                Some(MessageType::PublicKeyNizks) if accumulated => undecodable::<S::PublicKeyNizks>(q),
+                                Some(MessageType::Stage2) => undecodable::<S::Stage2>(q),
+                                Some(MessageType::Ihash) => undecodable::<Digest256>(q),
+                                _ => Ok(false)
+                            }
+                        })
+                    },
+                    Misbehavior::Oversized(length, limit) => {
+                        if length > limit { Evidence::Unconfirmed } else { Evidence::Unsupported }
+                    },
+                    Misbehavior::Unresponsive | Misbehavior::OutOfTime(_) | Misbehavior::Operator => Evidence::Unconfirmed,
+                    _ => Evidence::Unsupported
+                }
+            }));
+
+            continue;
         }
 
-        let ihash: Digest256 = decode_from(&mut f, Infinite).unwrap();
-        assert!(ihash == expected_ihash);
+        let record = try!(find_contribution(report, transcript, &config.transcript, 2, i));
+        let mut f = read!(report, record.open());
+
+        if accumulated {
+            let pubkey: S::PublicKey = read!(report, decode_from(&mut f, Infinite));
+            let nizks: S::PublicKeyNizks = read!(report, decode_from(&mut f, Infinite));
+            try!(check_pubkey::<S>(report, 2, i, num_players, &expected[i], &hash_of_commitments, &pubkey, &nizks));
+
+            pubkeys[i] = Some(pubkey);
+        }
+
+        let pubkey = pubkeys[i].as_ref().unwrap();
+        let new_stage = read!(report, record.stage_in(&f));
+        let mut prev = read!(report, stage2.open());
+        let mut batch = Batch::new();
+        let began = Instant::now();
+        let well_formed = match <S::Stage2 as Stage>::verify_transform_batched(&mut prev, &mut f, pubkey, window, &mut batch) {
+            Ok(well_formed) => Some(well_formed),
+            Err(e) => {
+                fail!(report, Failure::Malformed, "Could not decode the stage2 contribution of player {}: {}", i+1, e);
+                None
+            }
+        };
+        metrics.observe(&VERIFY_TRANSFORM_SECONDS, &[("stage", "2")], seconds(began.elapsed()));
+
+        let ihash = if report.check(i, 2, "transformation", well_formed == Some(true)) {
+            batches.push((i, batch));
+            read!(report, read_ihash(&mut f))
+        } else {
+            if well_formed.is_some() {
+                fail!(report, Failure::Transform, "Invalid stage2 transformation from player {}", i+1);
+            }
+            read!(report, record.ihash())
+        };
+
+        if !report.check(i, 2, "ihash", ihash == expected_ihash) {
+            fail!(report, Failure::Chain, "Player {} did not transform the stage2 they were given", i+1);
+        }
 
         {
-            last_message_hash = digest256_from_parts!(
-                new_stage,
-                ihash
-            );
+            last_message_hash = read!(report, record.digest());
 
             println!("Player {} hash of disk D: {}", i+1, last_message_hash.to_string());
+            report.disc(i, "D", &last_message_hash);
         }
 
         stage2 = new_stage;
+
+        if i < num_players {
+            contributions += 1;
+        }
+    }
+
+    if contributions == 0 {
+        return Err(report.fail(Failure::Transcript, "Nobody contributed to stage2".to_string()));
     }
 
-    let mut stage3 = Stage3Contents::new(&cs, &stage2);
+    try!(check_batches(report, 2, &batches));
+    let _ = fs::remove_file(&initial.path);
+    report.time("stage2", start);
+
+    let start = Instant::now();
+    let initial = {
+        let stage1: Stage1Contents = read!(report, stage1.decode());
+        let stage2: S::Stage2 = read!(report, stage2.decode());
+
+        spill(format!("{}.stage3", config.transcript), &S::stage3(qap, &stage1, &stage2))
+    };
+    let mut stage3 = initial.clone();
+    let mut contributions = 0;
+    let mut batches = vec![];
+
+    let active = (0..num_players).filter(|&i| roster.is_active(i)).collect();
+    let turns = try!(stage_turns(report, transcript, 3, active, num_players, total));
 
-    for i in 0..num_players {
+    for i in turns {
         let expected_ihash = {
-            let h = digest256_from_parts!(
-                stage3,
-                last_message_hash
-            );
+            let h = read!(report, digest_around(
+                &[],
+                &stage3,
+                &encoded(&last_message_hash)
+            ));
             println!("Player {} hash of disk E: {}", i+1, h.to_string());
+            report.disc(i, "E", &h);
 
             h
         };
 
-        let new_stage: Stage3Contents = decode_from(&mut f, Infinite).unwrap();
-        if !new_stage.verify_transform(&stage3, &pubkeys[i]) {
-            panic!("Invalid stage3 transformation from player {}", i);
+        let pubkey = pubkeys[i].as_ref().unwrap();
+
+        records += 1;
+
+        if i < num_players && transcript.contains(Section::Exclusion(3, i)) {
+            let exclusion: Exclusion<S> = read!(report, decode_from(read!(report, transcript.seek(Section::Exclusion(3, i))), Infinite));
+
+            try!(check_exclusion(config, report, &mut roster, &exclusion, i, 3, |reason| {
+                match *reason {
+                    Misbehavior::InvalidTransform(ref h) => {
+                        check_quarantine(3, i, h, |q| {
+                            Ok(!try!(<S::Stage3 as Stage>::verify_transform_streaming(&mut try!(stage3.open()), q, pubkey, window)))
+                        })
+                    },
+                    Misbehavior::Malformed(kind, ref h) => {
+                        check_quarantine(3, i, h, |q| {
+                            match MessageType::from_kind(kind) {
+                                Some(MessageType::Stage3) => undecodable::<S::Stage3>(q),
+                                Some(MessageType::Ihash) => undecodable::<Digest256>(q),
+                                _ => Ok(false)
+                            }
+                        })
+                    },
+                    Misbehavior::Oversized(length, limit) => {
+                        if length > limit { Evidence::Unconfirmed } else { Evidence::Unsupported }
+                    },
+                    Misbehavior::Unresponsive | Misbehavior::OutOfTime(_) | Misbehavior::Operator => Evidence::Unconfirmed,
+                    _ => Evidence::Unsupported
+                }
+            }));
+
+            continue;
         }
 
-        let ihash: Digest256 = decode_from(&mut f, Infinite).unwrap();
+        let record = try!(find_contribution(report, transcript, &config.transcript, 3, i));
+        let mut f = read!(report, record.open());
 
-        assert!(expected_ihash == ihash);
+        let new_stage = read!(report, record.stage_in(&f));
+        let mut prev = read!(report, stage3.open());
+        let mut batch = Batch::new();
+        let began = Instant::now();
+        let well_formed = match <S::Stage3 as Stage>::verify_transform_batched(&mut prev, &mut f, pubkey, window, &mut batch) {
+            Ok(well_formed) => Some(well_formed),
+            Err(e) => {
+                fail!(report, Failure::Malformed, "Could not decode the stage3 contribution of player {}: {}", i+1, e);
+                None
+            }
+        };
+        metrics.observe(&VERIFY_TRANSFORM_SECONDS, &[("stage", "3")], seconds(began.elapsed()));
+
+        let ihash = if report.check(i, 3, "transformation", well_formed == Some(true)) {
+            batches.push((i, batch));
+            read!(report, read_ihash(&mut f))
+        } else {
+            if well_formed.is_some() {
+                fail!(report, Failure::Transform, "Invalid stage3 transformation from player {}", i+1);
+            }
+            read!(report, record.ihash())
+        };
+
+        if !report.check(i, 3, "ihash", ihash == expected_ihash) {
+            fail!(report, Failure::Chain, "Player {} did not transform the stage3 they were given", i+1);
+        }
 
         {
-            last_message_hash = digest256_from_parts!(
-                new_stage,
-                ihash
-            );
+            last_message_hash = read!(report, record.digest());
             println!("Player {} hash of disk F: {}", i+1, last_message_hash.to_string());
+            report.disc(i, "F", &last_message_hash);
         }
 
         stage3 = new_stage;
+
+        if i < num_players {
+            contributions += 1;
+        }
     }
 
-    let kp = keypair(&cs, &stage1, &stage2, &stage3);
-    kp.write_to_disk();
+    if contributions == 0 {
+        return Err(report.fail(Failure::Transcript, "Nobody contributed to stage3".to_string()));
+    }
+
+    try!(check_batches(report, 3, &batches));
+    let _ = fs::remove_file(&initial.path);
+    report.time("stage3", start);
+
+    if records != transcript.records() {
+        fail!(report, Failure::Transcript, "Transcript contains unexpected records");
+    }
+
+    Ok((stage1, Some((stage2, stage3))))
+}
+
+/// Verifies the transcript, constructing the keypair from it if asked
+/// to.
+fn verify(config: &Config, coordinator_key: &CoordinatorKey, report: &mut Report, metrics: &Metrics, construct_keypair: bool) -> Result<(), Failure> {
+    let mut transcript = read!(report, TranscriptReader::open(read!(report, File::open(&config.transcript))));
+
+    if !transcript.is_complete() {
+        return Err(report.fail(Failure::Transcript, "Transcript is incomplete".to_string()));
+    }
+
+    match transcript.check_signatures(coordinator_key) {
+        Ok(true) => {
+            println!("Transcript is signed by coordinator key {}", coordinator_key.to_hex());
+            report.coordinator_key = Some(coordinator_key.to_hex());
+        },
+        Ok(false) => {
+            fail!(report, Failure::Signature, "The transcript's last record is not signed by the coordinator");
+        },
+        Err(e) => {
+            fail!(report, Failure::Signature, "The transcript is not signed by the coordinator: {}", e);
+        }
+    }
+
+    let ceremony = transcript.header().config.clone();
+    println!("Ceremony minimum players: {}", ceremony.min_players);
+    println!("Ceremony scheme: {}", ceremony.scheme.name());
+
+    // The scheme, and whether the ceremony is a universal one, are
+    // whatever the transcript was made for.
+    let (qap, params, initial_stage1) = match ceremony.powers_of_tau {
+        Some(k) => {
+            println!("Ceremony is universal, for 2^{} powers of tau", k);
+
+            let params = CeremonyParams::universal(k, &ceremony);
+            let stage1 = Stage1Contents::with_powers(params.powers);
+
+            (None, params, stage1)
+        },
+        None => {
+            println!("Ceremony constraint system: {}", ceremony.cs);
+
+            let r1cs = {
+                if config.use_dummy_cs() {
+                    R1CS::dummy()
+                } else {
+                    match R1CS::from_file(&config.cs) {
+                        Ok(r1cs) => r1cs,
+                        Err(e) => return Err(report.fail(Failure::Transcript, format!("Could not load constraint system {}: {}", config.cs, e)))
+                    }
+                }
+            };
+            let qap = match QAP::new(r1cs) {
+                Some(qap) => qap,
+                None => return Err(report.fail(Failure::Transcript, "Constraint system does not reduce to a radix-2 QAP".to_string()))
+            };
+
+            let accumulator = match config.accumulator {
+                Some(ref path) => {
+                    let accumulator = match Accumulator::from_file(path) {
+                        Ok(accumulator) => accumulator,
+                        Err(e) => return Err(report.fail(Failure::Transcript, format!("Could not load accumulator {}: {}", path, e)))
+                    };
+
+                    if !accumulator.is_well_formed() {
+                        return Err(report.fail(Failure::Transcript, format!("Accumulator {} does not contain powers of tau", path)));
+                    }
+
+                    Some(accumulator)
+                },
+                None => None
+            };
+
+            let params = CeremonyParams::new(&qap, accumulator.as_ref(), &Config {
+                scheme: ceremony.scheme,
+                .. config.clone()
+            });
+            let stage1 = match accumulator {
+                Some(ref accumulator) => match accumulator.stage1(params.powers) {
+                    Some(stage1) => stage1,
+                    None => return Err(report.fail(Failure::Transcript, "Accumulator has too few powers of tau for the constraint system".to_string()))
+                },
+                None => Stage1Contents::with_powers(params.powers)
+            };
+
+            (Some(qap), params, stage1)
+        }
+    };
+
+    if transcript.header().params != params {
+        return Err(report.fail(Failure::Transcript, "Transcript is for a different constraint system or accumulator".to_string()));
+    }
+
+    let (stage1, rest) = match params.scheme {
+        ProofSystem::Pghr13 => try!(verify_stages::<Pghr13>(config, report, metrics, &mut transcript, qap.as_ref(), &initial_stage1, &ceremony, &params)),
+        ProofSystem::Groth16 => try!(verify_stages::<Groth16>(config, report, metrics, &mut transcript, qap.as_ref(), &initial_stage1, &ceremony, &params))
+    };
+
+    if let Some(failure) = report.first_failure() {
+        return Err(failure);
+    }
+
+    if !construct_keypair {
+        return Ok(());
+    }
+
+    // Only the final contents of each stage are needed in full, to
+    // construct the keypair, or the accumulator of a universal ceremony.
+    let start = Instant::now();
+    let stage1: Stage1Contents = read!(report, stage1.decode());
+
+    let (stage2, stage3, qap) = match (rest, qap) {
+        (Some((stage2, stage3)), Some(qap)) => (stage2, stage3, qap),
+        _ => {
+            let accumulator = Accumulator::new(stage1);
+            accumulator.to_file("accumulator").unwrap_or_else(|e| {
+                panic!("could not write the accumulator: {}", e)
+            });
+
+            let hash = accumulator.hash();
+            println!("Accumulator hash: {}", hash.to_string());
+            report.accumulator_hash = Some(hash.to_string());
+            report.time("accumulator", start);
+
+            return Ok(());
+        }
+    };
+
+    let (pk_hash, vk_hash) = match params.scheme {
+        ProofSystem::Pghr13 => {
+            let stage2: Stage2Contents = read!(report, stage2.decode());
+            let stage3: Stage3Contents = read!(report, stage3.decode());
+
+            let kp = keypair(&qap, &stage1, &stage2, &stage3);
+            let hashes = kp.write_to_disk("pk", "vk").unwrap_or_else(|e| {
+                panic!("could not write the keypair: {}", e)
+            });
+
+            if config.legacy_keypair {
+                kp.to_libsnark(&qap.r1cs().to_libsnark()).write_to_disk("pk.libsnark", "vk.libsnark");
+            }
+
+            hashes
+        },
+        ProofSystem::Groth16 => {
+            let stage2: groth16::Stage2Contents = read!(report, stage2.decode());
+            let stage3: groth16::Stage3Contents = read!(report, stage3.decode());
+
+            if config.legacy_keypair {
+                println!("libsnark has no Groth16 keypair, so there is no legacy keypair to write");
+            }
+
+            groth16::keypair(&qap, &stage1, &stage2, &stage3).write_to_disk("pk", "vk").unwrap_or_else(|e| {
+                panic!("could not write the keypair: {}", e)
+            })
+        }
+    };
+
+    println!("Proving key hash: {}", pk_hash.to_string());
+    println!("Verifying key hash: {}", vk_hash.to_string());
+    report.pk_hash = Some(pk_hash.to_string());
+    report.vk_hash = Some(vk_hash.to_string());
+
+    report.time("keypair", start);
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (attestation, args) = if args.first().map(|a| &a[..]) == Some("attest") {
+        match Attestation::from_args(&args[1..]) {
+            Ok((attestation, rest)) => (Some(attestation), rest),
+            Err(e) => {
+                println!("verifier attest: {}\n\n{}", e, USAGE);
+                process::exit(2);
+            }
+        }
+    } else {
+        (None, args)
+    };
+
+    let config = Config::load_from("verifier", Config {
+        threads: THREADS,
+        .. Config::default()
+    }, &args);
+
+    set_threads(config.threads);
+
+    // Validated along with the rest of the config, if it's given. A
+    // transcript can't be told apart from one somebody else altered and
+    // signed again without it.
+    let coordinator_key = match config.coordinator_key {
+        Some(ref key) => CoordinatorKey::from_hex(key).unwrap(),
+        None => {
+            println!("The coordinator's published key is needed to check the transcript's signatures; pass it with --coordinator-key <hex>.");
+            process::exit(2);
+        }
+    };
+
+    // An attestation is judged on everything that is wrong with the
+    // transcript, not only the first thing.
+    let collect_all = config.collect_all || attestation.is_some();
+    let mut report = Report::new(&config.transcript, &config.cs, collect_all);
+
+    let start = Instant::now();
+    let metrics = Metrics::new();
+    let result = verify(&config, &coordinator_key, &mut report, &metrics, attestation.is_none());
+    report.time("total", start);
+    report.valid = result.is_ok();
+
+    let attested = attestation.map(|a| a.check(&mut report, &config.transcript));
+
+    if let Some(ref path) = config.report {
+        if let Err(e) = report.write(path) {
+            println!("Could not write report {}: {}", path, e);
+        }
+    }
+
+    if let Some(ref path) = config.metrics {
+        if let Err(e) = metrics.dump(path) {
+            println!("Could not write metrics {}: {}", path, e);
+        }
+    }
+
+    if result.is_ok() {
+        println!("Transcript is valid");
+    }
+
+    if attested == Some(true) {
+        println!("Attestation holds");
+    }
+
+    if let Some(failure) = report.first_failure() {
+        process::exit(failure.exit_code());
+    }
+}
+
+#[test]
+fn beacon_is_fixed_by_the_header() {
+    use std::io::Cursor;
+
+    let read = |ceremony: &Config, beacon: Option<&Beacon>| {
+        let header = TranscriptHeader {
+            config: ceremony.clone(),
+            params: CeremonyParams::universal(4, ceremony)
+        };
+
+        let mut writer = TranscriptWriter::create(Cursor::new(vec![]), &header, SigningKey::new(&mut rand::thread_rng())).unwrap();
+        writer.record(Section::Commitments, |w| encode_into(&Vec::<Digest256>::new(), w, Infinite)).unwrap();
+        if let Some(beacon) = beacon {
+            writer.record(Section::Beacon, |w| encode_into(beacon, w, Infinite)).unwrap();
+        }
+
+        let mut transcript = TranscriptReader::open(Cursor::new(writer.finish().unwrap().into_inner())).unwrap();
+
+        read_beacon(&mut Report::new("test", "test", false), &mut transcript, ceremony)
+    };
+
+    let mut with = Config::default();
+    with.beacon = true;
+    with.beacon_iterations = 1000;
+    let without = Config::default();
+
+    let beacon = Beacon::from_hex("00000000000000000004a1b2c3d4e5f6", 1000).unwrap();
+    assert_eq!(read(&with, Some(&beacon)), Ok(Some(beacon.clone())));
+    assert_eq!(read(&without, None), Ok(None));
+
+    // The coordinator can't grind the beacon's iterations, drop it, or
+    // add one the players didn't agree to.
+    assert_eq!(read(&with, Some(&Beacon { iterations: 999, .. beacon.clone() })), Err(Failure::Transcript));
+    assert_eq!(read(&with, None), Err(Failure::Transcript));
+    assert_eq!(read(&without, Some(&beacon)), Err(Failure::Transcript));
 }
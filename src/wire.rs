@@ -0,0 +1,607 @@
+//! The messages the network machines and the coordinator exchange over
+//! the channel, which are sent in frames. All integers are little endian.
+//!
+//! ```text
+//! frame: kind u8 | seq u64 | length u64 | checksum [u8; 8] | payload [u8; length]
+//! ```
+//!
+//! The checksum is the start of the blake2s hash of everything before it
+//! and the payload. Frames are one of:
+//!
+//! * a message, whose kind names what it contains and whose payload is
+//!   the bincode encoding of it,
+//! * `ACK`, acknowledging message `seq`,
+//! * `ERROR`, saying why message `seq` was rejected, in its payload,
+//! * `ABORT`, saying why the sender is hanging up for good,
+//! * `STATUS`, telling a player waiting for their turn where they are in
+//!   the queue, in its payload, or
+//! * `HEARTBEAT`, which a network machine sends every `HEARTBEAT_INTERVAL`
+//!   seconds while its player is busy with the compute machine, so that
+//!   the coordinator can tell a player who is taking their time from one
+//!   who has gone. The coordinator sends them as often to players waiting
+//!   for their turn, so that their reads don't time out.
+//!
+//! Status and heartbeat frames are neither numbered nor acknowledged.
+//!
+//! Both ends number the messages they exchange in either direction, so
+//! `seq` is always one more than the number of messages before it. After
+//! a reconnect, the handshake tells each end how many messages the other
+//! has received, so it knows where to pick up, and a message received
+//! twice is acknowledged again but otherwise ignored. A message out of
+//! sequence, or of a kind other than the one expected, means the ends
+//! disagree on where they are in the ceremony, which retrying can't fix,
+//! so it is answered with an `ERROR` instead.
+//!
+//! Every read is given a limit on the length of the frame it expects,
+//! which is checked before any of the payload is read. The payload is
+//! then read into a buffer that grows as it arrives, so a length field
+//! alone can't make the receiver allocate anything.
+
+use std::io::{self, Read, Write};
+use std::fmt;
+use std::time::Instant;
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt, LittleEndian};
+use blake2_rfc::blake2s::Blake2s;
+use bincode::SizeLimit::Infinite;
+use bincode::rustc_serialize::{encode, decode, EncodingError, DecodingError};
+use rustc_serialize::{Encodable, Decodable};
+
+const KIND_ACK: u8 = 0;
+const KIND_ERROR: u8 = 1;
+const KIND_ABORT: u8 = 2;
+const KIND_STATUS: u8 = 3;
+const KIND_HEARTBEAT: u8 = 4;
+
+/// Frames longer than this can only be garbage. Network machines accept
+/// stage contents up to this long from the coordinator, whose key they
+/// have pinned.
+pub const MAX_PAYLOAD: u64 = 1 << 36;
+
+/// Limit for every message other than the contents of a stage, whose
+/// limit is the size of the contents that were sent out, and for the
+/// frames that answer a message.
+pub const MAX_MESSAGE: u64 = 1 << 20;
+
+/// What a message contains.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageType {
+    Commitment,
+    Availability,
+    HashOfCommitments,
+    Stage1,
+    Stage2,
+    Stage3,
+    LastMessageHash,
+    PublicKey,
+    PublicKeyNizks,
+    Ihash
+}
+
+impl MessageType {
+    /// The frame kind of the message, which also names it in exclusion
+    /// records.
+    pub fn kind(&self) -> u8 {
+        match *self {
+            MessageType::Commitment => 16,
+            MessageType::HashOfCommitments => 17,
+            MessageType::Stage1 => 18,
+            MessageType::Stage2 => 19,
+            MessageType::Stage3 => 20,
+            MessageType::LastMessageHash => 21,
+            MessageType::PublicKey => 22,
+            MessageType::PublicKeyNizks => 23,
+            MessageType::Ihash => 24,
+            MessageType::Availability => 25
+        }
+    }
+
+    pub fn from_kind(kind: u8) -> Option<MessageType> {
+        match kind {
+            16 => Some(MessageType::Commitment),
+            17 => Some(MessageType::HashOfCommitments),
+            18 => Some(MessageType::Stage1),
+            19 => Some(MessageType::Stage2),
+            20 => Some(MessageType::Stage3),
+            21 => Some(MessageType::LastMessageHash),
+            22 => Some(MessageType::PublicKey),
+            23 => Some(MessageType::PublicKeyNizks),
+            24 => Some(MessageType::Ihash),
+            25 => Some(MessageType::Availability),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MessageType::Commitment => "commitment",
+            MessageType::Availability => "availability",
+            MessageType::HashOfCommitments => "hash of commitments",
+            MessageType::Stage1 => "stage1",
+            MessageType::Stage2 => "stage2",
+            MessageType::Stage3 => "stage3",
+            MessageType::LastMessageHash => "last message hash",
+            MessageType::PublicKey => "public key",
+            MessageType::PublicKeyNizks => "nizks",
+            MessageType::Ihash => "ihash"
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    Message(MessageType),
+    Ack,
+    Error,
+    Abort,
+    Status,
+    Heartbeat
+}
+
+impl Kind {
+    fn to_u8(&self) -> u8 {
+        match *self {
+            Kind::Message(ty) => ty.kind(),
+            Kind::Ack => KIND_ACK,
+            Kind::Error => KIND_ERROR,
+            Kind::Abort => KIND_ABORT,
+            Kind::Status => KIND_STATUS,
+            Kind::Heartbeat => KIND_HEARTBEAT
+        }
+    }
+
+    fn from_u8(kind: u8) -> Option<Kind> {
+        match kind {
+            KIND_ACK => Some(Kind::Ack),
+            KIND_ERROR => Some(Kind::Error),
+            KIND_ABORT => Some(Kind::Abort),
+            KIND_STATUS => Some(Kind::Status),
+            KIND_HEARTBEAT => Some(Kind::Heartbeat),
+            _ => MessageType::from_kind(kind).map(Kind::Message)
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            Kind::Message(ty) => ty.name(),
+            Kind::Ack => "ack",
+            Kind::Error => "error",
+            Kind::Abort => "abort",
+            Kind::Status => "status",
+            Kind::Heartbeat => "heartbeat"
+        }
+    }
+}
+
+pub struct Frame {
+    pub kind: Kind,
+    pub seq: u64,
+    pub payload: Vec<u8>
+}
+
+impl Frame {
+    fn checksum(kind: u8, seq: u64, payload: &[u8]) -> [u8; 8] {
+        let mut header = [0; 17];
+        header[0] = kind;
+        LittleEndian::write_u64(&mut header[1..9], seq);
+        LittleEndian::write_u64(&mut header[9..17], payload.len() as u64);
+
+        let mut h = Blake2s::new(32);
+        h.update(&header);
+        h.update(payload);
+
+        let mut checksum = [0; 8];
+        checksum.copy_from_slice(&h.finalize().as_bytes()[0..8]);
+
+        checksum
+    }
+
+    /// Reads a frame whose payload is at most `limit` bytes long.
+    pub fn read<R: Read>(r: &mut R, limit: u64) -> Result<Frame, WireError> {
+        let kind = try!(r.read_u8());
+        let seq = try!(r.read_u64::<LittleEndian>());
+        let length = try!(r.read_u64::<LittleEndian>());
+        let mut checksum = [0; 8];
+        try!(r.read_exact(&mut checksum));
+
+        if length > limit {
+            return Err(WireError::TooLong(length, limit));
+        }
+
+        let mut payload = vec![];
+        try!(r.by_ref().take(length).read_to_end(&mut payload));
+
+        if payload.len() as u64 != length {
+            return Err(WireError::Io(io::Error::new(io::ErrorKind::UnexpectedEof, "frame is truncated")));
+        }
+
+        if checksum != Frame::checksum(kind, seq, &payload) {
+            return Err(WireError::Corrupt);
+        }
+
+        let kind = try!(Kind::from_u8(kind).ok_or(WireError::UnknownKind(kind)));
+
+        Ok(Frame {
+            kind: kind,
+            seq: seq,
+            payload: payload
+        })
+    }
+
+    /// Writes and flushes the frame.
+    pub fn write<W: Write>(&self, w: &mut W) -> Result<(), WireError> {
+        let kind = self.kind.to_u8();
+
+        try!(w.write_u8(kind));
+        try!(w.write_u64::<LittleEndian>(self.seq));
+        try!(w.write_u64::<LittleEndian>(self.payload.len() as u64));
+        try!(w.write_all(&Frame::checksum(kind, self.seq, &self.payload)));
+        try!(w.write_all(&self.payload));
+        try!(w.flush());
+
+        Ok(())
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.payload).into_owned()
+    }
+}
+
+/// Sends `obj` as message `seq`, and waits for it to be acknowledged.
+pub fn send<C: Read + Write, E: Encodable>(c: &mut C, ty: MessageType, seq: u64, obj: &E) -> Result<(), WireError> {
+    let frame = Frame {
+        kind: Kind::Message(ty),
+        seq: seq,
+        payload: try!(encode(obj, Infinite))
+    };
+
+    try!(frame.write(c));
+
+    loop {
+        let frame = try!(Frame::read(c, MAX_MESSAGE));
+
+        match frame.kind {
+            Kind::Ack if frame.seq == seq => return Ok(()),
+            // Acknowledges a message we sent before a reconnect.
+            Kind::Ack if frame.seq < seq => continue,
+            Kind::Ack => return Err(WireError::OutOfSequence(seq, frame.seq)),
+            Kind::Error => return Err(WireError::Rejected(frame.text())),
+            Kind::Abort => return Err(WireError::Aborted(frame.text())),
+            Kind::Status | Kind::Heartbeat => continue,
+            Kind::Message(got) => {
+                let e = WireError::Unexpected(Kind::Ack, Kind::Message(got));
+                report(c, seq, &e);
+
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// Receives message `seq`, which must contain a `ty` encoded in at most
+/// `limit` bytes, and acknowledges it. Anything else is reported to the
+/// sender.
+pub fn receive<C: Read + Write, D: Decodable>(c: &mut C, ty: MessageType, seq: u64, limit: u64) -> Result<D, WireError> {
+    receive_frames(c, ty, seq, limit, None, |_| ())
+}
+
+/// Like `receive`, but passes the text of any status frames received in
+/// the meantime to `on_status`.
+pub fn receive_with_status<C, D, F>(c: &mut C, ty: MessageType, seq: u64, limit: u64, on_status: F) -> Result<D, WireError>
+    where C: Read + Write, D: Decodable, F: FnMut(String)
+{
+    receive_frames(c, ty, seq, limit, None, on_status)
+}
+
+/// Like `receive`, but gives up once `deadline` has passed. Since the
+/// sender keeps sending heartbeats while it's busy, this is noticed even
+/// though reads don't time out.
+pub fn receive_before<C: Read + Write, D: Decodable>(c: &mut C, ty: MessageType, seq: u64, limit: u64, deadline: Instant) -> Result<D, WireError> {
+    receive_frames(c, ty, seq, limit, Some(deadline), |_| ())
+}
+
+fn receive_frames<C, D, F>(c: &mut C, ty: MessageType, seq: u64, limit: u64, deadline: Option<Instant>, mut on_status: F) -> Result<D, WireError>
+    where C: Read + Write, D: Decodable, F: FnMut(String)
+{
+    loop {
+        if deadline.map(|d| Instant::now() >= d).unwrap_or(false) {
+            return Err(WireError::Deadline);
+        }
+
+        let frame = try!(Frame::read(c, limit));
+
+        let e = match frame.kind {
+            Kind::Message(_) if frame.seq < seq => {
+                // Sent again after a reconnect, before the sender knew we
+                // had it.
+                try!(ack(c, frame.seq));
+                continue;
+            },
+            Kind::Message(_) if frame.seq > seq => WireError::OutOfSequence(seq, frame.seq),
+            Kind::Message(got) if got != ty => WireError::Unexpected(Kind::Message(ty), Kind::Message(got)),
+            Kind::Message(_) => {
+                match decode(&frame.payload) {
+                    Ok(obj) => {
+                        try!(ack(c, seq));
+
+                        return Ok(obj);
+                    },
+                    Err(e) => WireError::Malformed(ty, e, frame.payload)
+                }
+            },
+            // Stale, from before a reconnect.
+            Kind::Ack => continue,
+            Kind::Status => {
+                on_status(frame.text());
+                continue;
+            },
+            Kind::Heartbeat => continue,
+            Kind::Error => return Err(WireError::Rejected(frame.text())),
+            Kind::Abort => return Err(WireError::Aborted(frame.text()))
+        };
+
+        report(c, seq, &e);
+
+        return Err(e);
+    }
+}
+
+fn ack<W: Write>(w: &mut W, seq: u64) -> Result<(), WireError> {
+    Frame {
+        kind: Kind::Ack,
+        seq: seq,
+        payload: vec![]
+    }.write(w)
+}
+
+/// Tells the other end why we couldn't accept message `seq`, if it's
+/// still listening.
+pub fn report<W: Write>(w: &mut W, seq: u64, e: &WireError) {
+    let _ = Frame {
+        kind: Kind::Error,
+        seq: seq,
+        payload: e.to_string().into_bytes()
+    }.write(w);
+}
+
+/// Tells a player waiting for their turn how it's going.
+pub fn status<W: Write>(w: &mut W, text: &str) -> Result<(), WireError> {
+    Frame {
+        kind: Kind::Status,
+        seq: 0,
+        payload: text.as_bytes().to_vec()
+    }.write(w)
+}
+
+/// Tells the other end we're still there: the coordinator, that we're
+/// busy with our turn, or a player, that we haven't forgotten them while
+/// they wait for theirs.
+pub fn heartbeat<W: Write>(w: &mut W) -> Result<(), WireError> {
+    Frame {
+        kind: Kind::Heartbeat,
+        seq: 0,
+        payload: vec![]
+    }.write(w)
+}
+
+/// Tells the other end we're hanging up for good, and why.
+pub fn abort<W: Write>(w: &mut W, reason: &str) {
+    let _ = Frame {
+        kind: Kind::Abort,
+        seq: 0,
+        payload: reason.as_bytes().to_vec()
+    }.write(w);
+}
+
+#[derive(Debug)]
+pub enum WireError {
+    Io(io::Error),
+    Encoding(EncodingError),
+    Decoding(DecodingError),
+    /// The frame's checksum doesn't match.
+    Corrupt,
+    /// The frame is `.0` bytes long, more than the limit of `.1`, so it
+    /// wasn't read.
+    TooLong(u64, u64),
+    /// The payload of a `.0` message doesn't decode as one. It is kept
+    /// as evidence against the sender.
+    Malformed(MessageType, DecodingError, Vec<u8>),
+    UnknownKind(u8),
+    /// Expected message `.0`, but got `.1`.
+    OutOfSequence(u64, u64),
+    /// Expected `.0`, but got `.1`.
+    Unexpected(Kind, Kind),
+    /// The other end couldn't accept our message.
+    Rejected(String),
+    /// The other end hung up for good.
+    Aborted(String),
+    /// We stopped waiting for a message.
+    Deadline
+}
+
+impl WireError {
+    /// Whether sending or receiving again, over the same connection or a
+    /// new one, can't help.
+    pub fn is_fatal(&self) -> bool {
+        match *self {
+            WireError::Io(_) | WireError::Corrupt | WireError::Deadline => false,
+            _ => true
+        }
+    }
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WireError::Io(ref e) => write!(f, "i/o error: {}", e),
+            WireError::Encoding(ref e) => write!(f, "encoding error: {}", e),
+            WireError::Decoding(ref e) => write!(f, "decoding error: {}", e),
+            WireError::Corrupt => write!(f, "corrupt frame"),
+            WireError::TooLong(length, limit) => write!(f, "frame of {} bytes is longer than the limit of {}", length, limit),
+            WireError::Malformed(ty, ref e, _) => write!(f, "malformed {}: {}", ty.name(), e),
+            WireError::UnknownKind(kind) => write!(f, "unknown frame kind {}", kind),
+            WireError::OutOfSequence(expected, got) => write!(f, "expected message {}, got message {}", expected, got),
+            WireError::Unexpected(expected, got) => write!(f, "expected {}, got {}", expected.name(), got.name()),
+            WireError::Rejected(ref e) => write!(f, "rejected by the other end: {}", e),
+            WireError::Aborted(ref e) => write!(f, "aborted by the other end: {}", e),
+            WireError::Deadline => write!(f, "deadline passed")
+        }
+    }
+}
+
+impl From<io::Error> for WireError {
+    fn from(e: io::Error) -> WireError {
+        WireError::Io(e)
+    }
+}
+
+impl From<EncodingError> for WireError {
+    fn from(e: EncodingError) -> WireError {
+        WireError::Encoding(e)
+    }
+}
+
+impl From<DecodingError> for WireError {
+    fn from(e: DecodingError) -> WireError {
+        WireError::Decoding(e)
+    }
+}
+
+#[test]
+fn wire_protocol() {
+    use std::thread;
+    use std::time::Duration;
+    use transport::{Transport, Listener, Memory};
+
+    let transport = Memory::new();
+    let listener = transport.listen("coordinator").unwrap();
+
+    let remote = transport.clone();
+    let player = thread::spawn(move || {
+        let mut c = remote.connect("coordinator").unwrap();
+
+        let greeting: String = receive(&mut c, MessageType::Commitment, 1, MAX_MESSAGE).unwrap();
+        send(&mut c, MessageType::Ihash, 2, &greeting.len()).unwrap();
+
+        // The coordinator sends message 3 twice, as if it hadn't seen
+        // our ack, and then sends message 4.
+        let n: u64 = receive(&mut c, MessageType::Stage1, 3, MAX_MESSAGE).unwrap();
+        let m: u64 = receive(&mut c, MessageType::Stage1, 4, MAX_MESSAGE).unwrap();
+        assert_eq!((n, m), (3, 4));
+
+        // Status frames may arrive while we wait.
+        let mut statuses = vec![];
+        let k: u64 = receive_with_status(&mut c, MessageType::Stage1, 5, MAX_MESSAGE, |text| statuses.push(text)).unwrap();
+        assert_eq!(k, 5);
+        assert_eq!(statuses, vec!["you're next".to_string()]);
+
+        // Then it skips ahead.
+        receive::<_, u64>(&mut c, MessageType::Stage2, 6, MAX_MESSAGE)
+    });
+
+    let mut c = listener.accept().unwrap();
+
+    send(&mut c, MessageType::Commitment, 1, &"hello".to_string()).unwrap();
+    let len: usize = receive(&mut c, MessageType::Ihash, 2, MAX_MESSAGE).unwrap();
+    assert_eq!(len, 5);
+
+    send(&mut c, MessageType::Stage1, 3, &3u64).unwrap();
+    send(&mut c, MessageType::Stage1, 3, &3u64).unwrap();
+    send(&mut c, MessageType::Stage1, 4, &4u64).unwrap();
+
+    status(&mut c, "you're next").unwrap();
+    send(&mut c, MessageType::Stage1, 5, &5u64).unwrap();
+
+    match send(&mut c, MessageType::Stage2, 7, &7u64) {
+        Err(WireError::Rejected(_)) => {},
+        _ => panic!("message out of sequence was accepted")
+    }
+
+    match player.join().unwrap() {
+        Err(ref e @ WireError::OutOfSequence(6, 7)) => assert!(e.is_fatal()),
+        _ => panic!("message out of sequence was accepted")
+    }
+
+    // A message of the wrong type is reported too.
+    let mut a = transport.connect("coordinator").unwrap();
+    let mut b = listener.accept().unwrap();
+
+    let receiver = thread::spawn(move || {
+        let result = receive::<_, u64>(&mut b, MessageType::Stage3, 1, MAX_MESSAGE);
+
+        abort(&mut b, "excluded");
+
+        result
+    });
+
+    match send(&mut a, MessageType::Stage2, 1, &1u64) {
+        Err(WireError::Rejected(ref e)) => assert_eq!(e, "expected stage3, got stage2"),
+        _ => panic!("message of the wrong type was accepted")
+    }
+
+    match receiver.join().unwrap() {
+        Err(WireError::Unexpected(Kind::Message(MessageType::Stage3), Kind::Message(MessageType::Stage2))) => {},
+        _ => panic!("message of the wrong type was accepted")
+    }
+
+    match receive::<_, u64>(&mut a, MessageType::Stage2, 2, MAX_MESSAGE) {
+        Err(WireError::Aborted(ref e)) => assert_eq!(e, "excluded"),
+        _ => panic!("abort was not received")
+    }
+
+    // Heartbeats are skipped, but don't keep us waiting past a deadline.
+    let mut a = transport.connect("coordinator").unwrap();
+    let mut b = listener.accept().unwrap();
+
+    heartbeat(&mut a).unwrap();
+    Frame { kind: Kind::Message(MessageType::Ihash), seq: 1, payload: encode(&1u64, Infinite).unwrap() }.write(&mut a).unwrap();
+    let n: u64 = receive_before(&mut b, MessageType::Ihash, 1, MAX_MESSAGE, Instant::now() + Duration::from_secs(60)).unwrap();
+    assert_eq!(n, 1);
+
+    heartbeat(&mut a).unwrap();
+    match receive_before::<_, u64>(&mut b, MessageType::Ihash, 2, MAX_MESSAGE, Instant::now()) {
+        Err(ref e @ WireError::Deadline) => assert!(!e.is_fatal()),
+        _ => panic!("deadline was not noticed")
+    }
+
+    // Corruption is caught by the checksum.
+    let mut frame = vec![];
+    Frame { kind: Kind::Ack, seq: 7, payload: vec![1, 2, 3] }.write(&mut frame).unwrap();
+    assert!(Frame::read(&mut &frame[..], MAX_MESSAGE).is_ok());
+    frame[1] ^= 1;
+    match Frame::read(&mut &frame[..], MAX_MESSAGE) {
+        Err(WireError::Corrupt) => {},
+        _ => panic!("corrupt frame was accepted")
+    }
+
+    // A frame over the limit is turned away before its payload is read,
+    // however long it claims to be.
+    let mut frame = vec![];
+    Frame { kind: Kind::Ack, seq: 7, payload: vec![1, 2, 3] }.write(&mut frame).unwrap();
+    LittleEndian::write_u64(&mut frame[9..17], !0);
+    match Frame::read(&mut &frame[..], MAX_MESSAGE) {
+        Err(ref e @ WireError::TooLong(length, MAX_MESSAGE)) => {
+            assert_eq!(length, !0);
+            assert!(e.is_fatal());
+        },
+        _ => panic!("frame over the limit was read")
+    }
+
+    let mut a = transport.connect("coordinator").unwrap();
+    let mut b = listener.accept().unwrap();
+
+    Frame { kind: Kind::Message(MessageType::Stage1), seq: 1, payload: vec![0; 100] }.write(&mut a).unwrap();
+    match receive::<_, u64>(&mut b, MessageType::Stage1, 1, 99) {
+        Err(WireError::TooLong(100, 99)) => {},
+        _ => panic!("message over the limit was read")
+    }
+
+    // A message that doesn't decode is kept, as evidence.
+    let mut a = transport.connect("coordinator").unwrap();
+    let mut b = listener.accept().unwrap();
+
+    Frame { kind: Kind::Message(MessageType::Ihash), seq: 1, payload: vec![1, 2, 3] }.write(&mut a).unwrap();
+    match receive::<_, u64>(&mut b, MessageType::Ihash, 1, MAX_MESSAGE) {
+        Err(WireError::Malformed(MessageType::Ihash, _, ref payload)) => assert_eq!(payload, &vec![1, 2, 3]),
+        _ => panic!("malformed message was accepted")
+    }
+}